@@ -1,21 +1,32 @@
-use crate::{AppState, AppConfig, LLMProvider, Thread, AgentState, ThreadConfig};
+use crate::{default_data_root, AppState, CONFIG_VERSION, ExecutionMode, AppConfig, LLMProvider, ModelPrice, Thread, AgentState, ThreadConfig};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::fs;
+use std::io::Write;
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 use tauri::{State, Manager};
 use tokio::process::{Command as TokioCommand};
+use tokio::sync::Semaphore;
+use futures::stream::{self, StreamExt};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateThreadRequest {
     pub name: String,
     pub working_dir: PathBuf,
-    pub planner_llm_alias: String,
-    pub decider_llm_alias: String,
+    // Omitted or empty aliases fall back to the app-wide defaults
+    #[serde(default)]
+    pub planner_llm_alias: Option<String>,
+    #[serde(default)]
+    pub decider_llm_alias: Option<String>,
     pub selected_knowledge: Vec<String>,
     pub selected_guides: Vec<String>,
     pub selected_actions: Vec<String>,
+    #[serde(default)]
+    pub execution_mode: ExecutionMode,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -26,6 +37,9 @@ pub struct DirectoryPermissionResult {
     pub writable: bool,
     pub is_directory: bool,
     pub error: Option<String>,
+    // First subpath found unreadable or unwritable by a recursive check
+    #[serde(default)]
+    pub inaccessible_path: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -34,6 +48,9 @@ pub struct AgentAskRequest {
     pub text: String,
     pub files: Vec<String>,
     pub execution_mode: String,
+    // Run this request somewhere other than the thread's working_dir; the thread is unchanged
+    #[serde(default)]
+    pub working_dir_override: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -52,7 +69,7 @@ pub struct NodeAgentResponse {
 
 // Initialize data directory structure
 fn init_data_dir(data_root: &PathBuf) -> Result<(), String> {
-    let dirs = ["guides", "knowledge", "actions", "saves", "logs"];
+    let dirs = ["guides", "knowledge", "actions", "saves", "logs", "themes", "locales"];
     for dir in &dirs {
         let dir_path = data_root.join(dir);
         if !dir_path.exists() {
@@ -232,18 +249,442 @@ pub async fn get_config(
     Ok(config)
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EffectiveConfig {
+    // The config in effect, API keys masked
+    pub config: serde_json::Value,
+    // Top-level field -> "default", "file" or "env"
+    pub sources: BTreeMap<String, String>,
+}
+
+#[tauri::command]
+pub async fn get_effective_config(
+    state: State<'_, AppState>,
+) -> Result<EffectiveConfig, String> {
+    let mut config = state.config.lock().unwrap().clone();
+    config.debug_llm = config.debug_llm || debug_llm_from_env();
+
+    // Keys present in configs.json count as coming from the file
+    let file_keys: HashSet<String> = read_resource_text(config.data_root.join("configs.json")).ok()
+        .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+        .and_then(|raw| raw.as_object().map(|map| map.keys().cloned().collect()))
+        .unwrap_or_default();
+
+    let mut value = serde_json::to_value(&config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    for provider in value["llm_providers"].as_array_mut().into_iter().flatten() {
+        if provider["api_key"].as_str().is_some_and(|key| !key.is_empty()) {
+            provider["api_key"] = serde_json::json!("***");
+        }
+        // Header values are often tokens too
+        for header in provider["headers"].as_object_mut().into_iter().flat_map(|headers| headers.values_mut()) {
+            *header = serde_json::json!("***");
+        }
+    }
+
+    let sources = value.as_object().into_iter().flatten()
+        .map(|(field, _)| {
+            let source = match field.as_str() {
+                "data_root" if data_root_from_env().is_some() => "env",
+                "data_root" if load_data_root_override().is_some() => "file",
+                "data_root" => "default",
+                "debug_llm" if debug_llm_from_env() => "env",
+                field if file_keys.contains(field) => "file",
+                _ => "default",
+            };
+            (field.clone(), source.to_string())
+        })
+        .collect();
+
+    Ok(EffectiveConfig { config: value, sources })
+}
+
 #[tauri::command]
 pub async fn initialize_data_directory(
     state: State<'_, AppState>,
 ) -> Result<String, String> {
+    ensure_writable(&state)?;
     let data_root = &state.config.lock().unwrap().data_root;
     init_default_data(data_root)?;
     Ok(format!("Data directory initialized: {:?}", data_root))
 }
 
+// Open a file or directory with the OS file manager / default application. This goes through
+// the OS directly rather than Tauri's shell API, whose scope is shared with the webview and only
+// allows URLs
+fn open_in_file_manager(path: &Path) -> Result<(), String> {
+    let program = if cfg!(target_os = "windows") {
+        "explorer"
+    } else if cfg!(target_os = "macos") {
+        "open"
+    } else {
+        "xdg-open"
+    };
+
+    Command::new(program)
+        .arg(path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("Failed to open {:?}: {}", path, e))?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_data_root(
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    Ok(data_root.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn open_data_dir(
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    if !data_root.exists() {
+        fs::create_dir_all(&data_root)
+            .map_err(|e| format!("Failed to create data directory: {}", e))?;
+    }
+
+    open_in_file_manager(&data_root)?;
+    Ok(data_root.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn reveal_resource(
+    kind: String,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let dir = resource_kind_dir(&kind)?;
+
+    // Guides and knowledge are single files; an action is its directory
+    let path = if kind == "action" {
+        if name.contains("..") || name.contains("/") || name.contains("\\") {
+            return Err("Invalid action name".to_string());
+        }
+        let action_dir = data_root.join(dir).join(&name);
+        if !action_dir.is_dir() {
+            return Err(format!("Action '{}' not found", name));
+        }
+        action_dir
+    } else {
+        existing_resource_path(&data_root, dir, &name)?
+    };
+
+    open_in_file_manager(&path)?;
+    Ok(path.to_string_lossy().to_string())
+}
+
+// The file behind a resource: the guide/knowledge document itself, or an action's meta.json
+fn resource_document_path(data_root: &Path, kind: &str, name: &str) -> Result<PathBuf, String> {
+    let dir = resource_kind_dir(kind)?;
+    if kind != "action" {
+        return existing_resource_path(data_root, dir, name);
+    }
+
+    if name.contains("..") || name.contains("/") || name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+    let meta_path = data_root.join(dir).join(name).join("meta.json");
+    if !meta_path.is_file() {
+        return Err(format!("Action '{}' not found", name));
+    }
+    Ok(meta_path)
+}
+
+#[tauri::command]
+pub async fn read_resource_raw(
+    kind: String,
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let path = resource_document_path(&data_root, &kind, &name)?;
+
+    // Compressed files are returned decompressed, otherwise byte for byte
+    read_resource_text(&path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))
+}
+
+// Where and why JSON text failed to parse; line and column are 1-based as in serde_json
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonSyntaxError {
+    pub message: String,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl JsonSyntaxError {
+    fn describe(&self) -> String {
+        format!("Invalid JSON at line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+fn parse_json_text(content: &str) -> Result<serde_json::Value, JsonSyntaxError> {
+    serde_json::from_str(content).map_err(|e| {
+        // serde_json appends the position to its message; it's reported separately here
+        let position = format!(" at line {} column {}", e.line(), e.column());
+        let message = e.to_string();
+        JsonSyntaxError {
+            message: message.strip_suffix(&position).unwrap_or(&message).to_string(),
+            line: e.line(),
+            column: e.column(),
+        }
+    })
+}
+
+fn validate_resource_document(kind: &str, document: &serde_json::Value) -> Result<(), String> {
+    validate_resource_document_with(kind, document, StructureOptions::default())
+}
+
+fn validate_resource_document_with(kind: &str, document: &serde_json::Value, options: StructureOptions) -> Result<(), String> {
+    match kind {
+        "guide" => validate_guide_structure_with(document, options),
+        "knowledge" => validate_knowledge_structure_with(document, options),
+        "action" => validate_action_meta_structure(document).map_or(Ok(()), Err),
+        _ => resource_kind_dir(kind).map(|_| ()),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct JsonCheck {
+    pub valid: bool,
+    // Set when the text isn't JSON at all, so the editor can mark the spot
+    pub syntax_error: Option<JsonSyntaxError>,
+    // Set when the JSON parses but doesn't match the resource schema
+    pub schema_error: Option<String>,
+}
+
+#[tauri::command]
+pub async fn validate_resource_json(
+    kind: String,
+    content: String,
+    unique_entry_names: Option<bool>,
+) -> Result<JsonCheck, String> {
+    resource_kind_dir(&kind)?;
+    let options = StructureOptions { unique_entry_names: unique_entry_names.unwrap_or(false) };
+
+    let (syntax_error, schema_error) = match parse_json_text(&content) {
+        Ok(document) => (None, validate_resource_document_with(&kind, &document, options).err()),
+        Err(e) => (Some(e), None),
+    };
+
+    Ok(JsonCheck {
+        valid: syntax_error.is_none() && schema_error.is_none(),
+        syntax_error,
+        schema_error,
+    })
+}
+
+#[tauri::command]
+pub async fn write_resource_raw(
+    kind: String,
+    name: String,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let path = resource_document_path(data_root, &kind, &name)?;
+
+    let document = parse_json_text(&content).map_err(|e| e.describe())?;
+    validate_resource_document(&kind, &document)?;
+    // Renaming would leave the directory and meta.json disagreeing
+    if kind == "action" && document["name"].as_str() != Some(name.as_str()) {
+        return Err(format!("meta.json name must stay '{}'", name));
+    }
+
+    // Save the text as given, keeping the file in the format it was stored in
+    let bytes = encode_resource(&path, &content)
+        .map_err(|e| format!("Failed to compress {:?}: {}", path, e))?;
+    write_atomic(&path, bytes)?;
+
+    println!("Raw {} saved: {:?}", kind, path);
+    Ok(())
+}
+
+// Pointer file under the default data root recording a relocated data_root
+const DATA_ROOT_POINTER_FILE: &str = "data_root.json";
+
+// Read the relocated data_root recorded by set_data_root, if any
+pub fn load_data_root_override() -> Option<PathBuf> {
+    let pointer_path = default_data_root().join(DATA_ROOT_POINTER_FILE);
+    let content = read_resource_text(pointer_path).ok()?;
+    let pointer: serde_json::Value = serde_json::from_str(&content).ok()?;
+    pointer.get("data_root").and_then(|v| v.as_str()).map(PathBuf::from)
+}
+
+// Environment overrides; they take effect at runtime but are never written to configs.json
+const DATA_ROOT_ENV: &str = "PULSAR_DATA_ROOT";
+const DEBUG_LLM_ENV: &str = "PULSAR_DEBUG_LLM";
+// Admin mode for managed setups; allows locking and unlocking providers
+const ADMIN_ENV: &str = "PULSAR_ADMIN";
+
+pub fn data_root_from_env() -> Option<PathBuf> {
+    std::env::var_os(DATA_ROOT_ENV)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+fn env_flag(name: &str) -> bool {
+    std::env::var(name)
+        .is_ok_and(|value| matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+}
+
+fn debug_llm_from_env() -> bool {
+    env_flag(DEBUG_LLM_ENV)
+}
+
+fn admin_from_env() -> bool {
+    env_flag(ADMIN_ENV)
+}
+
+// Probe that a directory accepts writes by creating and removing a test file
+fn check_directory_writable(path: &Path) -> Result<(), String> {
+    let probe = path.join(".pulsar_test");
+    fs::write(&probe, "")
+        .map_err(|e| format!("Cannot write to directory {:?}: {}", path, e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+// Startup probe: the data root (or, before it exists, its nearest existing parent) must accept writes
+pub fn is_data_root_read_only(data_root: &Path) -> bool {
+    let Some(existing) = data_root.ancestors().find(|p| p.exists()) else {
+        return false;
+    };
+    let read_only = check_directory_writable(existing).is_err();
+    if read_only {
+        println!("Data root {:?} is not writable; running in read-only mode", data_root);
+    }
+    read_only
+}
+
+// Mutating commands call this first so a read-only share fails cleanly instead of mid-write
+fn ensure_writable(state: &AppState) -> Result<(), String> {
+    if state.read_only.load(Ordering::SeqCst) {
+        let data_root = state.config.lock().unwrap().data_root.clone();
+        return Err(format!("Permission denied: data directory {:?} is read-only", data_root));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn is_read_only(
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    Ok(state.read_only.load(Ordering::SeqCst))
+}
+
+// Move a file or directory, falling back to copy + delete across filesystems
+fn move_path(source: &Path, target: &Path) -> Result<(), String> {
+    if fs::rename(source, target).is_ok() {
+        return Ok(());
+    }
+
+    if source.is_dir() {
+        copy_directory(
+            source.to_str().ok_or("Invalid source path")?,
+            target.to_str().ok_or("Invalid target path")?,
+        )?;
+        fs::remove_dir_all(source)
+            .map_err(|e| format!("Failed to remove {:?} after copying: {}", source, e))
+    } else {
+        fs::copy(source, target)
+            .map_err(|e| format!("Failed to copy file: {}", e))?;
+        fs::remove_file(source)
+            .map_err(|e| format!("Failed to remove {:?} after copying: {}", source, e))
+    }
+}
+
+#[tauri::command]
+pub async fn set_data_root(
+    new_path: String,
+    move_existing: bool,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut config = state.config.lock().unwrap();
+    let old_root = config.data_root.clone();
+    let new_root = PathBuf::from(new_path.trim());
+
+    if new_root.as_os_str().is_empty() {
+        return Err("Data root cannot be empty".to_string());
+    }
+
+    if new_root.exists() && !new_root.is_dir() {
+        return Err(format!("{:?} is a file, not a directory", new_root));
+    }
+
+    fs::create_dir_all(&new_root)
+        .map_err(|e| format!("Failed to create data root: {}", e))?;
+    check_directory_writable(&new_root)?;
+
+    let new_root = new_root.canonicalize()
+        .map_err(|e| format!("Failed to resolve data root: {}", e))?;
+
+    if move_existing && old_root.exists() {
+        let old_canonical = old_root.canonicalize()
+            .map_err(|e| format!("Failed to resolve current data root: {}", e))?;
+
+        if new_root != old_canonical {
+            if new_root.starts_with(&old_canonical) || old_canonical.starts_with(&new_root) {
+                return Err("Cannot move the data directory into itself or one of its parents".to_string());
+            }
+
+            let entries: Vec<_> = fs::read_dir(&old_root)
+                .map_err(|e| format!("Failed to read current data root: {}", e))?
+                .filter_map(|entry| entry.ok())
+                .filter(|entry| entry.file_name() != DATA_ROOT_POINTER_FILE)
+                .collect();
+
+            // Refuse to merge into a directory that already holds the same resources
+            let conflicts: Vec<String> = entries.iter()
+                .filter(|entry| new_root.join(entry.file_name()).exists())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .collect();
+            if !conflicts.is_empty() {
+                return Err(format!("Target already contains: {}", conflicts.join(", ")));
+            }
+
+            for entry in entries {
+                move_path(&entry.path(), &new_root.join(entry.file_name()))?;
+            }
+        }
+    }
+
+    config.data_root = new_root.clone();
+    // The new root passed the write probe above
+    state.read_only.store(false, Ordering::SeqCst);
+    init_data_dir(&config.data_root)?;
+    save_config_to_file(&config)?;
+
+    // Remember the location so it is used again on the next launch
+    let default_root = default_data_root();
+    let pointer_path = default_root.join(DATA_ROOT_POINTER_FILE);
+    if new_root == default_root {
+        let _ = fs::remove_file(&pointer_path);
+    } else {
+        fs::create_dir_all(&default_root)
+            .map_err(|e| format!("Failed to create default data directory: {}", e))?;
+        let pointer = serde_json::json!({ "data_root": new_root.to_string_lossy() });
+        fs::write(&pointer_path, serde_json::to_string_pretty(&pointer).unwrap())
+            .map_err(|e| format!("Failed to record data root: {}", e))?;
+    }
+
+    println!("Data root changed: {:?} -> {:?}", old_root, new_root);
+    Ok(new_root.to_string_lossy().to_string())
+}
+
 #[tauri::command]
 pub async fn validate_directory_permissions(
     path: String,
+    recursive: Option<bool>,
 ) -> Result<DirectoryPermissionResult, String> {
     use std::path::Path;
 
@@ -256,6 +697,7 @@ pub async fn validate_directory_permissions(
         writable: false,
         is_directory: path_obj.is_dir(),
         error: None,
+        inaccessible_path: None,
     };
 
     if !path_obj.exists() {
@@ -299,6 +741,19 @@ pub async fn validate_directory_permissions(
         }
     };
 
+    // Subpaths are only walked on request; large trees make this slow
+    if recursive.unwrap_or(false) {
+        if let Some((subpath, error)) = find_inaccessible_subpath(path_obj) {
+            return Ok(DirectoryPermissionResult {
+                readable,
+                writable,
+                error: Some(error),
+                inaccessible_path: Some(subpath.to_string_lossy().to_string()),
+                ..result
+            });
+        }
+    }
+
     Ok(DirectoryPermissionResult {
         readable,
         writable,
@@ -306,42 +761,152 @@ pub async fn validate_directory_permissions(
     })
 }
 
+// Bounds for the recursive permission check
+const PERMISSION_SCAN_MAX_DEPTH: usize = 8;
+const PERMISSION_SCAN_MAX_ENTRIES: usize = 10_000;
+
+// Walk below `root` (not following symlinks) and return the first subpath that can't be read or written
+fn find_inaccessible_subpath(root: &Path) -> Option<(PathBuf, String)> {
+    let mut pending = vec![(root.to_path_buf(), 0)];
+    let mut visited = 0;
+
+    while let Some((dir, depth)) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => return Some((dir, format!("Cannot read directory: {}", e))),
+        };
+        if dir != root {
+            if let Err(e) = check_directory_writable(&dir) {
+                return Some((dir, e));
+            }
+        }
+
+        for entry in entries.flatten() {
+            visited += 1;
+            if visited > PERMISSION_SCAN_MAX_ENTRIES {
+                return None;
+            }
+            let path = entry.path();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                if depth < PERMISSION_SCAN_MAX_DEPTH {
+                    pending.push((path, depth + 1));
+                }
+            } else if file_type.is_file() {
+                if let Err(e) = fs::File::open(&path) {
+                    return Some((path, format!("Cannot read file: {}", e)));
+                }
+                if entry.metadata().is_ok_and(|m| m.permissions().readonly()) {
+                    return Some((path, "File is read-only".to_string()));
+                }
+            }
+        }
+    }
+
+    None
+}
+
 #[tauri::command]
 pub async fn create_thread(
     request: CreateThreadRequest,
     state: State<'_, AppState>,
     _app_handle: tauri::AppHandle,
 ) -> Result<Thread, String> {
-    let thread_id = uuid::Uuid::new_v4().to_string();
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+    let thread = create_thread_internal(&config, request, uuid::Uuid::new_v4().to_string())?;
+    record_recent_thread(&mut config, &thread.id, true);
+    Ok(thread)
+}
 
-    // Initialize data directory and default data
-    let data_root = &state.config.lock().unwrap().data_root;
-    init_default_data(data_root)?;
+const RECENT_THREADS_LIMIT: usize = 10;
+const RECENT_THREADS_FILE: &str = "recent_threads.json";
 
-    // Save thread to file
-    let created_at = chrono::Utc::now().to_rfc3339();
-    let saves_dir = data_root.join("saves");
-    let thread_file = saves_dir.join(format!("{}.json", thread_id));
-    let config_data = serde_json::json!({
-        "plannerLlmAlias": request.planner_llm_alias,
-        "deciderLlmAlias": request.decider_llm_alias,
-        "selectedKnowledge": request.selected_knowledge,
-        "selectedGuides": request.selected_guides,
-        "selectedActions": request.selected_actions
-    });
+fn load_recent_threads(data_root: &Path) -> Option<Vec<String>> {
+    let content = read_resource_text(data_root.join(RECENT_THREADS_FILE)).ok()?;
+    serde_json::from_str(&content).ok()
+}
 
-    let thread_data = serde_json::json!({
-        "id": thread_id,
-        "name": request.name,
-        "working_dir": request.working_dir,
-        "created_at": created_at,
-        "updated_at": created_at,
-        "agent_state": null,
-        "config": config_data
-    });
+fn save_recent_threads(config: &AppConfig) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&config.recent_threads)
+        .map_err(|e| format!("Failed to serialize recent threads: {}", e))?;
+    write_atomic(&config.data_root.join(RECENT_THREADS_FILE), content)
+}
+
+// Move a thread to the front of the recent list; a failed save only costs the ordering
+fn record_recent_thread(config: &mut AppConfig, thread_id: &str, persist: bool) {
+    config.recent_threads.retain(|id| id != thread_id);
+    config.recent_threads.insert(0, thread_id.to_string());
+    config.recent_threads.truncate(RECENT_THREADS_LIMIT);
+
+    if persist {
+        if let Err(e) = save_recent_threads(config) {
+            println!("Failed to save recent threads: {}", e);
+        }
+    }
+}
 
-    fs::write(&thread_file, serde_json::to_string_pretty(&thread_data).unwrap())
-        .map_err(|e| format!("Failed to save thread: {}", e))?;
+#[tauri::command]
+pub async fn get_thread(
+    thread_id: String,
+    state: State<'_, AppState>,
+) -> Result<Thread, String> {
+    let mut config = state.config.lock().unwrap();
+    let thread = load_thread(&config.data_root, &thread_id)?;
+
+    // Opening a thread is a read, so a read-only data root keeps the list in memory only
+    let persist = !state.read_only.load(Ordering::SeqCst);
+    record_recent_thread(&mut config, &thread.id, persist);
+    Ok(thread)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecentThread {
+    pub id: String,
+    pub name: String,
+    pub updated_at: String,
+}
+
+#[tauri::command]
+pub async fn get_recent_threads(
+    state: State<'_, AppState>,
+) -> Result<Vec<RecentThread>, String> {
+    let config = state.config.lock().unwrap().clone();
+
+    // Threads deleted since they were opened are left out
+    Ok(config.recent_threads.iter()
+        .filter_map(|id| load_thread(&config.data_root, id).ok())
+        .map(|thread| RecentThread {
+            id: thread.id,
+            name: thread.name,
+            updated_at: thread.updated_at,
+        })
+        .collect())
+}
+
+// Build and validate the thread in memory; the save file is only written once everything succeeded
+fn create_thread_internal(config: &AppConfig, request: CreateThreadRequest, thread_id: String) -> Result<Thread, String> {
+    if request.name.trim().is_empty() {
+        return Err("Thread name cannot be empty".to_string());
+    }
+
+    let pick_alias = |requested: Option<String>, default: &Option<String>, role: &str| {
+        let alias = requested
+            .filter(|alias| !alias.trim().is_empty())
+            .or_else(|| default.clone())
+            .ok_or_else(|| format!("No {} provider given and no default {} is set", role, role))?;
+        // Store the provider's own spelling, not whatever casing was typed
+        config.llm_providers.iter()
+            .find(|p| alias_matches(&p.alias, &alias))
+            .map(|p| p.alias.clone())
+            .ok_or_else(|| format!("Unknown {} provider: {}", role, alias))
+    };
+    let planner_llm_alias = pick_alias(request.planner_llm_alias, &config.default_planner_alias, "planner")?;
+    let decider_llm_alias = pick_alias(request.decider_llm_alias, &config.default_decider_alias, "decider")?;
+
+    let created_at = chrono::Utc::now().to_rfc3339();
     let thread = Thread {
         id: thread_id,
         name: request.name,
@@ -350,1147 +915,8956 @@ pub async fn create_thread(
         updated_at: created_at,
         agent_state: None,
         config: Some(ThreadConfig {
-            planner_llm_alias: request.planner_llm_alias,
-            decider_llm_alias: request.decider_llm_alias,
+            planner_llm_alias,
+            decider_llm_alias,
             selected_knowledge: request.selected_knowledge,
             selected_guides: request.selected_guides,
             selected_actions: request.selected_actions,
+            execution_mode: request.execution_mode,
+            system_prompt: None,
         }),
+        tags: vec![],
     };
 
+    // Initialize data directory and default data, then commit the save file
+    init_default_data(&config.data_root)?;
+    save_thread(&config.data_root, &thread)?;
+
     Ok(thread)
 }
 
 #[tauri::command]
-pub async fn agent_ask(
-    request: AgentAskRequest,
+pub async fn duplicate_thread(
+    thread_id: String,
+    new_name: String,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    // Call Node.js agent
-    let node_request = NodeAgentRequest {
-        action: "ask_agent".to_string(),
-        thread_id: request.thread_id.clone(),
-        data: serde_json::json!({
-            "text": request.text,
-            "files": request.files,
-            "execution_mode": request.execution_mode
-        }),
-    };
-
-    let response = call_node_agent(node_request).await?;
+) -> Result<Thread, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let source = load_thread(data_root, &thread_id)?;
 
-    if response.success {
-        if let Some(data) = response.data {
-            if let Some(run_id) = data.get("run_id").and_then(|v| v.as_str()) {
-                return Ok(run_id.to_string());
-            }
-        }
-        Err("Invalid response from agent".to_string())
-    } else {
-        Err(response.error.unwrap_or_else(|| "Unknown error".to_string()))
+    if new_name.trim().is_empty() {
+        return Err("Thread name cannot be empty".to_string());
     }
+
+    // Same config and working directory, fresh identity and no agent state
+    let now = chrono::Utc::now().to_rfc3339();
+    let thread = Thread {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: new_name,
+        working_dir: source.working_dir,
+        created_at: now.clone(),
+        updated_at: now,
+        agent_state: None,
+        config: source.config,
+        tags: source.tags,
+    };
+    save_thread(data_root, &thread)?;
+
+    println!("Thread duplicated: {} -> {}", thread_id, thread.id);
+    Ok(thread)
 }
 
 #[tauri::command]
-pub async fn get_agent_report(
-    run_id: String,
+pub async fn get_thread_execution_mode(
+    thread_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    let node_request = NodeAgentRequest {
-        action: "get_report".to_string(),
-        thread_id: "".to_string(), // Not used for this operation
-        data: serde_json::json!({
-            "run_id": run_id
-        }),
-    };
+) -> Result<ExecutionMode, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let thread = load_thread(data_root, &thread_id)?;
 
-    let response = call_node_agent(node_request).await?;
-
-    if response.success {
-        response.data.ok_or_else(|| "No data returned".to_string())
-    } else {
-        Err(response.error.unwrap_or_else(|| "Unknown error".to_string()))
-    }
+    Ok(thread.config.map(|config| config.execution_mode).unwrap_or_default())
 }
 
 #[tauri::command]
-pub async fn submit_feedback(
-    action_run_id: String,
-    feedback: serde_json::Value,
+pub async fn set_thread_execution_mode(
+    thread_id: String,
+    mode: String,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    // TODO: Implement feedback submission
-    println!("Feedback submitted for action {}: {}", action_run_id, feedback);
-    Ok(())
-}
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let execution_mode: ExecutionMode = mode.parse()?;
 
+    let mut thread = load_thread(data_root, &thread_id)?;
+    let config = thread.config.as_mut()
+        .ok_or_else(|| format!("Thread '{}' has no config", thread_id))?;
+    config.execution_mode = execution_mode;
+    thread.updated_at = chrono::Utc::now().to_rfc3339();
+
+    save_thread(data_root, &thread)
+}
 
 #[tauri::command]
-pub async fn get_all_llm_providers(
+pub async fn get_thread_system_prompt(
+    thread_id: String,
     state: State<'_, AppState>,
-) -> Result<Vec<LLMProvider>, String> {
-    let config = state.config.lock().unwrap();
-    Ok(config.llm_providers.clone())
+) -> Result<Option<String>, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let thread = load_thread(data_root, &thread_id)?;
+    Ok(thread.config.and_then(|config| config.system_prompt))
 }
 
 #[tauri::command]
-pub async fn add_llm_provider(
-    provider: LLMProvider,
+pub async fn set_thread_system_prompt(
+    thread_id: String,
+    prompt: Option<String>,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    let mut config = state.config.lock().unwrap();
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
 
-    // Check if alias already exists
-    if config.llm_providers.iter().any(|p| p.alias == provider.alias) {
-        return Err(format!("Provider with alias '{}' already exists", provider.alias));
-    }
+    let mut thread = load_thread(data_root, &thread_id)?;
+    let config = thread.config.as_mut()
+        .ok_or_else(|| format!("Thread '{}' has no config", thread_id))?;
+    // An empty prompt removes it
+    config.system_prompt = prompt.filter(|p| !p.trim().is_empty());
+    thread.updated_at = chrono::Utc::now().to_rfc3339();
 
-    config.llm_providers.push(provider);
+    save_thread(data_root, &thread)
+}
 
-    // Save to file
-    save_config_to_file(&config)?;
-    Ok(())
+// Trimmed tags with case-insensitive duplicates removed, keeping the first spelling
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .collect()
 }
 
 #[tauri::command]
-pub async fn update_llm_provider(
-    id: String,
-    provider: LLMProvider,
+pub async fn set_thread_tags(
+    thread_id: String,
+    tags: Vec<String>,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let mut config = state.config.lock().unwrap();
+) -> Result<Vec<String>, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
 
-    // Find and update provider by alias (using alias as ID for simplicity)
-    if let Some(index) = config.llm_providers.iter().position(|p| p.alias == id) {
-        config.llm_providers[index] = provider;
-        save_config_to_file(&config)?;
-        Ok(())
-    } else {
-        Err(format!("Provider with alias '{}' not found", id))
-    }
+    let mut thread = load_thread(data_root, &thread_id)?;
+    thread.tags = normalize_tags(tags);
+    thread.updated_at = chrono::Utc::now().to_rfc3339();
+    save_thread(data_root, &thread)?;
+
+    Ok(thread.tags)
 }
 
+// Every saved thread, most recently updated first
 #[tauri::command]
-pub async fn delete_llm_provider(
-    id: String,
+pub async fn list_threads(
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let mut config = state.config.lock().unwrap();
-
-    let initial_len = config.llm_providers.len();
-    config.llm_providers.retain(|p| p.alias != id);
+) -> Result<Vec<Thread>, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let mut threads = list_saved_threads(data_root)?;
+    threads.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(threads)
+}
 
-    if config.llm_providers.len() < initial_len {
-        save_config_to_file(&config)?;
-        Ok(())
-    } else {
-        Err(format!("Provider with alias '{}' not found", id))
-    }
+#[tauri::command]
+pub async fn list_threads_by_tag(
+    tag: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<Thread>, String> {
+    let tag = tag.trim().to_lowercase();
+    let threads = list_threads(state).await?;
+    Ok(threads.into_iter()
+        .filter(|thread| thread.tags.iter().any(|t| t.to_lowercase() == tag))
+        .collect())
 }
 
+// Short hash of a thread's normalized config: aliases ignore case, selections ignore order and
+// duplicates, and a blank system prompt counts as none. Threads set up alike share a fingerprint
+fn thread_config_fingerprint(config: &ThreadConfig) -> String {
+    let selections = |names: &[String]| -> BTreeSet<String> {
+        names.iter().map(|name| name.trim().to_string()).filter(|name| !name.is_empty()).collect()
+    };
+    let normalized = serde_json::json!({
+        "planner": config.planner_llm_alias.trim().to_lowercase(),
+        "decider": config.decider_llm_alias.trim().to_lowercase(),
+        "knowledge": selections(&config.selected_knowledge),
+        "guides": selections(&config.selected_guides),
+        "actions": selections(&config.selected_actions),
+        "execution_mode": config.execution_mode,
+        "system_prompt": config.system_prompt.as_deref().map(str::trim).filter(|p| !p.is_empty()),
+    });
+    blake3::hash(normalized.to_string().as_bytes()).to_hex()[..16].to_string()
+}
 
 #[tauri::command]
-pub async fn save_config_to_file_public(
+pub async fn get_thread_fingerprint(
+    thread_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let config = state.config.lock().unwrap();
-    save_config_to_file(&config)
+) -> Result<String, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let thread = load_thread(data_root, &thread_id)?;
+    let config = thread.config
+        .ok_or_else(|| format!("Thread '{}' has no config", thread_id))?;
+    Ok(thread_config_fingerprint(&config))
+}
+
+// Ids of threads that have at least one recorded agent run
+fn thread_ids_with_runs(data_root: &Path) -> HashSet<String> {
+    let Ok(entries) = fs::read_dir(data_root.join("logs").join("runs")) else {
+        return HashSet::new();
+    };
+
+    entries.flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            load_run_metrics(data_root, name.strip_suffix(".metrics.json")?)
+        })
+        .map(|metrics| metrics.thread_id)
+        .collect()
 }
 
 #[tauri::command]
-pub async fn load_config_from_file(
+pub async fn prune_threads(
+    older_than_days: u64,
+    keep_with_runs: bool,
     state: State<'_, AppState>,
-) -> Result<Vec<LLMProvider>, String> {
-    let config = state.config.lock().unwrap();
-    let config_path = config.data_root.join("configs.json");
-
-    if !config_path.exists() {
-        // Create default config file if it doesn't exist
-        save_config_to_file(&*config)?;
-        return Ok(config.llm_providers.clone());
-    }
+) -> Result<Vec<String>, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
 
-    let content = fs::read_to_string(&config_path)
-        .map_err(|e| format!("Failed to read config file: {}", e))?;
+    // Clamp so absurd values can't overflow the date arithmetic
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(older_than_days.min(36_500) as i64);
+    let with_runs = if keep_with_runs { thread_ids_with_runs(data_root) } else { HashSet::new() };
+
+    let mut deleted = Vec::new();
+    for thread in list_saved_threads(data_root)? {
+        // Threads with an unreadable timestamp are never pruned
+        let Ok(updated_at) = chrono::DateTime::parse_from_rfc3339(&thread.updated_at) else {
+            continue;
+        };
+        if updated_at >= cutoff {
+            continue;
+        }
 
-    let loaded_config: AppConfig = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse config file: {}", e))?;
+        let has_runs = thread.agent_state.as_ref().is_some_and(|s| s.run_id.is_some())
+            || with_runs.contains(&thread.id);
+        if keep_with_runs && has_runs {
+            continue;
+        }
 
-    // Update state config and return providers
-    let providers = loaded_config.llm_providers.clone();
-    {
-        let mut state_config = state.config.lock().unwrap();
-        state_config.llm_providers = loaded_config.llm_providers;
+        // Pruned threads go to the trash so they can be restored
+        match move_to_trash(data_root, "saves", &format!("{}.json", thread.id)) {
+            Ok(_) => deleted.push(thread.id),
+            Err(e) => println!("Failed to prune thread {}: {}", thread.id, e),
+        }
     }
 
-    Ok(providers)
+    println!("Pruned {} threads older than {} days", deleted.len(), older_than_days);
+    Ok(deleted)
 }
 
-// Helper function to save config to file
-fn save_config_to_file(config: &AppConfig) -> Result<(), String> {
-    let config_path = config.data_root.join("configs.json");
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolvedThread {
+    pub thread_id: String,
+    pub planner: Option<LLMProvider>,
+    pub decider: Option<LLMProvider>,
+    pub missing_aliases: Vec<String>,
+}
 
-    // Ensure directory exists
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+// Load a saved thread from saves/{thread_id}.json
+fn load_thread(data_root: &Path, thread_id: &str) -> Result<Thread, String> {
+    // Validate thread id to prevent directory traversal
+    if thread_id.contains("..") || thread_id.contains("/") || thread_id.contains("\\") {
+        return Err("Invalid thread id".to_string());
     }
 
-    let content = serde_json::to_string_pretty(config)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    let thread_path = data_root.join("saves").join(format!("{}.json", thread_id));
+    if !thread_path.exists() {
+        return Err(format!("Thread '{}' not found", thread_id));
+    }
 
-    fs::write(&config_path, content)
-        .map_err(|e| format!("Failed to write config file: {}", e))?;
+    let content = read_resource_text(&thread_path)
+        .map_err(|e| format!("Failed to read thread file: {}", e))?;
 
-    println!("Config saved to: {:?}", config_path);
-    Ok(())
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse thread file: {}", e))
 }
 
-#[tauri::command]
-pub async fn list_guides(
-    state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let guides_dir = data_root.join("guides");
+// Saved form of a thread, keeping the frontend's camelCase config keys
+fn thread_save_json(thread: &Thread) -> serde_json::Value {
+    let config_data = thread.config.as_ref().map(|config| serde_json::json!({
+        "plannerLlmAlias": config.planner_llm_alias,
+        "deciderLlmAlias": config.decider_llm_alias,
+        "selectedKnowledge": config.selected_knowledge,
+        "selectedGuides": config.selected_guides,
+        "selectedActions": config.selected_actions,
+        "executionMode": config.execution_mode,
+        "systemPrompt": config.system_prompt
+    }));
+
+    serde_json::json!({
+        "id": thread.id,
+        "name": thread.name,
+        "working_dir": thread.working_dir,
+        "created_at": thread.created_at,
+        "updated_at": thread.updated_at,
+        "agent_state": thread.agent_state,
+        "config": config_data,
+        "tags": thread.tags
+    })
+}
 
-    if !guides_dir.exists() {
+// Write a thread to saves/{id}.json
+fn save_thread(data_root: &Path, thread: &Thread) -> Result<(), String> {
+    let thread_file = data_root.join("saves").join(format!("{}.json", thread.id));
+    write_atomic(&thread_file, serde_json::to_string_pretty(&thread_save_json(thread)).unwrap())
+        .map_err(|e| format!("Failed to save thread: {}", e))
+}
+
+// Load every saved thread, skipping files that can't be parsed
+fn list_saved_threads(data_root: &Path) -> Result<Vec<Thread>, String> {
+    let saves_dir = data_root.join("saves");
+
+    if !saves_dir.exists() {
         return Ok(vec![]);
     }
 
-    let mut guides = Vec::new();
+    let mut threads = Vec::new();
 
-    for entry in fs::read_dir(guides_dir).map_err(|e| format!("Failed to read guides directory: {}", e))? {
+    for entry in fs::read_dir(&saves_dir).map_err(|e| format!("Failed to read saves directory: {}", e))? {
         let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
         let path = entry.path();
 
         if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let content = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read guide file: {}", e))?;
-            let json: serde_json::Value = serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse guide JSON: {}", e))?;
-
-            guides.push(json);
+            let content = read_resource_text(&path)
+                .map_err(|e| format!("Failed to read thread file: {}", e))?;
+            match serde_json::from_str::<Thread>(&content) {
+                Ok(thread) => threads.push(thread),
+                Err(e) => println!("Skipping unreadable thread file {:?}: {}", path, e),
+            }
         }
     }
 
-    Ok(guides)
+    Ok(threads)
 }
 
 #[tauri::command]
-pub async fn load_guide(
-    filename: String,
+pub async fn resolve_thread_providers(
+    thread_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let guides_dir = data_root.join("guides");
-    let guide_path = guides_dir.join(&filename);
+) -> Result<ResolvedThread, String> {
+    let config = state.config.lock().unwrap().clone();
+    let thread = load_thread(&config.data_root, &thread_id)?;
+    let thread_config = thread.config
+        .ok_or_else(|| format!("Thread '{}' has no config", thread_id))?;
 
-    // Validate filename to prevent directory traversal
-    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
-        return Err("Invalid filename".to_string());
-    }
+    let find = |alias: &str| config.llm_providers.iter()
+        .find(|p| alias_matches(&p.alias, alias))
+        .cloned();
 
-    if !guide_path.exists() {
-        return Err(format!("Guide file '{}' not found", filename));
+    let planner = find(&thread_config.planner_llm_alias);
+    let decider = find(&thread_config.decider_llm_alias);
+
+    // Flag aliases that were deleted after the thread was created
+    let mut missing_aliases = Vec::new();
+    if planner.is_none() {
+        missing_aliases.push(thread_config.planner_llm_alias.clone());
+    }
+    if decider.is_none() && !missing_aliases.contains(&thread_config.decider_llm_alias) {
+        missing_aliases.push(thread_config.decider_llm_alias.clone());
     }
 
-    let content = fs::read_to_string(&guide_path)
-        .map_err(|e| format!("Failed to read guide file: {}", e))?;
+    Ok(ResolvedThread {
+        thread_id,
+        planner,
+        decider,
+        missing_aliases,
+    })
+}
 
-    let json: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse guide JSON: {}", e))?;
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DanglingRef {
+    pub thread_id: String,
+    pub thread_name: String,
+    pub role: String, // "planner" or "decider"
+    pub alias: String,
+}
 
-    Ok(json)
+#[tauri::command]
+pub async fn find_dangling_aliases(state: State<'_, AppState>) -> Result<Vec<DanglingRef>, String> {
+    let config = state.config.lock().unwrap().clone();
+    let known = |alias: &str| config.llm_providers.iter().any(|p| alias_matches(&p.alias, alias));
+
+    let mut dangling = Vec::new();
+    for thread in list_saved_threads(&config.data_root)? {
+        let Some(thread_config) = &thread.config else {
+            continue;
+        };
+        for (role, alias) in [
+            ("planner", &thread_config.planner_llm_alias),
+            ("decider", &thread_config.decider_llm_alias),
+        ] {
+            if !known(alias) {
+                dangling.push(DanglingRef {
+                    thread_id: thread.id.clone(),
+                    thread_name: thread.name.clone(),
+                    role: role.to_string(),
+                    alias: alias.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(dangling)
 }
 
 #[tauri::command]
-pub async fn save_guide(
-    filename: String,
-    guide_data: serde_json::Value,
+pub async fn remap_alias(
+    old: String,
+    new: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let guides_dir = data_root.join("guides");
+) -> Result<Vec<String>, String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap().clone();
 
-    // Validate filename to prevent directory traversal
-    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
-        return Err("Invalid filename".to_string());
-    }
+    // Only remap onto a provider that actually exists, using its stored spelling
+    let new_alias = config.llm_providers.iter()
+        .find(|p| alias_matches(&p.alias, &new))
+        .map(|p| p.alias.clone())
+        .ok_or_else(|| format!("Provider with alias '{}' not found", new))?;
 
-    // Ensure filename ends with .json
-    let filename = if filename.ends_with(".json") {
-        filename
-    } else {
-        format!("{}.json", filename)
-    };
+    let updated = remap_thread_aliases(&config.data_root, &old, &new_alias)?;
 
-    // Ensure guides directory exists
-    fs::create_dir_all(&guides_dir)
-        .map_err(|e| format!("Failed to create guides directory: {}", e))?;
+    println!("Remapped alias {} -> {} in {} threads", old, new_alias, updated.len());
+    Ok(updated)
+}
 
-    let guide_path = guides_dir.join(&filename);
+// Point every saved thread's planner/decider at new_alias where it used old; returns their ids
+fn remap_thread_aliases(data_root: &Path, old: &str, new_alias: &str) -> Result<Vec<String>, String> {
+    let mut updated = Vec::new();
+    for mut thread in list_saved_threads(data_root)? {
+        let Some(thread_config) = thread.config.as_mut() else {
+            continue;
+        };
+        let mut changed = false;
+        for alias in [&mut thread_config.planner_llm_alias, &mut thread_config.decider_llm_alias] {
+            if alias_matches(alias, old) {
+                *alias = new_alias.to_string();
+                changed = true;
+            }
+        }
+        if changed {
+            thread.updated_at = chrono::Utc::now().to_rfc3339();
+            save_thread(data_root, &thread)?;
+            updated.push(thread.id);
+        }
+    }
+    Ok(updated)
+}
 
-    // Validate guide structure
-    validate_guide_structure(&guide_data)?;
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SelectionReport {
+    pub thread_id: String,
+    pub missing_guides: Vec<String>,
+    pub missing_knowledge: Vec<String>,
+    pub missing_actions: Vec<String>,
+    pub all_present: bool,
+}
 
-    let content = serde_json::to_string_pretty(&guide_data)
-        .map_err(|e| format!("Failed to serialize guide data: {}", e))?;
+// Map every name a selection may refer to (file or directory name, and each resource's
+// meta.name) to the resource's path
+fn resource_index(dir: &Path, meta_file: Option<&str>) -> HashMap<String, PathBuf> {
+    let mut index = HashMap::new();
 
-    fs::write(&guide_path, content)
-        .map_err(|e| format!("Failed to write guide file: {}", e))?;
+    let Ok(entries) = fs::read_dir(dir) else {
+        return index;
+    };
 
-    println!("Guide saved to: {:?}", guide_path);
-    Ok(())
-}
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let (is_resource, meta_path) = match meta_file {
+            Some(meta_file) => (path.is_dir(), path.join(meta_file)),
+            None => (is_resource_file(&path), path.clone()),
+        };
 
-#[tauri::command]
-pub async fn delete_guide(
-    filename: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let guides_dir = data_root.join("guides");
-    let guide_path = guides_dir.join(&filename);
+        if !is_resource {
+            continue;
+        }
 
-    // Validate filename to prevent directory traversal
-    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
-        return Err("Invalid filename".to_string());
-    }
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        index.insert(file_name.clone(), path.clone());
+        // Selections saved before a file was (de)compressed still name foo.json or foo.json.gz
+        if meta_file.is_none() {
+            for alias in [resource_file_name(&file_name, false), resource_file_name(&file_name, true)] {
+                index.entry(alias).or_insert_with(|| path.clone());
+            }
+        }
 
-    if !guide_path.exists() {
-        return Err(format!("Guide file '{}' not found", filename));
+        let meta_name = read_resource_text(&meta_path).ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|json| {
+                let name = json.get("meta").unwrap_or(&json).get("name")?;
+                name.as_str().map(|s| s.to_string())
+            });
+        if let Some(meta_name) = meta_name {
+            index.entry(meta_name).or_insert(path);
+        }
     }
 
-    fs::remove_file(&guide_path)
-        .map_err(|e| format!("Failed to delete guide file: {}", e))?;
+    index
+}
 
-    println!("Guide deleted: {:?}", guide_path);
-    Ok(())
+// Names a selection may refer to: file or directory names plus each resource's meta.name
+fn available_resource_names(dir: &Path, meta_file: Option<&str>) -> HashSet<String> {
+    resource_index(dir, meta_file).into_keys().collect()
 }
 
 #[tauri::command]
-pub async fn create_guides_directory(
+pub async fn validate_thread_selections(
+    thread_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let guides_dir = data_root.join("guides");
-
-    fs::create_dir_all(&guides_dir)
-        .map_err(|e| format!("Failed to create guides directory: {}", e))?;
+) -> Result<SelectionReport, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let thread = load_thread(&data_root, &thread_id)?;
+    let thread_config = thread.config
+        .ok_or_else(|| format!("Thread '{}' has no config", thread_id))?;
+
+    let missing = |selected: &[String], available: &HashSet<String>| -> Vec<String> {
+        selected.iter().filter(|name| !available.contains(*name)).cloned().collect()
+    };
 
-    // Create a default guide if directory is empty
-    let default_guide = serde_json::json!({
-        "meta": {
-            "name": "Software Development Guide",
-            "version": "1.0.0",
-            "domain": "development"
-        },
-        "entries": [
-            {
-                "name": "Project Setup",
-                "description": "Initial project configuration and setup",
-                "plan": [
-                    "Create project structure",
-                    "Configure development environment",
-                    "Set up version control",
-                    "Install dependencies"
-                ]
-            },
-            {
-                "name": "Feature Development",
-                "description": "Develop new features incrementally",
-                "plan": [
-                    "Analyze requirements",
-                    "Design solution approach",
-                    "Implement core functionality",
-                    "Write tests",
-                    "Review and refactor"
-                ]
-            }
-        ]
-    });
+    let missing_guides = missing(
+        &thread_config.selected_guides,
+        &available_resource_names(&data_root.join("guides"), None),
+    );
+    let missing_knowledge = missing(
+        &thread_config.selected_knowledge,
+        &available_resource_names(&data_root.join("knowledge"), None),
+    );
+    let missing_actions = missing(
+        &thread_config.selected_actions,
+        &available_resource_names(&data_root.join("actions"), Some("meta.json")),
+    );
+
+    let all_present = missing_guides.is_empty() && missing_knowledge.is_empty() && missing_actions.is_empty();
+
+    Ok(SelectionReport {
+        thread_id,
+        missing_guides,
+        missing_knowledge,
+        missing_actions,
+        all_present,
+    })
+}
 
-    let default_path = guides_dir.join("software-development.json");
+// Thread bundles: a zip with thread.json, providers.json (without API keys), manifest.json
+// and copies of the referenced guides/, knowledge/ and actions/
+const BUNDLE_FORMAT_VERSION: u32 = 1;
+const BUNDLE_RESOURCE_DIRS: [&str; 3] = ["guides", "knowledge", "actions"];
 
-    if !default_path.exists() {
-        let content = serde_json::to_string_pretty(&default_guide)
-            .map_err(|e| format!("Failed to serialize default guide: {}", e))?;
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    pub thread_id: String,
+    pub exported_at: String,
+    pub app_version: String,
+    // File or directory names included under each resource directory
+    pub guides: Vec<String>,
+    pub knowledge: Vec<String>,
+    pub actions: Vec<String>,
+    // Selections that could not be found when exporting
+    pub missing_guides: Vec<String>,
+    pub missing_knowledge: Vec<String>,
+    pub missing_actions: Vec<String>,
+}
 
-        fs::write(&default_path, content)
-            .map_err(|e| format!("Failed to write default guide: {}", e))?;
+// Resolve selections to resource paths, splitting out the ones that don't exist
+fn resolve_selections(selected: &[String], index: &HashMap<String, PathBuf>) -> (Vec<PathBuf>, Vec<String>) {
+    let mut found: Vec<PathBuf> = Vec::new();
+    let mut missing = Vec::new();
 
-        println!("Default guide created at: {:?}", default_path);
+    for name in selected {
+        match index.get(name) {
+            Some(path) if !found.contains(path) => found.push(path.clone()),
+            Some(_) => {}
+            None => missing.push(name.clone()),
+        }
     }
 
-    Ok(())
+    (found, missing)
 }
 
-// Helper function to validate guide structure
-fn validate_guide_structure(guide: &serde_json::Value) -> Result<(), String> {
-    // Check for required meta section
-    let meta = guide.get("meta").ok_or("Missing 'meta' section")?;
+fn add_bundle_file(zip: &mut zip::ZipWriter<fs::File>, name: &str, content: &[u8]) -> Result<(), String> {
+    let options = zip::write::FileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options)
+        .map_err(|e| format!("Failed to add {} to bundle: {}", name, e))?;
+    zip.write_all(content)
+        .map_err(|e| format!("Failed to write {} to bundle: {}", name, e))
+}
 
-    // Check required meta fields
-    if !meta.get("name").and_then(|v| v.as_str()).is_some() {
-        return Err("Missing or invalid 'meta.name' field".to_string());
-    }
+// Add a directory recursively under `prefix`; an action's status.json is local state and stays behind
+fn add_bundle_dir(zip: &mut zip::ZipWriter<fs::File>, source: &Path, prefix: &str, is_action_root: bool) -> Result<(), String> {
+    for entry in fs::read_dir(source)
+        .map_err(|e| format!("Failed to read directory {:?}: {}", source, e))?
+    {
+        let entry = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let name = format!("{}/{}", prefix, entry.file_name().to_string_lossy());
 
-    if !meta.get("version").and_then(|v| v.as_str()).is_some() {
-        return Err("Missing or invalid 'meta.version' field".to_string());
+        if path.is_dir() {
+            add_bundle_dir(zip, &path, &name, false)?;
+        } else if !(is_action_root && entry.file_name() == "status.json") {
+            let content = fs::read(&path)
+                .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+            add_bundle_file(zip, &name, &content)?;
+        }
     }
+    Ok(())
+}
 
-    // Check for entries array
-    let entries = guide.get("entries").and_then(|v| v.as_array())
-        .ok_or("Missing or invalid 'entries' array")?;
+fn write_thread_bundle(data_root: &Path, thread: &Thread, providers: &[LLMProvider], dest: &Path) -> Result<(), String> {
+    let file = fs::File::create(dest)
+        .map_err(|e| format!("Failed to create bundle file: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    let mut manifest = BundleManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        thread_id: thread.id.clone(),
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        ..BundleManifest::default()
+    };
 
-    if entries.is_empty() {
-        return Err("Entries array cannot be empty".to_string());
-    }
+    if let Some(config) = &thread.config {
+        let file_name = |path: &PathBuf| path.file_name().unwrap_or_default().to_string_lossy().to_string();
 
-    // Validate each entry
-    for (index, entry) in entries.iter().enumerate() {
-        let entry_path = format!("entries[{}]", index);
+        let (guides, missing) = resolve_selections(&config.selected_guides, &resource_index(&data_root.join("guides"), None));
+        manifest.missing_guides = missing;
+        for path in &guides {
+            let content = fs::read(path).map_err(|e| format!("Failed to read guide file: {}", e))?;
+            add_bundle_file(&mut zip, &format!("guides/{}", file_name(path)), &content)?;
+            manifest.guides.push(file_name(path));
+        }
 
-        if !entry.get("name").and_then(|v| v.as_str()).is_some() {
-            return Err(format!("Missing or invalid '{}.name' field", entry_path));
+        let (knowledge, missing) = resolve_selections(&config.selected_knowledge, &resource_index(&data_root.join("knowledge"), None));
+        manifest.missing_knowledge = missing;
+        for path in &knowledge {
+            let content = fs::read(path).map_err(|e| format!("Failed to read knowledge file: {}", e))?;
+            add_bundle_file(&mut zip, &format!("knowledge/{}", file_name(path)), &content)?;
+            manifest.knowledge.push(file_name(path));
         }
 
-        if !entry.get("description").and_then(|v| v.as_str()).is_some() {
-            return Err(format!("Missing or invalid '{}.description' field", entry_path));
+        let (actions, missing) = resolve_selections(&config.selected_actions, &resource_index(&data_root.join("actions"), Some("meta.json")));
+        manifest.missing_actions = missing;
+        for path in &actions {
+            add_bundle_dir(&mut zip, path, &format!("actions/{}", file_name(path)), true)?;
+            manifest.actions.push(file_name(path));
         }
+    }
 
-        let plan = entry.get("plan").and_then(|v| v.as_array())
-            .ok_or(format!("Missing or invalid '{}.plan' array", entry_path))?;
+    // Providers travel without their keys; the recipient fills in their own
+    let providers: Vec<LLMProvider> = providers.iter()
+        .filter(|p| thread.config.as_ref().is_some_and(|c| {
+            alias_matches(&p.alias, &c.planner_llm_alias) || alias_matches(&p.alias, &c.decider_llm_alias)
+        }))
+        .map(|p| LLMProvider { api_key: None, ..p.clone() })
+        .collect();
+
+    let to_json = |value: &serde_json::Value| serde_json::to_vec_pretty(value)
+        .map_err(|e| format!("Failed to serialize bundle data: {}", e));
+    add_bundle_file(&mut zip, "thread.json", &to_json(&thread_save_json(thread))?)?;
+    add_bundle_file(&mut zip, "providers.json", &to_json(&serde_json::json!(providers))?)?;
+    add_bundle_file(&mut zip, "manifest.json", &to_json(&serde_json::json!(manifest))?)?;
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish bundle: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn export_thread_bundle(
+    thread_id: String,
+    dest_path: String,
+    state: State<'_, AppState>,
+) -> Result<BundleManifest, String> {
+    let (data_root, providers) = {
+        let config = state.config.lock().unwrap();
+        (config.data_root.clone(), config.llm_providers.clone())
+    };
+    let thread = load_thread(&data_root, &thread_id)?;
+    let dest = PathBuf::from(&dest_path);
+
+    // Don't leave a half-written bundle behind
+    if let Err(e) = write_thread_bundle(&data_root, &thread, &providers, &dest) {
+        let _ = fs::remove_file(&dest);
+        return Err(e);
+    }
+
+    let manifest = read_bundle_json(&mut open_bundle(&dest)?, "manifest.json")?;
+    println!("Thread bundle exported: {} -> {:?}", thread_id, dest);
+    Ok(manifest)
+}
+
+fn open_bundle(path: &Path) -> Result<zip::ZipArchive<fs::File>, String> {
+    let file = fs::File::open(path)
+        .map_err(|e| format!("Failed to open bundle: {}", e))?;
+    zip::ZipArchive::new(file)
+        .map_err(|e| format!("Failed to read bundle: {}", e))
+}
+
+fn read_bundle_json<T: serde::de::DeserializeOwned>(archive: &mut zip::ZipArchive<fs::File>, name: &str) -> Result<T, String> {
+    let entry = archive.by_name(name)
+        .map_err(|e| format!("Bundle is missing {}: {}", name, e))?;
+    serde_json::from_reader(entry)
+        .map_err(|e| format!("Failed to parse {} in bundle: {}", name, e))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ThreadBundleImport {
+    pub thread: Thread,
+    // "alias: reason" for bundled providers that failed validation
+    pub skipped_providers: Vec<String>,
+    // "dir/name: reason" for bundled resources that failed validation
+    pub invalid_resources: Vec<String>,
+}
 
-        if plan.is_empty() {
-            return Err(format!("'{}' plan array cannot be empty", entry_path));
+// Unpack the bundle's resources into `staging`, leaving out the ones that already exist locally.
+// Returns the "dir/name" of every staged guide, knowledge file and action directory
+fn stage_bundle_resources(
+    archive: &mut zip::ZipArchive<fs::File>,
+    data_root: &Path,
+    staging: &Path,
+) -> Result<Vec<String>, String> {
+    let mut skipped: HashSet<String> = HashSet::new();
+    let mut staged: Vec<String> = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read bundle entry: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        // enclosed_name rejects absolute paths and ".." components
+        let Some(relative) = entry.enclosed_name().map(|p| p.to_path_buf()) else {
+            continue;
+        };
+        let mut components = relative.components();
+        let (Some(dir), Some(resource)) = (components.next(), components.next()) else {
+            continue;
+        };
+        let dir = dir.as_os_str().to_string_lossy().to_string();
+        if !BUNDLE_RESOURCE_DIRS.contains(&dir.as_str()) {
+            continue;
         }
 
-        // Check that each plan step is a string
-        for (step_index, step) in plan.iter().enumerate() {
-            if !step.as_str().is_some() {
-                return Err(format!("'{}.plan[{}]' must be a string", entry_path, step_index));
+        // A guide, knowledge file or whole action directory is skipped when it existed before the import
+        let resource_key = format!("{}/{}", dir, resource.as_os_str().to_string_lossy());
+        if skipped.contains(&resource_key) {
+            continue;
+        }
+        if !staged.contains(&resource_key) {
+            if data_root.join(&dir).join(resource.as_os_str()).exists() {
+                println!("Skipping existing resource from bundle: {}", resource_key);
+                skipped.insert(resource_key);
+                continue;
             }
+            staged.push(resource_key);
+        }
+
+        let target = staging.join(&relative);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
         }
+        let mut content = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut content)
+            .map_err(|e| format!("Failed to read {:?} from bundle: {}", relative, e))?;
+        fs::write(&target, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", target, e))?;
     }
+    Ok(staged)
+}
 
-    Ok(())
+// Same checks the guide, knowledge and action editors run before saving
+async fn validate_bundle_resource(path: &Path, dir: &str) -> Result<(), String> {
+    if dir == "actions" {
+        let validation = validate_action_directory_internal(&path.to_string_lossy(), false).await?;
+        if !validation.is_valid {
+            return Err(validation.error.unwrap_or_else(|| "Unknown error".to_string()));
+        }
+        return Ok(());
+    }
+    if !path.is_file() {
+        return Err("Not a file".to_string());
+    }
+    let content = read_resource_text(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+    if dir == "guides" {
+        validate_guide_structure(&value)
+    } else {
+        validate_knowledge_structure(&value)
+    }
 }
 
 #[tauri::command]
-pub async fn list_knowledge(
+pub async fn import_thread_bundle(
+    bundle_path: String,
     state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let knowledge_dir = data_root.join("knowledge");
-
-    if !knowledge_dir.exists() {
-        return Ok(vec![]);
+) -> Result<ThreadBundleImport, String> {
+    ensure_writable(&state)?;
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    init_default_data(&data_root)?;
+
+    let mut archive = open_bundle(Path::new(&bundle_path))?;
+    let manifest: BundleManifest = read_bundle_json(&mut archive, "manifest.json")?;
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!("Bundle format {} is newer than supported format {}", manifest.format_version, BUNDLE_FORMAT_VERSION));
     }
+    let mut thread: Thread = read_bundle_json(&mut archive, "thread.json")?;
+    // Older bundles have no providers.json; one that is there but unreadable fails the import
+    // before anything is written
+    let providers: Vec<LLMProvider> = if archive.file_names().any(|name| name == "providers.json") {
+        read_bundle_json(&mut archive, "providers.json")?
+    } else {
+        Vec::new()
+    };
 
-    let mut knowledge_entries = Vec::new();
+    // Try the thread's providers that aren't configured here yet against a copy of the config;
+    // invalid ones are reported and left out
+    let mut skipped_providers = Vec::new();
+    let mut accepted = Vec::new();
+    {
+        let mut scratch = state.config.lock().unwrap().clone();
+        for provider in providers {
+            if scratch.llm_providers.iter().any(|p| alias_matches(&p.alias, &provider.alias)) {
+                continue;
+            }
+            match add_provider_to_config(&mut scratch, provider.clone()) {
+                Ok(()) => accepted.push(provider),
+                Err(e) => skipped_providers.push(format!("{}: {}", provider.alias, e)),
+            }
+        }
+    }
 
-    for entry in fs::read_dir(knowledge_dir).map_err(|e| format!("Failed to read knowledge directory: {}", e))? {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+    // Resources are unpacked and validated next to the library, then moved into place
+    let staging = data_root.join(format!("{}{}", ACTION_IMPORT_STAGING_PREFIX, uuid::Uuid::new_v4()));
+    let staged = match stage_bundle_resources(&mut archive, &data_root, &staging) {
+        Ok(staged) => staged,
+        Err(e) => {
+            let _ = fs::remove_dir_all(&staging);
+            return Err(e);
+        }
+    };
+    let mut invalid_resources = Vec::new();
+    let mut valid = Vec::new();
+    for resource_key in staged {
+        let dir = resource_key.split('/').next().unwrap_or_default();
+        match validate_bundle_resource(&staging.join(&resource_key), dir).await {
+            Ok(()) => valid.push(resource_key),
+            Err(e) => invalid_resources.push(format!("{}: {}", resource_key, e)),
+        }
+    }
 
-        if path.extension().and_then(|s| s.to_str()) == Some("json") {
-            let content = fs::read_to_string(&path)
-                .map_err(|e| format!("Failed to read knowledge file: {}", e))?;
-            let json: serde_json::Value = serde_json::from_str(&content)
-                .map_err(|e| format!("Failed to parse knowledge JSON: {}", e))?;
+    let mut installed = Ok(());
+    for resource_key in &valid {
+        let target = data_root.join(resource_key);
+        if let Err(e) = target.parent().map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::rename(staging.join(resource_key), &target))
+        {
+            installed = Err(format!("Failed to move {:?} into place: {}", target, e));
+            break;
+        }
+    }
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        println!("Failed to remove import staging directory {:?}: {}", staging, e);
+    }
+    installed?;
 
-            knowledge_entries.push(json);
+    {
+        let mut config = state.config.lock().unwrap();
+        let before = config.llm_providers.len();
+        for provider in accepted {
+            let alias = provider.alias.clone();
+            // Keys were stripped on export
+            if let Err(e) = add_provider_to_config(&mut config, provider) {
+                skipped_providers.push(format!("{}: {}", alias, e));
+            }
+        }
+        if config.llm_providers.len() != before {
+            save_config_to_file(&config)?;
         }
     }
 
-    Ok(knowledge_entries)
+    // Imported threads get a fresh identity so they never overwrite a local thread
+    let now = chrono::Utc::now().to_rfc3339();
+    thread.id = uuid::Uuid::new_v4().to_string();
+    thread.agent_state = None;
+    thread.updated_at = now;
+    save_thread(&data_root, &thread)?;
+
+    for skipped in skipped_providers.iter().chain(&invalid_resources) {
+        println!("Skipped from thread bundle: {}", skipped);
+    }
+    println!("Thread bundle imported: {:?} -> {}", bundle_path, thread.id);
+    Ok(ThreadBundleImport { thread, skipped_providers, invalid_resources })
 }
 
 #[tauri::command]
-pub async fn list_actions(
+pub async fn agent_ask(
+    request: AgentAskRequest,
     state: State<'_, AppState>,
-) -> Result<Vec<serde_json::Value>, String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let actions_dir = data_root.join("actions");
+) -> Result<String, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
 
-    if !actions_dir.exists() {
-        return Ok(vec![]);
+    let thread = load_thread(&data_root, &request.thread_id).ok();
+
+    // The agent runs in the override directory for this request only
+    let working_dir = match request.working_dir_override.as_deref().map(str::trim).filter(|dir| !dir.is_empty()) {
+        Some(dir) => {
+            let path = Path::new(dir);
+            if !path.is_dir() {
+                return Err(format!("Working directory {:?} does not exist", path));
+            }
+            check_directory_writable(path)?;
+            Some(dir.to_string())
+        }
+        None => thread.as_ref().map(|thread| thread.working_dir.clone()),
+    };
+
+    let thread_config = thread.and_then(|thread| thread.config);
+
+    // An empty mode means the thread's default
+    let execution_mode: ExecutionMode = if request.execution_mode.trim().is_empty() {
+        thread_config.as_ref()
+            .map(|config| config.execution_mode)
+            .unwrap_or_default()
+    } else {
+        request.execution_mode.parse()?
+    };
+
+    // Call Node.js agent
+    let mut data = serde_json::json!({
+        "text": request.text,
+        "files": request.files,
+        "execution_mode": execution_mode
+    });
+    if let Some(working_dir) = working_dir {
+        data["working_dir"] = serde_json::json!(working_dir);
+    }
+    if let Some(system_prompt) = thread_config.and_then(|config| config.system_prompt) {
+        data["system_prompt"] = serde_json::json!(system_prompt);
     }
+    let node_request = NodeAgentRequest {
+        action: "ask_agent".to_string(),
+        thread_id: request.thread_id.clone(),
+        data,
+    };
 
-    let mut actions = Vec::new();
+    let started_at = chrono::Utc::now();
 
-    for entry in fs::read_dir(actions_dir).map_err(|e| format!("Failed to read actions directory: {}", e))? {
-        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let path = entry.path();
+    // The agent talks to the thread's providers, so it counts against the request cap too
+    let response = {
+        let _permit = request_semaphore(&state).acquire_owned().await;
+        call_node_agent(node_request).await?
+    };
 
-        if path.is_dir() {
-            let meta_file = path.join("meta.json");
-            if meta_file.exists() {
-                let content = fs::read_to_string(&meta_file)
-                    .map_err(|e| format!("Failed to read action meta file: {}", e))?;
-                let mut json: serde_json::Value = serde_json::from_str(&content)
-                    .map_err(|e| format!("Failed to parse action meta JSON: {}", e))?;
-
-                // Add directory_name to the action data
-                if let Some(dir_name) = path.file_name().and_then(|n| n.to_str()) {
-                    let json_obj = json.as_object_mut().unwrap();
-                    json_obj.insert("directory_name".to_string(), serde_json::Value::String(dir_name.to_string()));
+    if response.success {
+        if let Some(data) = response.data {
+            if let Some(run_id) = data.get("run_id").and_then(|v| v.as_str()) {
+                let thread_config = load_thread(&data_root, &request.thread_id).ok()
+                    .and_then(|thread| thread.config);
+                if let Some(config) = &thread_config {
+                    let mut aliases = vec![config.planner_llm_alias.clone()];
+                    if !alias_matches(&config.decider_llm_alias, &config.planner_llm_alias) {
+                        aliases.push(config.decider_llm_alias.clone());
+                    }
+                    record_provider_usage(&data_root, &aliases);
                 }
-
-                actions.push(json);
+                let planner_alias = thread_config.map(|config| config.planner_llm_alias);
+                save_run_metrics(&data_root, &RunMetrics {
+                    run_id: run_id.to_string(),
+                    thread_id: request.thread_id.clone(),
+                    planner_alias,
+                    started_at: started_at.to_rfc3339(),
+                    first_token_at: None,
+                    completed_at: None,
+                    first_token_ms: None,
+                    total_ms: None,
+                    prompt_tokens: None,
+                    completion_tokens: None,
+                });
+                return Ok(run_id.to_string());
             }
         }
+        Err("Invalid response from agent".to_string())
+    } else {
+        Err(response.error.unwrap_or_else(|| "Unknown error".to_string()))
     }
-
-    Ok(actions)
 }
 
 #[tauri::command]
-pub async fn test_llm_provider(
-    provider: LLMProvider,
+pub async fn get_agent_report(
+    run_id: String,
+    state: State<'_, AppState>,
 ) -> Result<serde_json::Value, String> {
-    // For now, return a mock test response
-    // In a real implementation, this would make an actual API call to test the provider
-    println!("Testing provider: {} ({})", provider.name, provider.alias);
+    let node_request = NodeAgentRequest {
+        action: "get_report".to_string(),
+        thread_id: "".to_string(), // Not used for this operation
+        data: serde_json::json!({
+            "run_id": run_id
+        }),
+    };
 
-    // Simulate a basic test
-    let test_result = serde_json::json!({
-        "success": true,
-        "provider": provider.name,
-        "alias": provider.alias,
-        "model": provider.model,
-        "base_url": provider.base_url,
-        "response_time_ms": 150,
-        "test_message": "Connection test successful",
-        "timestamp": chrono::Utc::now().to_rfc3339()
-    });
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let response = call_node_agent(node_request).await?;
 
-    Ok(test_result)
+    if response.success {
+        let report = response.data.ok_or_else(|| "No data returned".to_string())?;
+        if report.get("status").and_then(|v| v.as_str()) == Some("completed") {
+            complete_run_metrics(&data_root, &run_id, &report);
+        }
+        Ok(report)
+    } else {
+        Err(response.error.unwrap_or_else(|| "Unknown error".to_string()))
+    }
 }
 
-#[tauri::command]
-pub async fn export_providers(
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let config = state.config.lock().unwrap();
-    let export_data = serde_json::json!({
-        "version": "1.0",
-        "exported_at": chrono::Utc::now().to_rfc3339(),
-        "providers": config.llm_providers
-    });
+// Run Metrics
 
-    serde_json::to_string_pretty(&export_data)
-        .map_err(|e| format!("Failed to serialize providers: {}", e))
+// Timing and token usage of one agent run, stored as logs/runs/{run_id}.metrics.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunMetrics {
+    pub run_id: String,
+    pub thread_id: String,
+    pub planner_alias: Option<String>,
+    pub started_at: String,
+    pub first_token_at: Option<String>,
+    pub completed_at: Option<String>,
+    pub first_token_ms: Option<u64>,
+    pub total_ms: Option<u64>,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
 }
 
-#[tauri::command]
-pub async fn import_providers(
-    providers_json: String,
-    state: State<'_, AppState>,
-) -> Result<usize, String> {
-    let import_data: serde_json::Value = serde_json::from_str(&providers_json)
-        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderLatencyStats {
+    pub alias: String,
+    pub completed_runs: usize,
+    pub avg_total_ms: Option<f64>,
+    pub avg_first_token_ms: Option<f64>,
+}
 
-    let providers = import_data.get("providers")
-        .and_then(|p| p.as_array())
-        .ok_or("Invalid format: missing providers array")?;
+fn run_metrics_path(data_root: &Path, run_id: &str) -> PathBuf {
+    data_root.join("logs").join("runs").join(format!("{}.metrics.json", run_id))
+}
 
-    let mut config = state.config.lock().unwrap();
-    let mut imported_count = 0;
+// Metrics are best effort; a failed write never fails the run
+fn save_run_metrics(data_root: &Path, metrics: &RunMetrics) {
+    let path = run_metrics_path(data_root, &metrics.run_id);
+    let result = path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .map_err(|e| e.to_string())
+        .and_then(|_| serde_json::to_string_pretty(metrics).map_err(|e| e.to_string()))
+        .and_then(|content| write_atomic(&path, &content));
+
+    if let Err(e) = result {
+        println!("Failed to save metrics for run {}: {}", metrics.run_id, e);
+    }
+}
 
-    for provider_value in providers {
-        if let Ok(provider) = serde_json::from_value::<LLMProvider>(provider_value.clone()) {
-            // Check if alias already exists
-            if !config.llm_providers.iter().any(|p| p.alias == provider.alias) {
-                config.llm_providers.push(provider);
-                imported_count += 1;
-            }
-        }
+fn load_run_metrics(data_root: &Path, run_id: &str) -> Option<RunMetrics> {
+    let content = read_resource_text(run_metrics_path(data_root, run_id)).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+fn millis_since(start: &str, end: &chrono::DateTime<chrono::Utc>) -> Option<u64> {
+    let start = chrono::DateTime::parse_from_rfc3339(start).ok()?;
+    u64::try_from(end.signed_duration_since(start).num_milliseconds()).ok()
+}
+
+// Record completion time and token usage the first time a run is reported as completed.
+// The time comes from the report's "completed_at", since the report may be polled long after
+// the run ended; it may also carry "first_token_at" and a "usage" object with token counts.
+fn complete_run_metrics(data_root: &Path, run_id: &str, report: &serde_json::Value) {
+    let Some(mut metrics) = load_run_metrics(data_root, run_id) else {
+        return;
+    };
+    if metrics.completed_at.is_some() {
+        return;
     }
+    let Some(completed_at) = report.get("completed_at").and_then(|v| v.as_str()) else {
+        return;
+    };
 
-    if imported_count > 0 {
-        save_config_to_file(&*config)?;
+    metrics.total_ms = chrono::DateTime::parse_from_rfc3339(completed_at).ok()
+        .and_then(|at| millis_since(&metrics.started_at, &at.with_timezone(&chrono::Utc)));
+    metrics.completed_at = Some(completed_at.to_string());
+
+    if let Some(first_token_at) = report.get("first_token_at").and_then(|v| v.as_str()) {
+        metrics.first_token_ms = chrono::DateTime::parse_from_rfc3339(first_token_at).ok()
+            .and_then(|at| millis_since(&metrics.started_at, &at.with_timezone(&chrono::Utc)));
+        metrics.first_token_at = Some(first_token_at.to_string());
     }
 
-    Ok(imported_count)
+    let usage = &report["usage"];
+    metrics.prompt_tokens = usage["prompt_tokens"].as_u64();
+    metrics.completion_tokens = usage["completion_tokens"].as_u64();
+
+    save_run_metrics(data_root, &metrics);
 }
 
-// Knowledge Management Commands
 #[tauri::command]
-pub async fn load_knowledge(
-    filename: String,
+pub async fn get_run_metrics(
+    run_id: String,
     state: State<'_, AppState>,
-) -> Result<serde_json::Value, String> {
+) -> Result<RunMetrics, String> {
     let data_root = &state.config.lock().unwrap().data_root;
-    let knowledge_dir = data_root.join("knowledge");
-    let knowledge_path = knowledge_dir.join(&filename);
-
-    // Validate filename to prevent directory traversal
-    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
-        return Err("Invalid filename".to_string());
-    }
 
-    if !knowledge_path.exists() {
-        return Err(format!("Knowledge file '{}' not found", filename));
+    // Validate run id to prevent directory traversal
+    if run_id.contains("..") || run_id.contains("/") || run_id.contains("\\") {
+        return Err("Invalid run id".to_string());
     }
 
-    let content = fs::read_to_string(&knowledge_path)
-        .map_err(|e| format!("Failed to read knowledge file: {}", e))?;
-
-    let json: serde_json::Value = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse knowledge JSON: {}", e))?;
-
-    Ok(json)
+    load_run_metrics(data_root, &run_id)
+        .ok_or_else(|| format!("No metrics found for run '{}'", run_id))
 }
 
 #[tauri::command]
-pub async fn save_knowledge(
-    filename: String,
-    knowledge_data: serde_json::Value,
+pub async fn get_provider_latency_stats(
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Vec<ProviderLatencyStats>, String> {
     let data_root = &state.config.lock().unwrap().data_root;
-    let knowledge_dir = data_root.join("knowledge");
-
-    // Validate filename to prevent directory traversal
-    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
-        return Err("Invalid filename".to_string());
+    let runs_dir = data_root.join("logs").join("runs");
+
+    let mut by_alias: HashMap<String, Vec<RunMetrics>> = HashMap::new();
+    if let Ok(entries) = fs::read_dir(&runs_dir) {
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(run_id) = name.strip_suffix(".metrics.json") else {
+                continue;
+            };
+            if let Some(metrics) = load_run_metrics(data_root, run_id) {
+                if let (Some(alias), Some(_)) = (metrics.planner_alias.clone(), metrics.total_ms) {
+                    by_alias.entry(alias).or_default().push(metrics);
+                }
+            }
+        }
     }
 
-    // Ensure filename ends with .json
-    let filename = if filename.ends_with(".json") {
-        filename
-    } else {
-        format!("{}.json", filename)
+    let average = |values: Vec<u64>| {
+        (!values.is_empty()).then(|| values.iter().sum::<u64>() as f64 / values.len() as f64)
     };
 
-    // Ensure knowledge directory exists
-    fs::create_dir_all(&knowledge_dir)
-        .map_err(|e| format!("Failed to create knowledge directory: {}", e))?;
+    let mut stats: Vec<ProviderLatencyStats> = by_alias.into_iter()
+        .map(|(alias, runs)| ProviderLatencyStats {
+            alias,
+            completed_runs: runs.len(),
+            avg_total_ms: average(runs.iter().filter_map(|r| r.total_ms).collect()),
+            avg_first_token_ms: average(runs.iter().filter_map(|r| r.first_token_ms).collect()),
+        })
+        .collect();
+    stats.sort_by(|a, b| a.alias.cmp(&b.alias));
+
+    Ok(stats)
+}
 
-    let knowledge_path = knowledge_dir.join(&filename);
+// Per-alias call counts kept in provider_usage.json, bumped by the agent and test paths
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ProviderUsage {
+    calls: u64,
+    last_used: Option<String>,
+}
 
-    // Validate knowledge structure
-    validate_knowledge_structure(&knowledge_data)?;
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderUsageStats {
+    pub alias: String,
+    pub calls: u64,
+    pub last_used: Option<String>,
+}
 
-    let content = serde_json::to_string_pretty(&knowledge_data)
-        .map_err(|e| format!("Failed to serialize knowledge data: {}", e))?;
+// Serializes read-modify-writes of provider_usage.json across concurrent calls
+static PROVIDER_USAGE_LOCK: Mutex<()> = Mutex::new(());
 
-    fs::write(&knowledge_path, content)
-        .map_err(|e| format!("Failed to write knowledge file: {}", e))?;
+fn provider_usage_path(data_root: &Path) -> PathBuf {
+    data_root.join("provider_usage.json")
+}
 
-    println!("Knowledge saved to: {:?}", knowledge_path);
-    Ok(())
+fn load_provider_usage(data_root: &Path) -> BTreeMap<String, ProviderUsage> {
+    read_resource_text(provider_usage_path(data_root)).ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+// Usage tracking is best effort; a failed write never fails the call being counted
+fn record_provider_usage(data_root: &Path, aliases: &[String]) {
+    if aliases.is_empty() {
+        return;
+    }
+    let _guard = PROVIDER_USAGE_LOCK.lock().unwrap();
+    let mut usage = load_provider_usage(data_root);
+    let now = chrono::Utc::now().to_rfc3339();
+    for alias in aliases {
+        let entry = usage.entry(alias.trim().to_lowercase()).or_default();
+        entry.calls += 1;
+        entry.last_used = Some(now.clone());
+    }
+
+    let result = serde_json::to_string_pretty(&usage)
+        .map_err(|e| e.to_string())
+        .and_then(|content| write_atomic(&provider_usage_path(data_root), &content));
+    if let Err(e) = result {
+        println!("Failed to record provider usage: {}", e);
+    }
 }
 
 #[tauri::command]
-pub async fn delete_knowledge(
-    filename: String,
+pub async fn get_provider_usage_stats(
     state: State<'_, AppState>,
-) -> Result<(), String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let knowledge_dir = data_root.join("knowledge");
-    let knowledge_path = knowledge_dir.join(&filename);
+) -> Result<Vec<ProviderUsageStats>, String> {
+    let config = state.config.lock().unwrap();
+    let usage = load_provider_usage(&config.data_root);
+
+    // Every configured provider is listed, including ones that were never used
+    let mut stats: Vec<ProviderUsageStats> = config.llm_providers.iter()
+        .map(|provider| {
+            let entry = usage.get(&provider.alias.trim().to_lowercase()).cloned().unwrap_or_default();
+            ProviderUsageStats {
+                alias: provider.alias.clone(),
+                calls: entry.calls,
+                last_used: entry.last_used,
+            }
+        })
+        .collect();
+    stats.sort_by(|a, b| a.alias.cmp(&b.alias));
 
-    // Validate filename to prevent directory traversal
-    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
-        return Err("Invalid filename".to_string());
-    }
+    Ok(stats)
+}
 
-    if !knowledge_path.exists() {
-        return Err(format!("Knowledge file '{}' not found", filename));
+// Feedback for a run lives in feedback/{action_run_id}.jsonl, one submission per line
+fn feedback_path(data_root: &Path, action_run_id: &str) -> Result<PathBuf, String> {
+    if action_run_id.trim().is_empty() || action_run_id.contains("..") || action_run_id.contains("/") || action_run_id.contains("\\") {
+        return Err("Invalid action run id".to_string());
     }
+    Ok(data_root.join("feedback").join(format!("{}.jsonl", action_run_id)))
+}
 
-    fs::remove_file(&knowledge_path)
-        .map_err(|e| format!("Failed to delete knowledge file: {}", e))?;
-
-    println!("Knowledge deleted: {:?}", knowledge_path);
+#[tauri::command]
+pub async fn submit_feedback(
+    action_run_id: String,
+    feedback: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let path = feedback_path(data_root, &action_run_id)?;
+
+    let mut line = serde_json::json!({
+        "ts": chrono::Utc::now().to_rfc3339(),
+        "action_run_id": action_run_id,
+        "feedback": feedback,
+    }).to_string();
+    line.push('\n');
+
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::OpenOptions::new().create(true).append(true).open(&path))
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .map_err(|e| format!("Failed to save feedback: {}", e))?;
+
+    println!("Feedback submitted for action {}", action_run_id);
     Ok(())
 }
 
 #[tauri::command]
-pub async fn create_knowledge_directory(
+pub async fn list_feedback(
+    action_run_id: String,
     state: State<'_, AppState>,
-) -> Result<(), String> {
+) -> Result<Vec<serde_json::Value>, String> {
     let data_root = &state.config.lock().unwrap().data_root;
-    let knowledge_dir = data_root.join("knowledge");
+    let path = feedback_path(data_root, &action_run_id)?;
 
-    fs::create_dir_all(&knowledge_dir)
-        .map_err(|e| format!("Failed to create knowledge directory: {}", e))?;
+    if !path.exists() {
+        return Ok(vec![]);
+    }
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read feedback: {}", e))?;
 
-    // Create a default knowledge entry if directory is empty
-    let default_knowledge = serde_json::json!({
-        "meta": {
-            "name": "Software Development Knowledge",
-            "version": "1.0.0",
-            "domain": "development"
-        },
-        "entries": [
-            {
-                "name": "React Best Practices",
-                "description": "Essential practices for React development",
-                "content": "Use functional components with hooks, follow component composition patterns, implement proper error handling, use React.memo for performance optimization, follow proper state management patterns."
-            },
-            {
-                "name": "TypeScript Guidelines",
-                "description": "TypeScript development standards and conventions",
-                "content": "Use strict type checking, prefer interfaces over types for object shapes, use generics for reusable code, avoid 'any' type, implement proper error boundaries, use type guards for runtime type checking."
-            },
-            {
-                "name": "Git Workflow",
-                "description": "Version control best practices and workflows",
-                "content": "Use feature branches for new development, write clear commit messages, use pull requests for code review, maintain clean commit history, resolve conflicts properly, use semantic versioning."
-            }
-        ]
-    });
+    // A torn last line from an interrupted write is skipped
+    Ok(content.lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
 
-    let default_path = knowledge_dir.join("software-development.json");
 
-    if !default_path.exists() {
-        let content = serde_json::to_string_pretty(&default_knowledge)
-            .map_err(|e| format!("Failed to serialize default knowledge: {}", e))?;
+#[tauri::command]
+pub async fn get_all_llm_providers(
+    state: State<'_, AppState>,
+) -> Result<Vec<LLMProvider>, String> {
+    let config = state.config.lock().unwrap();
+    Ok(config.llm_providers.clone())
+}
 
-        fs::write(&default_path, content)
-            .map_err(|e| format!("Failed to write default knowledge: {}", e))?;
+// Endpoint paths users paste by accident; provider calls append these themselves
+const ENDPOINT_PATH_SUFFIXES: [&str; 8] = [
+    "/chat/completions", "/completions", "/messages", "/models", "/embeddings",
+    "/api/chat", "/api/generate", "/api/tags",
+];
+
+// Add a missing scheme, drop trailing slashes and strip an accidental endpoint path
+fn clean_base_url(url: &str) -> Result<String, String> {
+    let trimmed = url.trim();
+    if trimmed.is_empty() {
+        return Err("Base URL cannot be empty".to_string());
+    }
 
-        println!("Default knowledge created at: {:?}", default_path);
+    let with_scheme = if trimmed.contains("://") {
+        trimmed.to_string()
+    } else if trimmed.starts_with("localhost") || trimmed.starts_with("127.0.0.1") {
+        // Local servers (e.g. Ollama) rarely serve TLS
+        format!("http://{}", trimmed)
+    } else {
+        format!("https://{}", trimmed)
+    };
+
+    let parsed = reqwest::Url::parse(&with_scheme)
+        .map_err(|e| format!("Invalid base URL '{}': {}", trimmed, e))?;
+    if parsed.scheme() != "http" && parsed.scheme() != "https" {
+        return Err(format!("Invalid base URL '{}': scheme must be http or https", trimmed));
+    }
+    let host = parsed.host_str()
+        .ok_or_else(|| format!("Invalid base URL '{}': missing host", trimmed))?;
+
+    let mut path = parsed.path().trim_end_matches('/');
+    if let Some(suffix) = ENDPOINT_PATH_SUFFIXES.iter().find(|s| path.ends_with(*s)) {
+        path = path[..path.len() - suffix.len()].trim_end_matches('/');
     }
 
+    let mut cleaned = format!("{}://{}", parsed.scheme(), host);
+    if let Some(port) = parsed.port() {
+        cleaned.push_str(&format!(":{}", port));
+    }
+    cleaned.push_str(path);
+    if let Some(query) = parsed.query() {
+        cleaned.push_str(&format!("?{}", query));
+    }
+
+    Ok(cleaned)
+}
+
+#[tauri::command]
+pub async fn normalize_base_url(
+    url: String,
+) -> Result<String, String> {
+    clean_base_url(&url)
+}
+
+// Aliases are compared case-insensitively so "GPT-4" and "gpt-4" can't coexist
+fn alias_matches(a: &str, b: &str) -> bool {
+    a.trim().to_lowercase() == b.trim().to_lowercase()
+}
+
+// Locked providers refuse edits and deletion until an administrator unlocks them
+fn ensure_unlocked(provider: &LLMProvider) -> Result<(), String> {
+    if provider.locked {
+        return Err(format!(
+            "Provider '{}' is locked and can't be changed; ask an administrator to unlock it",
+            provider.alias
+        ));
+    }
     Ok(())
 }
 
-// Helper function to validate knowledge structure
-fn validate_knowledge_structure(knowledge: &serde_json::Value) -> Result<(), String> {
-    // Check for required meta section
-    let meta = knowledge.get("meta").ok_or("Missing 'meta' section")?;
+// Trim the alias, reject duplicates and append the provider to the config
+fn add_provider_to_config(config: &mut AppConfig, mut provider: LLMProvider) -> Result<(), String> {
+    provider.alias = provider.alias.trim().to_string();
+    provider.base_url = clean_base_url(&provider.base_url)?;
+
+    if provider.alias.is_empty() {
+        return Err("Provider alias cannot be empty".to_string());
+    }
+    provider_header_map(&provider)?;
+    // Providers coming from import files or bundles only stay locked in admin mode
+    if !admin_from_env() {
+        provider.locked = false;
+    }
 
-    // Check required meta fields
-    if !meta.get("name").and_then(|v| v.as_str()).is_some() {
-        return Err("Missing or invalid 'meta.name' field".to_string());
+    // Check if alias already exists
+    if config.llm_providers.iter().any(|p| alias_matches(&p.alias, &provider.alias)) {
+        return Err(format!("Provider with alias '{}' already exists", provider.alias));
     }
 
-    if !meta.get("version").and_then(|v| v.as_str()).is_some() {
-        return Err("Missing or invalid 'meta.version' field".to_string());
+    config.llm_providers.push(provider);
+    Ok(())
+}
+
+// Normalized (base_url, model) pair identifying where a provider sends requests
+fn provider_endpoint(provider: &LLMProvider) -> (String, String) {
+    (
+        clean_base_url(&provider.base_url).unwrap_or_else(|_| provider.base_url.clone()).to_lowercase(),
+        provider.model.trim().to_lowercase(),
+    )
+}
+
+// Providers with the same fingerprint behave identically whatever their alias
+fn provider_fingerprint(provider: &LLMProvider) -> String {
+    let (base_url, model) = provider_endpoint(provider);
+    format!("{}|{}|{}|{}", provider.provider.trim().to_lowercase(), base_url, model, provider.temperature)
+}
+
+// Non-fatal problems with a provider entry, returned to the settings UI after saving
+fn provider_warnings(provider: &LLMProvider, others: &[LLMProvider]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    if let Err(warning) = validate_max_tokens(&provider.model, provider.max_tokens) {
+        warnings.push(warning);
     }
 
-    // Check for entries array
-    let entries = knowledge.get("entries").and_then(|v| v.as_array())
-        .ok_or("Missing or invalid 'entries' array")?;
+    // Same endpoint and model under another alias is allowed (e.g. different temperatures) but usually a mistake
+    let duplicates: Vec<&str> = others.iter()
+        .filter(|other| !alias_matches(&other.alias, &provider.alias) && provider_endpoint(other) == provider_endpoint(provider))
+        .map(|other| other.alias.as_str())
+        .collect();
+    if !duplicates.is_empty() {
+        warnings.push(format!(
+            "Provider '{}' uses the same base_url and model as: {}",
+            provider.alias, duplicates.join(", ")
+        ));
+    }
+    warnings
+}
 
-    if entries.is_empty() {
-        return Err("Entries array cannot be empty".to_string());
+// Warn when the endpoint lists its models and the provider's model isn't one of them.
+// Offline or unlisted endpoints give no warning, so configuring without network still works
+async fn model_availability_warning(provider: &LLMProvider, proxy: Option<&str>) -> Option<String> {
+    let base_url = clean_base_url(&provider.base_url).unwrap_or_else(|_| provider.base_url.clone());
+    let models = match provider.provider.as_str() {
+        "ollama" => fetch_ollama_models(&base_url, proxy).await,
+        "openai_compatible" => fetch_provider_models(&base_url, provider.api_key.as_deref(), proxy).await,
+        _ => return None,
+    };
+    let models = match models {
+        Ok(models) if !models.is_empty() => models,
+        Ok(_) => return None,
+        Err(e) => {
+            println!("Skipping model check for {}: {}", provider.alias, e);
+            return None;
+        }
+    };
+
+    let model = provider.model.trim();
+    // Ollama lists "llama3.2:latest" for a model configured as "llama3.2"
+    let listed = models.iter().any(|m| {
+        m.eq_ignore_ascii_case(model) || m.strip_suffix(":latest").is_some_and(|m| m.eq_ignore_ascii_case(model))
+    });
+    (!listed).then(|| format!(
+        "Model '{}' is not in the models listed by {}; calls to provider '{}' may fail",
+        model, base_url, provider.alias
+    ))
+}
+
+#[tauri::command]
+pub async fn add_llm_provider(
+    provider: LLMProvider,
+    check_model: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    ensure_writable(&state)?;
+    if provider.locked && !admin_from_env() {
+        return Err("Only an administrator can add a locked provider".to_string());
     }
 
-    // Validate each entry
-    for (index, entry) in entries.iter().enumerate() {
-        let entry_path = format!("entries[{}]", index);
+    // Ask the endpoint before taking the config lock
+    let model_warning = if check_model.unwrap_or(false) {
+        let proxy = state.config.lock().unwrap().http_proxy.clone();
+        let _permit = request_semaphore(&state).acquire_owned().await;
+        model_availability_warning(&provider, proxy.as_deref()).await
+    } else {
+        None
+    };
+
+    let mut config = state.config.lock().unwrap();
+    let mut warnings = provider_warnings(&provider, &config.llm_providers);
+    warnings.extend(model_warning);
+    add_provider_to_config(&mut config, provider)?;
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(warnings)
+}
+
+#[tauri::command]
+pub async fn update_llm_provider(
+    id: String,
+    mut provider: LLMProvider,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    provider.alias = provider.alias.trim().to_string();
+    if provider.alias.is_empty() {
+        return Err("Provider alias cannot be empty".to_string());
+    }
+    provider.base_url = clean_base_url(&provider.base_url)?;
+    provider_header_map(&provider)?;
 
-        if !entry.get("name").and_then(|v| v.as_str()).is_some() {
-            return Err(format!("Missing or invalid '{}.name' field", entry_path));
+    // Find and update provider by alias (using alias as ID for simplicity)
+    if let Some(index) = config.llm_providers.iter().position(|p| alias_matches(&p.alias, &id)) {
+        ensure_unlocked(&config.llm_providers[index])?;
+        if provider.locked {
+            return Err("Use set_provider_locked to lock a provider".to_string());
         }
 
-        if !entry.get("description").and_then(|v| v.as_str()).is_some() {
-            return Err(format!("Missing or invalid '{}.description' field", entry_path));
+        // The new alias must not collide with any other provider
+        let collides = config.llm_providers.iter().enumerate()
+            .any(|(i, p)| i != index && alias_matches(&p.alias, &provider.alias));
+        if collides {
+            return Err(format!("Provider with alias '{}' already exists", provider.alias));
         }
 
-        if !entry.get("content").and_then(|v| v.as_str()).is_some() {
-            return Err(format!("Missing or invalid '{}.content' field", entry_path));
+        let mut others = config.llm_providers.clone();
+        others.remove(index);
+        let warnings = provider_warnings(&provider, &others);
+        config.llm_providers[index] = provider;
+        save_config_to_file(&config)?;
+        Ok(warnings)
+    } else {
+        Err(format!("Provider with alias '{}' not found", id))
+    }
+}
+
+#[tauri::command]
+pub async fn patch_llm_provider(
+    alias: String,
+    patch: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    let index = config.llm_providers.iter()
+        .position(|p| alias_matches(&p.alias, &alias))
+        .ok_or_else(|| format!("Provider with alias '{}' not found", alias))?;
+    ensure_unlocked(&config.llm_providers[index])?;
+    let patch = patch.as_object().ok_or("Provider patch must be a JSON object")?;
+
+    // Overlay only the fields present in the patch onto the stored provider
+    let mut merged = serde_json::to_value(&config.llm_providers[index])
+        .map_err(|e| format!("Failed to serialize provider: {}", e))?;
+    let fields = merged.as_object_mut().ok_or("Failed to serialize provider")?;
+    for (key, value) in patch {
+        if !fields.contains_key(key) {
+            return Err(format!("Unknown provider field: {}", key));
+        }
+        if key == "locked" {
+            return Err("Use set_provider_locked to lock a provider".to_string());
         }
+        // A null key is treated as "not sent"; clear_provider_key removes keys explicitly
+        if key == "api_key" && value.is_null() {
+            continue;
+        }
+        fields.insert(key.clone(), value.clone());
+    }
+    let mut provider: LLMProvider = serde_json::from_value(merged)
+        .map_err(|e| format!("Invalid provider patch: {}", e))?;
+
+    // Validate the merged result the same way update_llm_provider does
+    provider.alias = provider.alias.trim().to_string();
+    if provider.alias.is_empty() {
+        return Err("Provider alias cannot be empty".to_string());
+    }
+    provider.base_url = clean_base_url(&provider.base_url)?;
+    provider_header_map(&provider)?;
+    let collides = config.llm_providers.iter().enumerate()
+        .any(|(i, p)| i != index && alias_matches(&p.alias, &provider.alias));
+    if collides {
+        return Err(format!("Provider with alias '{}' already exists", provider.alias));
+    }
+
+    let mut others = config.llm_providers.clone();
+    others.remove(index);
+    let warnings = provider_warnings(&provider, &others);
+    config.llm_providers[index] = provider;
+    save_config_to_file(&config)?;
+    Ok(warnings)
+}
+
+#[tauri::command]
+pub async fn set_provider_locked(
+    alias: String,
+    locked: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    if !admin_from_env() {
+        return Err(format!("Locking providers requires admin mode ({}=1)", ADMIN_ENV));
     }
+    let mut config = state.config.lock().unwrap();
 
+    let provider = config.llm_providers.iter_mut()
+        .find(|p| alias_matches(&p.alias, &alias))
+        .ok_or_else(|| format!("Provider with alias '{}' not found", alias))?;
+    provider.locked = locked;
+
+    save_config_to_file(&config)?;
+    println!("Provider {} {}", alias, if locked { "locked" } else { "unlocked" });
     Ok(())
 }
 
-// Action Management Commands
+#[tauri::command]
+pub async fn clear_provider_key(
+    alias: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    let provider = config.llm_providers.iter_mut()
+        .find(|p| alias_matches(&p.alias, &alias))
+        .ok_or_else(|| format!("Provider with alias '{}' not found", alias))?;
+    ensure_unlocked(provider)?;
+    provider.api_key = None;
+
+    save_config_to_file(&config)?;
+    println!("API key cleared for provider: {}", alias);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn rotate_provider_key(
+    alias: String,
+    new_key: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    let new_key = new_key.trim().to_string();
+    if new_key.is_empty() {
+        return Err("New API key cannot be empty; use clear_provider_key to remove the key".to_string());
+    }
+
+    let provider = config.llm_providers.iter_mut()
+        .find(|p| alias_matches(&p.alias, &alias))
+        .ok_or_else(|| format!("Provider with alias '{}' not found", alias))?;
+    ensure_unlocked(provider)?;
+    provider.api_key = Some(new_key);
+
+    save_config_to_file(&config)?;
+    // Never log the key itself
+    println!("API key replaced for provider: {}", alias);
+    Ok(())
+}
+
+// .env variables recognised as provider keys: (variable, provider kind, base_url host it must contain)
+const ENV_KEY_VARIABLES: [(&str, &str, Option<&str>); 10] = [
+    ("OPENAI_API_KEY", "openai_compatible", Some("api.openai.com")),
+    ("ANTHROPIC_API_KEY", "anthropic", None),
+    ("GEMINI_API_KEY", "google_gemini", None),
+    ("GOOGLE_API_KEY", "google_gemini", None),
+    ("GROQ_API_KEY", "openai_compatible", Some("groq.com")),
+    ("OPENROUTER_API_KEY", "openai_compatible", Some("openrouter.ai")),
+    ("MISTRAL_API_KEY", "openai_compatible", Some("mistral.ai")),
+    ("DEEPSEEK_API_KEY", "openai_compatible", Some("deepseek.com")),
+    ("TOGETHER_API_KEY", "openai_compatible", Some("together")),
+    ("XAI_API_KEY", "openai_compatible", Some("x.ai")),
+];
+
+// KEY=value pairs of a .env file; handles `export`, comments and quoted values
+fn parse_env_file(content: &str) -> Vec<(String, String)> {
+    content.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or_default(),
+                // An unquoted value ends at an inline comment
+                _ => value.split(" #").next().unwrap_or_default().trim_end(),
+            };
+            Some((key.trim().to_string(), value.to_string()))
+        })
+        .collect()
+}
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ActionStatus {
-    pub status: String, // "healthy", "error", "disabled"
-    pub last_error: Option<ActionError>,
-    pub error_count: u32,
-    pub last_success: Option<String>, // ISO8601 timestamp
+pub struct EnvKeyAssignment {
+    pub alias: String,
+    pub variable: String,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
-pub struct ActionError {
-    pub message: String,
-    pub timestamp: String, // ISO8601
-    pub execution_id: String,
+pub struct EnvKeyImportSummary {
+    // Providers that received a key; key values are never returned
+    pub updated: Vec<EnvKeyAssignment>,
+    // Recognised variables that no provider matched
+    pub unmatched_variables: Vec<String>,
+    // Matching providers left alone because they are locked
+    pub locked: Vec<String>,
 }
 
 #[tauri::command]
-pub async fn import_action_directory(
-    source_path: String,
+pub async fn import_keys_from_env_file(
+    path: String,
     state: State<'_, AppState>,
-) -> Result<String, String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let actions_dir = data_root.join("actions");
+) -> Result<EnvKeyImportSummary, String> {
+    ensure_writable(&state)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let mut config = state.config.lock().unwrap();
 
-    // Ensure actions directory exists
-    fs::create_dir_all(&actions_dir)
-        .map_err(|e| format!("Failed to create actions directory: {}", e))?;
+    let mut summary = EnvKeyImportSummary { updated: vec![], unmatched_variables: vec![], locked: vec![] };
+    for (variable, value) in parse_env_file(&content) {
+        let Some((_, kind, host)) = ENV_KEY_VARIABLES.iter().find(|(name, _, _)| *name == variable) else {
+            continue;
+        };
+        if value.is_empty() {
+            continue;
+        }
 
-    // Validate the source directory
-    let validation_result = validate_action_directory_internal(&source_path)?;
-    if !validation_result.is_valid {
-        return Err(format!("Invalid action directory: {}", validation_result.error.unwrap_or_else(|| "Unknown error".to_string())));
+        let mut matched = false;
+        for provider in config.llm_providers.iter_mut() {
+            let host_matches = host.is_none_or(|host| provider.base_url.to_lowercase().contains(host));
+            if provider.provider != *kind || !host_matches {
+                continue;
+            }
+            matched = true;
+            if provider.locked {
+                summary.locked.push(provider.alias.clone());
+                continue;
+            }
+            provider.api_key = Some(value.clone());
+            summary.updated.push(EnvKeyAssignment { alias: provider.alias.clone(), variable: variable.clone() });
+        }
+        if !matched {
+            summary.unmatched_variables.push(variable);
+        }
     }
 
-    // Get action name from meta.json
-    let meta_path = PathBuf::from(&source_path).join("meta.json");
-    let meta_content = fs::read_to_string(&meta_path)
-        .map_err(|e| format!("Failed to read meta.json: {}", e))?;
-    let meta: serde_json::Value = serde_json::from_str(&meta_content)
-        .map_err(|e| format!("Failed to parse meta.json: {}", e))?;
+    if !summary.updated.is_empty() {
+        save_config_to_file(&config)?;
+    }
+    // Never log the keys themselves
+    println!("Imported API keys from {} into {} providers", path, summary.updated.len());
+    Ok(summary)
+}
 
-    let action_name = meta.get("name")
-        .and_then(|v| v.as_str())
-        .ok_or("Missing action name in meta.json")?;
+#[tauri::command]
+pub async fn set_default_providers(
+    planner: String,
+    decider: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
 
-    // Create target directory
-    let target_dir = actions_dir.join(action_name);
-    if target_dir.exists() {
-        return Err(format!("Action '{}' already exists", action_name));
+    // Store each alias as spelled in the provider list
+    let find_alias = |alias: &str| {
+        config.llm_providers.iter()
+            .find(|p| alias_matches(&p.alias, alias))
+            .map(|p| p.alias.clone())
+            .ok_or_else(|| format!("Provider with alias '{}' not found", alias))
+    };
+    let planner = find_alias(&planner)?;
+    let decider = find_alias(&decider)?;
+
+    config.default_planner_alias = Some(planner);
+    config.default_decider_alias = Some(decider);
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+// Ids of saved threads whose planner or decider uses the given alias
+fn threads_referencing_alias(data_root: &Path, alias: &str) -> Result<Vec<String>, String> {
+    Ok(list_saved_threads(data_root)?
+        .into_iter()
+        .filter(|thread| thread.config.as_ref().is_some_and(|c| {
+            alias_matches(&c.planner_llm_alias, alias) || alias_matches(&c.decider_llm_alias, alias)
+        }))
+        .map(|thread| thread.id)
+        .collect())
+}
+
+#[tauri::command]
+pub async fn delete_llm_provider(
+    id: String,
+    force: bool,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    let provider = config.llm_providers.iter()
+        .find(|p| alias_matches(&p.alias, &id))
+        .ok_or_else(|| format!("Provider with alias '{}' not found", id))?;
+    ensure_unlocked(provider)?;
+
+    // Refuse to break threads that still use this provider unless forced
+    let affected_threads = threads_referencing_alias(&config.data_root, &id)?;
+    if !affected_threads.is_empty() && !force {
+        return Err(format!(
+            "Provider '{}' is used by threads: {}",
+            id,
+            affected_threads.join(", ")
+        ));
     }
 
-    // Copy directory recursively
-    copy_directory(&source_path, target_dir.to_str().ok_or("Invalid target path")?)?;
+    config.llm_providers.retain(|p| !alias_matches(&p.alias, &id));
 
-    // Initialize action status as healthy
-    let status_data = ActionStatus {
-        status: "healthy".to_string(),
-        last_error: None,
-        error_count: 0,
-        last_success: Some(chrono::Utc::now().to_rfc3339()),
+    // A deleted provider can't stay the default for new threads
+    let AppConfig { default_planner_alias, default_decider_alias, .. } = &mut *config;
+    for default in [default_planner_alias, default_decider_alias] {
+        if default.as_deref().is_some_and(|alias| alias_matches(alias, &id)) {
+            *default = None;
+        }
+    }
+    save_config_to_file(&config)?;
+
+    Ok(affected_threads)
+}
+
+#[tauri::command]
+pub async fn rename_provider_alias(
+    old_alias: String,
+    new_alias: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    let new_alias = new_alias.trim().to_string();
+    if new_alias.is_empty() {
+        return Err("Provider alias cannot be empty".to_string());
+    }
+
+    let data_root = {
+        let mut config = state.config.lock().unwrap();
+        let index = config.llm_providers.iter()
+            .position(|p| alias_matches(&p.alias, &old_alias))
+            .ok_or_else(|| format!("Provider with alias '{}' not found", old_alias))?;
+        ensure_unlocked(&config.llm_providers[index])?;
+
+        // Changing only the case of a provider's own alias is allowed
+        if config.llm_providers.iter().enumerate().any(|(i, p)| i != index && alias_matches(&p.alias, &new_alias)) {
+            return Err(format!("Provider with alias '{}' already exists", new_alias));
+        }
+
+        config.llm_providers[index].alias = new_alias.clone();
+        let AppConfig { default_planner_alias, default_decider_alias, .. } = &mut *config;
+        for default in [default_planner_alias, default_decider_alias] {
+            if default.as_deref().is_some_and(|alias| alias_matches(alias, &old_alias)) {
+                *default = Some(new_alias.clone());
+            }
+        }
+        save_config_to_file(&config)?;
+        config.data_root.clone()
     };
 
-    let status_path = target_dir.join("status.json");
-    let status_content = serde_json::to_string_pretty(&status_data)
-        .map_err(|e| format!("Failed to serialize action status: {}", e))?;
+    let updated = remap_thread_aliases(&data_root, &old_alias, &new_alias)?;
 
-    fs::write(&status_path, status_content)
-        .map_err(|e| format!("Failed to write action status: {}", e))?;
+    println!("Renamed provider alias {} -> {} ({} threads updated)", old_alias, new_alias, updated.len());
+    Ok(updated.len())
+}
 
-    println!("Action imported: {:?} -> {:?}", source_path, target_dir);
-    Ok(action_name.to_string())
+#[tauri::command]
+pub async fn save_config_to_file_public(
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap();
+    save_config_to_file(&config)
+}
+
+// Event telling the UI that configs.json was unreadable and has been reset to defaults
+const CONFIG_RESET_EVENT: &str = "config-reset";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigResetWarning {
+    pub error: String,
+    // Where the unreadable file was moved, if it could be moved
+    pub backup_path: Option<String>,
+}
+
+// Move an unreadable configs.json aside and write defaults in its place
+fn recover_corrupt_config(config_path: &Path, data_root: &Path, read_only: bool) -> (AppConfig, Option<PathBuf>) {
+    let defaults = AppConfig {
+        data_root: data_root.to_path_buf(),
+        ..AppConfig::default()
+    };
+    if read_only {
+        return (defaults, None);
+    }
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%SZ");
+    let backup_path = config_path.with_file_name(format!("configs.json.bak-{}", timestamp));
+    let backup_path = match fs::rename(config_path, &backup_path) {
+        Ok(()) => Some(backup_path),
+        Err(e) => {
+            println!("Failed to back up corrupt config {:?}: {}", config_path, e);
+            None
+        }
+    };
+
+    if let Err(e) = save_config_to_file(&defaults) {
+        println!("Failed to write default config: {}", e);
+    }
+    (defaults, backup_path)
+}
+
+#[tauri::command]
+pub async fn load_config_from_file(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<Vec<LLMProvider>, String> {
+    let mut config = state.config.lock().unwrap();
+    let config_path = config.data_root.join("configs.json");
+
+    let read_only = state.read_only.load(Ordering::SeqCst);
+
+    if !config_path.exists() {
+        // Create default config file if it doesn't exist
+        if !read_only {
+            save_config_to_file(&config)?;
+        }
+        return Ok(config.llm_providers.clone());
+    }
+
+    let content = read_resource_text(&config_path)
+        .map_err(|e| format!("Failed to read config file: {}", e))?;
+
+    let parsed = serde_json::from_str::<serde_json::Value>(&content)
+        .map_err(|e| format!("Failed to parse config file: {}", e))
+        .and_then(|raw| migrate_config(raw, &config));
+
+    match parsed {
+        Ok((loaded_config, upgraded)) => {
+            // Persist the upgraded shape so the migration only runs once
+            if upgraded && !read_only {
+                let content = serde_json::to_string_pretty(&loaded_config)
+                    .map_err(|e| format!("Failed to serialize config: {}", e))?;
+                write_atomic(&config_path, &content)?;
+                println!("Config upgraded to version {}: {:?}", CONFIG_VERSION, config_path);
+            }
+
+            // Apply every persisted field; data_root stays where it was resolved at startup
+            *config = AppConfig {
+                data_root: config.data_root.clone(),
+                ..loaded_config
+            };
+
+            // A list still in configs.json came from an older version and moves to its own file
+            match load_recent_threads(&config.data_root) {
+                Some(recent) => config.recent_threads = recent,
+                None if !config.recent_threads.is_empty() && !read_only => {
+                    if let Err(e) = save_recent_threads(&config) {
+                        println!("Failed to save recent threads: {}", e);
+                    }
+                }
+                None => {}
+            }
+        }
+        Err(error) => {
+            // A broken file must not leave the app without providers
+            println!("Config file {:?} is corrupt, resetting to defaults: {}", config_path, error);
+            let (defaults, backup_path) = recover_corrupt_config(&config_path, &config.data_root, read_only);
+            *config = defaults;
+
+            let warning = ConfigResetWarning {
+                error,
+                backup_path: backup_path.map(|p| p.to_string_lossy().to_string()),
+            };
+            if let Err(e) = app_handle.emit_all(CONFIG_RESET_EVENT, warning) {
+                println!("Failed to emit {} event: {}", CONFIG_RESET_EVENT, e);
+            }
+        }
+    }
+
+    Ok(config.llm_providers.clone())
+}
+
+// Copy keys missing from `target` over from `defaults`, returning whether anything was added
+fn fill_missing_fields(
+    target: &mut serde_json::Map<String, serde_json::Value>,
+    defaults: &serde_json::Value,
+) -> bool {
+    let mut changed = false;
+    for (key, value) in defaults.as_object().into_iter().flatten() {
+        if !target.contains_key(key) {
+            target.insert(key.clone(), value.clone());
+            changed = true;
+        }
+    }
+    changed
+}
+
+// Bring a configs.json document up to CONFIG_VERSION, filling fields older files lack.
+// Returns the parsed config and whether the document changed and should be rewritten.
+fn migrate_config(mut raw: serde_json::Value, current: &AppConfig) -> Result<(AppConfig, bool), String> {
+    let obj = raw.as_object_mut().ok_or("Config file is not a JSON object")?;
+    let version = obj.get("config_version").and_then(|v| v.as_u64()).unwrap_or(0);
+
+    if version > CONFIG_VERSION as u64 {
+        // Written by a newer build; read what we understand and leave the file alone
+        println!("Config version {} is newer than supported version {}", version, CONFIG_VERSION);
+    }
+
+    let defaults = AppConfig {
+        data_root: current.data_root.clone(),
+        ..AppConfig::default()
+    };
+    let defaults = serde_json::to_value(&defaults)
+        .map_err(|e| format!("Failed to serialize default config: {}", e))?;
+    let mut changed = fill_missing_fields(obj, &defaults);
+
+    // Version 0 -> 1: providers gained think/alias/max_tokens over time
+    if let Some(providers) = obj.get_mut("llm_providers").and_then(|v| v.as_array_mut()) {
+        for provider in providers.iter_mut().filter_map(|p| p.as_object_mut()) {
+            let name = provider.get("name").cloned().unwrap_or_else(|| serde_json::json!(""));
+            let provider_defaults = serde_json::json!({
+                "name": name,
+                "provider": "openai_compatible",
+                "base_url": "",
+                "model": "",
+                "api_key": null,
+                "temperature": 0.7,
+                "max_tokens": null,
+                "think": false,
+                "alias": provider.get("alias").cloned().unwrap_or(name),
+            });
+            changed |= fill_missing_fields(provider, &provider_defaults);
+        }
+    }
+
+    if version < CONFIG_VERSION as u64 {
+        obj.insert("config_version".to_string(), serde_json::json!(CONFIG_VERSION));
+        changed = true;
+    }
+
+    let mut config: AppConfig = serde_json::from_value(raw)
+        .map_err(|e| format!("Failed to parse config file: {}", e))?;
+    // A hand-edited 0 would leave every provider request waiting forever
+    config.max_concurrent_requests = config.max_concurrent_requests
+        .map(|n| n.clamp(1, MAX_CONCURRENT_REQUESTS_LIMIT));
+
+    // Never rewrite a newer file: fields this build doesn't know would be lost
+    Ok((config, changed && version <= CONFIG_VERSION as u64))
+}
+
+// Helper function to save config to file
+fn save_config_to_file(config: &AppConfig) -> Result<(), String> {
+    let config_path = config.data_root.join("configs.json");
+
+    // Ensure directory exists
+    if let Some(parent) = config_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+
+    fs::write(&config_path, &content)
+        .map_err(|e| format!("Failed to write config file: {}", e))?;
+
+    // History is best effort; a failed snapshot never fails the save
+    if let Err(e) = write_config_snapshot(&config.data_root, &content) {
+        println!("Failed to write config snapshot: {}", e);
+    }
+
+    println!("Config saved to: {:?}", config_path);
+    Ok(())
+}
+
+// Every save leaves a copy in config_history/{timestamp}.json; only the newest are kept
+const CONFIG_HISTORY_DIR: &str = "config_history";
+const CONFIG_HISTORY_LIMIT: usize = 20;
+
+// Snapshots never hold credentials: a key removed after a leak must not survive in the history.
+// Returns whether anything was stripped
+fn strip_snapshot_secrets(snapshot: &mut serde_json::Value) -> bool {
+    let mut stripped = false;
+    for provider in snapshot["llm_providers"].as_array_mut().into_iter().flatten().filter_map(|p| p.as_object_mut()) {
+        for field in ["api_key", "headers"] {
+            if provider.get(field).is_some_and(|value| !value.is_null()) {
+                provider.insert(field.to_string(), serde_json::Value::Null);
+                stripped = true;
+            }
+        }
+    }
+    stripped
+}
+
+// Rewrite snapshots saved before credentials were stripped from them
+fn scrub_config_snapshots(history_dir: &Path, timestamps: &[String]) {
+    for timestamp in timestamps {
+        let path = history_dir.join(format!("{}.json", timestamp));
+        let Some(mut snapshot) = read_resource_text(&path).ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok()) else {
+            continue;
+        };
+        if !strip_snapshot_secrets(&mut snapshot) {
+            continue;
+        }
+        let result = serde_json::to_string_pretty(&snapshot)
+            .map_err(|e| e.to_string())
+            .and_then(|content| write_atomic(&path, content));
+        if let Err(e) = result {
+            println!("Failed to scrub config snapshot {}: {}", timestamp, e);
+        }
+    }
+}
+
+fn write_config_snapshot(data_root: &Path, content: &str) -> Result<(), String> {
+    let history_dir = data_root.join(CONFIG_HISTORY_DIR);
+    fs::create_dir_all(&history_dir)
+        .map_err(|e| format!("Failed to create {:?}: {}", history_dir, e))?;
+    scrub_config_snapshots(&history_dir, &config_snapshot_timestamps(data_root));
+
+    let mut snapshot: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| format!("Failed to parse config: {}", e))?;
+    strip_snapshot_secrets(&mut snapshot);
+    let content = &serde_json::to_string_pretty(&snapshot)
+        .map_err(|e| format!("Failed to serialize config snapshot: {}", e))?;
+
+    let settings = |content: &str| serde_json::from_str::<serde_json::Value>(content).ok();
+    let latest = config_snapshot_timestamps(data_root).pop()
+        .and_then(|timestamp| read_resource_text(history_dir.join(format!("{}.json", timestamp))).ok());
+    if latest.is_some_and(|latest| settings(&latest) == settings(content)) {
+        return Ok(());
+    }
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ").to_string();
+    write_atomic(&history_dir.join(format!("{}.json", timestamp)), content)?;
+
+    // Timestamps sort chronologically, so the oldest snapshots come first
+    let snapshots = config_snapshot_timestamps(data_root);
+    for old in snapshots.iter().take(snapshots.len().saturating_sub(CONFIG_HISTORY_LIMIT)) {
+        let _ = fs::remove_file(history_dir.join(format!("{}.json", old)));
+    }
+    Ok(())
+}
+
+// Snapshot timestamps, oldest first
+fn config_snapshot_timestamps(data_root: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(data_root.join(CONFIG_HISTORY_DIR)) else {
+        return vec![];
+    };
+    let mut timestamps: Vec<String> = entries.flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.strip_suffix(".json").map(String::from))
+        .filter(|name| !name.starts_with('.'))
+        .collect();
+    timestamps.sort();
+    timestamps
+}
+
+fn load_config_snapshot(current: &AppConfig, timestamp: &str) -> Result<AppConfig, String> {
+    if timestamp.contains("..") || timestamp.contains("/") || timestamp.contains("\\") {
+        return Err("Invalid snapshot timestamp".to_string());
+    }
+    let path = current.data_root.join(CONFIG_HISTORY_DIR).join(format!("{}.json", timestamp));
+    let content = read_resource_text(&path)
+        .map_err(|_| format!("Config snapshot '{}' not found", timestamp))?;
+    let raw: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse config snapshot '{}': {}", timestamp, e))?;
+
+    // Older snapshots are upgraded the same way configs.json is on load
+    let (snapshot, _) = migrate_config(raw, current)?;
+    // Where the data lives and which threads were opened aren't part of what gets reverted
+    Ok(AppConfig {
+        data_root: current.data_root.clone(),
+        recent_threads: current.recent_threads.clone(),
+        ..snapshot
+    })
+}
+
+// Snapshots carry no credentials, so restored providers keep the key and headers they have now;
+// providers that only exist in the snapshot come back without them
+fn keep_provider_credentials(current: &[LLMProvider], restored: &mut [LLMProvider]) {
+    for provider in restored.iter_mut() {
+        let existing = current.iter().find(|p| alias_matches(&p.alias, &provider.alias));
+        provider.api_key = existing.and_then(|p| p.api_key.clone());
+        provider.headers = existing.and_then(|p| p.headers.clone());
+    }
+}
+
+// Locked providers stay exactly as they are now, whatever the snapshot says about them
+fn keep_locked_providers(current: &[LLMProvider], restored: &mut Vec<LLMProvider>) {
+    for locked in current.iter().filter(|provider| provider.locked) {
+        match restored.iter().position(|provider| alias_matches(&provider.alias, &locked.alias)) {
+            Some(index) => restored[index] = locked.clone(),
+            None => restored.push(locked.clone()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigSnapshot {
+    pub timestamp: String,
+    pub provider_count: usize,
+    pub size_bytes: u64,
+}
+
+#[tauri::command]
+pub async fn list_config_snapshots(
+    state: State<'_, AppState>,
+) -> Result<Vec<ConfigSnapshot>, String> {
+    let config = state.config.lock().unwrap().clone();
+    let history_dir = config.data_root.join(CONFIG_HISTORY_DIR);
+
+    // Newest first
+    Ok(config_snapshot_timestamps(&config.data_root).into_iter().rev()
+        .map(|timestamp| {
+            let path = history_dir.join(format!("{}.json", timestamp));
+            ConfigSnapshot {
+                provider_count: load_config_snapshot(&config, &timestamp)
+                    .map_or(0, |snapshot| snapshot.llm_providers.len()),
+                size_bytes: fs::metadata(&path).map_or(0, |m| m.len()),
+                timestamp,
+            }
+        })
+        .collect())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderChange {
+    pub alias: String,
+    // Changed fields other than api_key and headers, which a restore never touches
+    pub fields: Vec<String>,
+    // The snapshot has a different key or headers than the provider has now (only possible
+    // for snapshots written before credentials were stripped); the current ones are kept
+    pub credentials_differ: bool,
+}
+
+// What restoring a snapshot would do to the current config
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigSnapshotDiff {
+    pub timestamp: String,
+    // Aliases only in the snapshot; restoring brings them back
+    pub added: Vec<String>,
+    // Aliases only in the current config; restoring removes them
+    pub removed: Vec<String>,
+    pub changed: Vec<ProviderChange>,
+    // Other top-level settings that differ, e.g. theme or default_planner_alias
+    pub settings_changed: Vec<String>,
+}
+
+// Top-level keys whose values differ between two JSON objects, sorted
+fn changed_fields(before: &serde_json::Value, after: &serde_json::Value) -> Vec<String> {
+    let keys: BTreeSet<&String> = before.as_object().into_iter().flatten()
+        .chain(after.as_object().into_iter().flatten())
+        .map(|(key, _)| key)
+        .collect();
+    keys.into_iter()
+        .filter(|key| before.get(key.as_str()) != after.get(key.as_str()))
+        .cloned()
+        .collect()
+}
+
+fn diff_configs(current: &AppConfig, snapshot: &AppConfig, timestamp: String) -> Result<ConfigSnapshotDiff, String> {
+    let to_value = |value: &AppConfig| serde_json::to_value(value)
+        .map_err(|e| format!("Failed to serialize config: {}", e));
+    let provider_value = |provider: &LLMProvider| serde_json::to_value(provider)
+        .map_err(|e| format!("Failed to serialize provider: {}", e));
+    let find = |providers: &[LLMProvider], alias: &str| providers.iter().find(|p| alias_matches(&p.alias, alias)).cloned();
+
+    let mut diff = ConfigSnapshotDiff { timestamp, added: vec![], removed: vec![], changed: vec![], settings_changed: vec![] };
+    for provider in &snapshot.llm_providers {
+        match find(&current.llm_providers, &provider.alias) {
+            None => diff.added.push(provider.alias.clone()),
+            Some(existing) => {
+                let fields: Vec<String> = changed_fields(&provider_value(&existing)?, &provider_value(provider)?)
+                    .into_iter()
+                    .filter(|field| field != "api_key" && field != "headers")
+                    .collect();
+                let credentials_differ = provider.api_key.as_ref().is_some_and(|key| Some(key) != existing.api_key.as_ref())
+                    || provider.headers.as_ref().is_some_and(|headers| Some(headers) != existing.headers.as_ref());
+                if !fields.is_empty() || credentials_differ {
+                    diff.changed.push(ProviderChange { alias: provider.alias.clone(), fields, credentials_differ });
+                }
+            }
+        }
+    }
+    diff.removed = current.llm_providers.iter()
+        .filter(|provider| find(&snapshot.llm_providers, &provider.alias).is_none())
+        .map(|provider| provider.alias.clone())
+        .collect();
+
+    diff.settings_changed = changed_fields(&to_value(current)?, &to_value(snapshot)?)
+        .into_iter()
+        .filter(|field| field != "llm_providers")
+        .collect();
+    Ok(diff)
+}
+
+#[tauri::command]
+pub async fn diff_config_snapshot(
+    timestamp: String,
+    state: State<'_, AppState>,
+) -> Result<ConfigSnapshotDiff, String> {
+    let config = state.config.lock().unwrap().clone();
+    let snapshot = load_config_snapshot(&config, &timestamp)?;
+    diff_configs(&config, &snapshot, timestamp)
+}
+
+#[tauri::command]
+pub async fn restore_config_snapshot(
+    timestamp: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+    let mut restored = load_config_snapshot(&config, &timestamp)?;
+    keep_provider_credentials(&config.llm_providers, &mut restored.llm_providers);
+    if !admin_from_env() {
+        keep_locked_providers(&config.llm_providers, &mut restored.llm_providers);
+    }
+    *config = restored;
+
+    // The restore is itself saved, and snapshotted, so it can be undone
+    save_config_to_file(&config)?;
+    println!("Config restored from snapshot {}", timestamp);
+    Ok(())
+}
+
+// Rebuild objects with their keys sorted so rewritten files diff cleanly
+fn sort_json_keys(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<(String, serde_json::Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            serde_json::Value::Object(entries.into_iter().map(|(k, v)| (k, sort_json_keys(v))).collect())
+        }
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(sort_json_keys).collect()),
+        other => other,
+    }
+}
+
+// Re-serialize one JSON (or .json.gz) file in place; Ok(true) when its content changed
+fn reformat_json_file(path: &Path) -> Result<bool, String> {
+    let bytes = fs::read(path)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    let content = decode_resource(path, bytes)
+        .map_err(|e| format!("Failed to decompress file: {}", e))?;
+    let value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let formatted = serde_json::to_string_pretty(&sort_json_keys(value))
+        .map_err(|e| format!("Failed to serialize JSON: {}", e))?;
+    if formatted == content {
+        return Ok(false);
+    }
+
+    let encoded = encode_resource(path, &formatted)
+        .map_err(|e| format!("Failed to compress file: {}", e))?;
+    write_atomic(path, encoded)?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn reformat_all(
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    let mut paths = vec![data_root.join("configs.json")];
+    for dir in ["guides", "knowledge"] {
+        if let Ok(entries) = fs::read_dir(data_root.join(dir)) {
+            paths.extend(entries.flatten().map(|entry| entry.path()).filter(|path| is_resource_file(path)));
+        }
+    }
+    paths.extend(resource_index(&data_root.join("actions"), Some("meta.json"))
+        .into_values()
+        .map(|dir| dir.join("meta.json")));
+
+    let mut normalized = 0;
+    for path in paths.iter().filter(|path| path.is_file()) {
+        match reformat_json_file(path) {
+            Ok(true) => normalized += 1,
+            Ok(false) => {}
+            Err(e) => println!("Skipping {:?} during reformat: {}", path, e),
+        }
+    }
+
+    println!("Reformatted {} of {} data files", normalized, paths.len());
+    Ok(normalized)
+}
+
+// Number of files the list commands read at the same time
+const LIST_READ_CONCURRENCY: usize = 16;
+
+// Paths of all entries in a directory, in directory order
+async fn list_dir_paths(dir: &Path, label: &str) -> Result<Vec<PathBuf>, String> {
+    let mut read_dir = tokio::fs::read_dir(dir).await
+        .map_err(|e| format!("Failed to read {} directory: {}", label, e))?;
+
+    let mut paths = Vec::new();
+    while let Some(entry) = read_dir.next_entry().await
+        .map_err(|e| format!("Failed to read directory entry: {}", e))?
+    {
+        paths.push(entry.path());
+    }
+
+    Ok(paths)
+}
+
+// Read and parse JSON files concurrently, keeping the input order
+async fn read_json_files(paths: Vec<PathBuf>, label: &str) -> Result<Vec<serde_json::Value>, String> {
+    let results: Vec<Result<serde_json::Value, String>> = stream::iter(paths)
+        .map(|path| async move {
+            let bytes = tokio::fs::read(&path).await
+                .map_err(|e| format!("Failed to read {} file: {}", label, e))?;
+            let content = decode_resource(&path, bytes)
+                .map_err(|e| format!("Failed to read {} file: {}", label, e))?;
+            serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {} JSON: {}", label, e))
+        })
+        .buffered(LIST_READ_CONCURRENCY)
+        .collect()
+        .await;
+
+    results.into_iter().collect()
+}
+
+fn is_json_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("json")
+}
+
+// Guides and knowledge may be stored gzip-compressed as {name}.json.gz
+const COMPRESSED_RESOURCE_SUFFIX: &str = ".json.gz";
+
+fn is_compressed_resource(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.ends_with(COMPRESSED_RESOURCE_SUFFIX))
+}
+
+// A guide or knowledge file, plain or compressed
+fn is_resource_file(path: &Path) -> bool {
+    is_json_file(path) || is_compressed_resource(path)
+}
+
+// Bytes as stored on disk -> JSON text, decompressing .json.gz files. A leading UTF-8 BOM, as
+// written by some Windows editors, is dropped so serde_json doesn't trip over it
+fn decode_resource(path: &Path, bytes: Vec<u8>) -> std::io::Result<String> {
+    let bytes = if is_compressed_resource(path) {
+        let mut decompressed = Vec::new();
+        std::io::Read::read_to_end(&mut flate2::read::GzDecoder::new(bytes.as_slice()), &mut decompressed)?;
+        decompressed
+    } else {
+        bytes
+    };
+    let content = String::from_utf8(bytes).map_err(|e| std::io::Error::new(
+        std::io::ErrorKind::InvalidData,
+        format!(
+            "file {} is not valid UTF-8 (invalid byte at offset {})",
+            path.file_name().unwrap_or_default().to_string_lossy(),
+            e.utf8_error().valid_up_to()
+        ),
+    ))?;
+    Ok(match content.strip_prefix('\u{feff}') {
+        Some(stripped) => stripped.to_string(),
+        None => content,
+    })
+}
+
+// JSON text -> bytes to store, compressing for .json.gz files
+fn encode_resource(path: &Path, content: &str) -> std::io::Result<Vec<u8>> {
+    if is_compressed_resource(path) {
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(content.as_bytes())?;
+        encoder.finish()
+    } else {
+        Ok(content.as_bytes().to_vec())
+    }
+}
+
+// Read any JSON data file (guides, knowledge, config, threads, action files) as text
+fn read_resource_text(path: impl AsRef<Path>) -> std::io::Result<String> {
+    let path = path.as_ref();
+    decode_resource(path, fs::read(path)?)
+}
+
+// "{stem}.json" or "{stem}.json.gz" depending on the compress setting
+fn resource_file_name(filename: &str, compress: bool) -> String {
+    let stem = filename
+        .strip_suffix(COMPRESSED_RESOURCE_SUFFIX)
+        .or_else(|| filename.strip_suffix(".json"))
+        .unwrap_or(filename);
+    if compress {
+        format!("{}{}", stem, COMPRESSED_RESOURCE_SUFFIX)
+    } else {
+        format!("{}.json", stem)
+    }
+}
+
+// The stored file for a guide/knowledge name, whichever format it was saved in
+fn locate_resource(dir: &Path, filename: &str) -> Option<PathBuf> {
+    [filename.to_string(), resource_file_name(filename, false), resource_file_name(filename, true)]
+        .into_iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+// Write a guide/knowledge document in the configured format, removing the copy in the other format
+fn write_resource(dir: &Path, filename: &str, content: &str, compress: bool) -> Result<PathBuf, String> {
+    let path = dir.join(resource_file_name(filename, compress));
+    let bytes = encode_resource(&path, content)
+        .map_err(|e| format!("Failed to compress {:?}: {}", path, e))?;
+    write_atomic(&path, bytes)?;
+
+    let other = dir.join(resource_file_name(filename, !compress));
+    if other.exists() {
+        fs::remove_file(&other)
+            .map_err(|e| format!("Failed to remove {:?}: {}", other, e))?;
+    }
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn list_guides(
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let guides_dir = data_root.join("guides");
+
+    if !guides_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let paths = list_dir_paths(&guides_dir, "guides").await?
+        .into_iter()
+        .filter(|path| is_resource_file(path))
+        .collect();
+
+    read_json_files(paths, "guide").await
+}
+
+#[tauri::command]
+pub async fn load_guide(
+    filename: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let guides_dir = data_root.join("guides");
+
+    // Validate filename to prevent directory traversal
+    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    let guide_path = locate_resource(&guides_dir, &filename)
+        .ok_or_else(|| format!("Guide file '{}' not found", filename))?;
+
+    let content = read_resource_text(&guide_path)
+        .map_err(|e| format!("Failed to read guide file: {}", e))?;
+
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse guide JSON: {}", e))?;
+
+    Ok(json)
+}
+
+#[tauri::command]
+pub async fn save_guide(
+    filename: String,
+    guide_data: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap();
+    let guides_dir = config.data_root.join("guides");
+
+    // Validate filename to prevent directory traversal
+    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    // Ensure guides directory exists
+    fs::create_dir_all(&guides_dir)
+        .map_err(|e| format!("Failed to create guides directory: {}", e))?;
+
+    // Validate guide structure
+    validate_guide_structure(&guide_data)?;
+
+    check_guide_action_references(&config.data_root, &guide_data)?;
+
+    let content = serde_json::to_string_pretty(&guide_data)
+        .map_err(|e| format!("Failed to serialize guide data: {}", e))?;
+
+    // Saved as .json or .json.gz depending on the compress setting
+    let guide_path = write_resource(&guides_dir, &filename, &content, config.compress)?;
+
+    println!("Guide saved to: {:?}", guide_path);
+    Ok(())
+}
+
+// Save a guide from editor text so syntax errors come back with their line and column
+#[tauri::command]
+pub async fn save_guide_text(
+    filename: String,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let guide_data = parse_json_text(&content).map_err(|e| e.describe())?;
+    save_guide(filename, guide_data, state).await
+}
+
+#[tauri::command]
+pub async fn delete_guide(
+    filename: String,
+    permanent: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let guides_dir = data_root.join("guides");
+
+    // Validate filename to prevent directory traversal
+    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    let guide_path = locate_resource(&guides_dir, &filename)
+        .ok_or_else(|| format!("Guide file '{}' not found", filename))?;
+
+    if permanent {
+        fs::remove_file(&guide_path)
+            .map_err(|e| format!("Failed to delete guide file: {}", e))?;
+    } else {
+        let stored_name = guide_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        move_to_trash(data_root, "guides", &stored_name)?;
+    }
+
+    println!("Guide deleted: {:?}", guide_path);
+    Ok(())
+}
+
+// Write a file via a temporary sibling and rename so readers never see a partial file
+fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> Result<(), String> {
+    let file_name = path.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid file path")?;
+    let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+
+    let result = fs::write(&temp_path, content)
+        .and_then(|_| fs::rename(&temp_path, path));
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write {:?}: {}", path, e));
+    }
+
+    Ok(())
+}
+
+// Resolve an existing guide/knowledge file, accepting the name with or without .json
+fn existing_resource_path(data_root: &Path, dir: &str, filename: &str) -> Result<PathBuf, String> {
+    // Validate filename to prevent directory traversal
+    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    locate_resource(&data_root.join(dir), filename)
+        .ok_or_else(|| format!("File '{}' not found in {}", resource_file_name(filename, false), dir))
+}
+
+// Load a resource file, let `mutate` edit its entries, re-validate and save it atomically
+fn modify_resource_entries<T>(
+    path: &Path,
+    validate: fn(&serde_json::Value) -> Result<(), String>,
+    mutate: impl FnOnce(&mut Vec<serde_json::Value>) -> Result<T, String>,
+) -> Result<T, String> {
+    let content = read_resource_text(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let mut document: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+
+    let entries = document.get_mut("entries")
+        .and_then(|v| v.as_array_mut())
+        .ok_or("Missing or invalid 'entries' array")?;
+    let result = mutate(entries)?;
+
+    validate(&document)?;
+
+    let content = serde_json::to_string_pretty(&document)
+        .map_err(|e| format!("Failed to serialize {:?}: {}", path, e))?;
+    // Keep the file in the format it was stored in
+    let bytes = encode_resource(path, &content)
+        .map_err(|e| format!("Failed to compress {:?}: {}", path, e))?;
+    write_atomic(path, bytes)?;
+
+    Ok(result)
+}
+
+fn check_entry_index(entries: &[serde_json::Value], entry_index: usize) -> Result<(), String> {
+    if entry_index >= entries.len() {
+        return Err(format!(
+            "Entry index {} is out of range (file has {} entries)",
+            entry_index,
+            entries.len()
+        ));
+    }
+    Ok(())
+}
+
+// Normalized entry name used to detect duplicates ("  Setup " and "setup" collide)
+fn entry_name_key(entry: &serde_json::Value) -> Option<String> {
+    entry.get("name")
+        .and_then(|v| v.as_str())
+        .map(|name| name.trim().to_lowercase())
+}
+
+// Drop entries whose name collides with an earlier one, returning how many were removed
+fn dedupe_entries_by_name(entries: &mut Vec<serde_json::Value>) -> usize {
+    let before = entries.len();
+    let mut seen = HashSet::new();
+    entries.retain(|entry| match entry_name_key(entry) {
+        Some(key) => seen.insert(key),
+        None => true,
+    });
+    before - entries.len()
+}
+
+#[tauri::command]
+pub async fn add_guide_entry(
+    filename: String,
+    entry: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    // Holding the config lock serializes concurrent entry edits
+    let data_root = &state.config.lock().unwrap().data_root;
+    let guide_path = existing_resource_path(data_root, "guides", &filename)?;
+    check_guide_action_references(data_root, &serde_json::json!({ "entries": [&entry] }))?;
+
+    modify_resource_entries(&guide_path, validate_guide_structure, |entries| {
+        entries.push(entry);
+        Ok(entries.len() - 1)
+    })
+}
+
+#[tauri::command]
+pub async fn update_guide_entry(
+    filename: String,
+    entry_index: usize,
+    entry: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let guide_path = existing_resource_path(data_root, "guides", &filename)?;
+    check_guide_action_references(data_root, &serde_json::json!({ "entries": [&entry] }))?;
+
+    modify_resource_entries(&guide_path, validate_guide_structure, |entries| {
+        check_entry_index(entries, entry_index)?;
+        entries[entry_index] = entry;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn delete_guide_entry(
+    filename: String,
+    entry_index: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let guide_path = existing_resource_path(data_root, "guides", &filename)?;
+
+    modify_resource_entries(&guide_path, validate_guide_structure, |entries| {
+        check_entry_index(entries, entry_index)?;
+        entries.remove(entry_index);
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn dedupe_guide(
+    filename: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let guide_path = existing_resource_path(data_root, "guides", &filename)?;
+
+    let removed = modify_resource_entries(&guide_path, validate_guide_structure, |entries| {
+        Ok(dedupe_entries_by_name(entries))
+    })?;
+
+    println!("Removed {} duplicate entries from guide {}", removed, filename);
+    Ok(removed)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EntryRename {
+    pub entry_index: usize,
+    pub old_name: String,
+    pub new_name: String,
+}
+
+// Rename later entries whose names collide with an earlier one to the first free "{name}-N";
+// the first entry with a name keeps it
+fn dedupe_entry_names(entries: &mut [serde_json::Value]) -> Vec<EntryRename> {
+    let mut taken: HashSet<String> = entries.iter().filter_map(entry_name_key).collect();
+    let mut seen = HashSet::new();
+    let mut renames = Vec::new();
+
+    for (entry_index, entry) in entries.iter_mut().enumerate() {
+        let Some(key) = entry_name_key(entry) else {
+            continue;
+        };
+        if seen.insert(key) {
+            continue;
+        }
+        let old_name = entry["name"].as_str().unwrap_or_default().trim().to_string();
+        let new_name = (2..)
+            .map(|n| format!("{}-{}", old_name, n))
+            .find(|candidate| !taken.contains(&candidate.to_lowercase()))
+            .unwrap();
+        taken.insert(new_name.to_lowercase());
+        seen.insert(new_name.to_lowercase());
+        entry["name"] = serde_json::json!(new_name);
+        renames.push(EntryRename { entry_index, old_name, new_name });
+    }
+    renames
+}
+
+#[tauri::command]
+pub async fn normalize_entry_names(
+    filename: String,
+    kind: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<EntryRename>, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+
+    let validate: fn(&serde_json::Value) -> Result<(), String> = match kind.as_str() {
+        "guide" => |document| validate_guide_structure_with(document, StructureOptions { unique_entry_names: true }),
+        "knowledge" => |document| validate_knowledge_structure_with(document, StructureOptions { unique_entry_names: true }),
+        _ => return Err(format!("Unknown entry file kind '{}'. Must be 'guide' or 'knowledge'", kind)),
+    };
+    let path = existing_resource_path(data_root, resource_kind_dir(&kind)?, &filename)?;
+
+    let renames = modify_resource_entries(&path, validate, |entries| Ok(dedupe_entry_names(entries)))?;
+
+    println!("Renamed {} colliding entries in {} {}", renames.len(), kind, filename);
+    Ok(renames)
+}
+
+#[tauri::command]
+pub async fn create_guides_directory(
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let guides_dir = data_root.join("guides");
+
+    fs::create_dir_all(&guides_dir)
+        .map_err(|e| format!("Failed to create guides directory: {}", e))?;
+
+    // Create a default guide if directory is empty
+    let default_guide = serde_json::json!({
+        "meta": {
+            "name": "Software Development Guide",
+            "version": "1.0.0",
+            "domain": "development"
+        },
+        "entries": [
+            {
+                "name": "Project Setup",
+                "description": "Initial project configuration and setup",
+                "plan": [
+                    "Create project structure",
+                    "Configure development environment",
+                    "Set up version control",
+                    "Install dependencies"
+                ]
+            },
+            {
+                "name": "Feature Development",
+                "description": "Develop new features incrementally",
+                "plan": [
+                    "Analyze requirements",
+                    "Design solution approach",
+                    "Implement core functionality",
+                    "Write tests",
+                    "Review and refactor"
+                ]
+            }
+        ]
+    });
+
+    let default_path = guides_dir.join("software-development.json");
+
+    if !default_path.exists() {
+        let content = serde_json::to_string_pretty(&default_guide)
+            .map_err(|e| format!("Failed to serialize default guide: {}", e))?;
+
+        fs::write(&default_path, content)
+            .map_err(|e| format!("Failed to write default guide: {}", e))?;
+
+        println!("Default guide created at: {:?}", default_path);
+    }
+
+    Ok(())
+}
+
+// JSON Schemas for the resource files; the validators below are driven off these
+fn guide_schema() -> serde_json::Value {
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Guide",
+        "type": "object",
+        "required": ["meta", "entries"],
+        "properties": {
+            "meta": resource_meta_schema(),
+            "entries": {
+                "type": "array",
+                "minItems": 1,
+                "items": {
+                    "type": "object",
+                    "required": ["name", "description", "plan"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "description": { "type": "string" },
+                        "plan": {
+                            "type": "array",
+                            "minItems": 1,
+                            // Plain text, or text linked to an action in the actions library
+                            "items": {
+                                "oneOf": [
+                                    { "type": "string" },
+                                    {
+                                        "type": "object",
+                                        "required": ["text"],
+                                        "properties": {
+                                            "text": { "type": "string" },
+                                            "action": { "type": "string", "minLength": 1 }
+                                        },
+                                        "additionalProperties": false
+                                    }
+                                ]
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn knowledge_schema() -> serde_json::Value {
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Knowledge",
+        "type": "object",
+        "required": ["meta", "entries"],
+        "properties": {
+            "meta": resource_meta_schema(),
+            "entries": {
+                "type": "array",
+                "minItems": 1,
+                "items": {
+                    "type": "object",
+                    "required": ["name", "description", "content"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "description": { "type": "string" },
+                        "content": { "type": "string" }
+                    }
+                }
+            }
+        }
+    })
+}
+
+fn action_meta_schema() -> serde_json::Value {
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Action meta",
+        "type": "object",
+        "required": ["name", "description", "arguments", "timeout_sec"],
+        "properties": {
+            "name": { "type": "string" },
+            "description": { "type": "string" },
+            "arguments": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "required": ["name", "type", "description", "required"],
+                    "properties": {
+                        "name": { "type": "string" },
+                        "type": { "type": "string" },
+                        "description": { "type": "string" },
+                        "required": { "type": "boolean" },
+                        "default": {},
+                        "enum": { "type": "array", "minItems": 1 }
+                    }
+                }
+            },
+            "timeout_sec": { "type": "integer", "minimum": MIN_ACTION_TIMEOUT_SECS, "maximum": MAX_ACTION_TIMEOUT_SECS }
+        }
+    })
+}
+
+// Color tokens a custom theme may set; each maps to a --color-* variable in globals.css
+const THEME_COLOR_TOKENS: [&str; 24] = [
+    "bg-primary", "bg-secondary", "bg-tertiary", "bg-elevated", "border", "border-subtle",
+    "text-primary", "text-secondary", "text-tertiary", "text-inverse",
+    "accent", "accent-hover", "accent-active", "primary",
+    "success", "warning", "error", "info",
+    "success-bg", "success-border", "error-bg", "error-border", "error-dark", "overlay",
+];
+
+fn theme_schema() -> serde_json::Value {
+    serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Theme",
+        "type": "object",
+        "required": ["colors"],
+        "properties": {
+            // Built-in theme supplying every token the custom theme leaves out
+            "base": { "enum": BUILTIN_THEMES.iter().filter(|t| **t != "system").collect::<Vec<_>>() },
+            "colors": {
+                "type": "object",
+                "minProperties": 1,
+                "propertyNames": { "enum": THEME_COLOR_TOKENS },
+                "additionalProperties": {
+                    "type": "string",
+                    "pattern": "^(#([0-9a-fA-F]{3,4}|[0-9a-fA-F]{6}|[0-9a-fA-F]{8})|(rgb|rgba|hsl|hsla)\\([^()]*\\))$"
+                }
+            }
+        },
+        "additionalProperties": false
+    })
+}
+
+// Shared "meta" section of guide and knowledge files
+fn resource_meta_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "required": ["name", "version"],
+        "properties": {
+            "name": { "type": "string" },
+            "version": { "type": "string" },
+            "domain": { "type": "string" }
+        }
+    })
+}
+
+static GUIDE_VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+static KNOWLEDGE_VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+static ACTION_META_VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+static THEME_VALIDATOR: OnceLock<jsonschema::Validator> = OnceLock::new();
+
+fn compiled_validator(
+    cell: &'static OnceLock<jsonschema::Validator>,
+    schema: fn() -> serde_json::Value,
+) -> &'static jsonschema::Validator {
+    cell.get_or_init(|| jsonschema::validator_for(&schema()).expect("built-in schema must compile"))
+}
+
+// Render a JSON pointer such as /entries/0/plan as entries[0].plan
+fn instance_path_label(pointer: &str) -> String {
+    let mut label = String::new();
+    for segment in pointer.split('/').filter(|s| !s.is_empty()) {
+        if segment.chars().all(|c| c.is_ascii_digit()) {
+            label.push_str(&format!("[{}]", segment));
+        } else {
+            if !label.is_empty() {
+                label.push('.');
+            }
+            label.push_str(segment);
+        }
+    }
+
+    if label.is_empty() {
+        "(root)".to_string()
+    } else {
+        label
+    }
+}
+
+// Every schema violation of the document, each prefixed with its location
+fn schema_violations(validator: &jsonschema::Validator, instance: &serde_json::Value) -> Vec<String> {
+    validator.iter_errors(instance)
+        .map(|error| format!("'{}': {}", instance_path_label(error.instance_path.as_str()), error))
+        .collect()
+}
+
+// Validate a document against a schema, joining all violations into one error
+fn validate_against_schema(validator: &jsonschema::Validator, instance: &serde_json::Value) -> Result<(), String> {
+    let violations = schema_violations(validator, instance);
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations.join("; "))
+    }
+}
+
+#[tauri::command]
+pub async fn get_schema(
+    kind: String,
+) -> Result<serde_json::Value, String> {
+    match kind.as_str() {
+        "guide" => Ok(guide_schema()),
+        "knowledge" => Ok(knowledge_schema()),
+        "action_meta" => Ok(action_meta_schema()),
+        "theme" => Ok(theme_schema()),
+        _ => Err(format!("Unknown schema kind '{}'. Must be 'guide', 'knowledge', 'action_meta', or 'theme'", kind)),
+    }
+}
+
+// Checks on top of the JSON schema, off unless a caller asks for them
+#[derive(Debug, Clone, Copy, Default)]
+struct StructureOptions {
+    // Reject entries whose names collide case-insensitively
+    unique_entry_names: bool,
+}
+
+fn check_structure_options(document: &serde_json::Value, options: StructureOptions) -> Result<(), String> {
+    if options.unique_entry_names {
+        let duplicates = duplicate_entry_names(document);
+        if !duplicates.is_empty() {
+            return Err(format!("Duplicate entry names: {}", duplicates.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+// Helper function to validate guide structure
+fn validate_guide_structure(guide: &serde_json::Value) -> Result<(), String> {
+    validate_guide_structure_with(guide, StructureOptions::default())
+}
+
+fn validate_guide_structure_with(guide: &serde_json::Value, options: StructureOptions) -> Result<(), String> {
+    validate_against_schema(compiled_validator(&GUIDE_VALIDATOR, guide_schema), guide)?;
+    check_structure_options(guide, options)
+}
+
+#[tauri::command]
+pub async fn list_knowledge(
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let knowledge_dir = data_root.join("knowledge");
+
+    if !knowledge_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let paths = list_dir_paths(&knowledge_dir, "knowledge").await?
+        .into_iter()
+        .filter(|path| is_resource_file(path))
+        .collect();
+
+    read_json_files(paths, "knowledge").await
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResourceSummary {
+    pub kind: String,
+    pub filename: String,
+    pub name: Option<String>,
+    pub domain: Option<String>,
+    pub version: Option<String>,
+    pub entry_count: usize,
+    pub size_bytes: u64,
+}
+
+fn summarize_resource_dir(dir: &Path, kind: &str) -> Result<Vec<ResourceSummary>, String> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut summaries = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|e| format!("Failed to read {} directory: {}", kind, e))? {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+
+        if !is_resource_file(&path) {
+            continue;
+        }
+
+        let bytes = fs::read(&path)
+            .map_err(|e| format!("Failed to read {} file: {}", kind, e))?;
+        let size_bytes = bytes.len() as u64;
+        let parsed = decode_resource(&path, bytes)
+            .map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string()));
+        let json = match parsed {
+            Ok(json) => json,
+            Err(e) => {
+                println!("Skipping unreadable {} file {:?}: {}", kind, path, e);
+                continue;
+            }
+        };
+
+        let meta_field = |field: &str| json.pointer(&format!("/meta/{}", field))
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string());
+
+        summaries.push(ResourceSummary {
+            kind: kind.to_string(),
+            filename: entry.file_name().to_string_lossy().to_string(),
+            name: meta_field("name"),
+            domain: meta_field("domain"),
+            version: meta_field("version"),
+            entry_count: json.get("entries").and_then(|v| v.as_array()).map_or(0, |a| a.len()),
+            size_bytes,
+        });
+    }
+
+    Ok(summaries)
+}
+
+#[tauri::command]
+pub async fn get_resource_summaries(
+    state: State<'_, AppState>,
+) -> Result<Vec<ResourceSummary>, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+
+    let mut summaries = summarize_resource_dir(&data_root.join("guides"), "guide")?;
+    summaries.extend(summarize_resource_dir(&data_root.join("knowledge"), "knowledge")?);
+
+    Ok(summaries)
+}
+
+// Bucket for guide/knowledge files without a meta.domain
+const UNCATEGORIZED_DOMAIN: &str = "uncategorized";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DomainInfo {
+    pub domain: String,
+    pub file_count: usize,
+    pub guide_count: usize,
+    pub knowledge_count: usize,
+}
+
+#[tauri::command]
+pub async fn list_domains(
+    state: State<'_, AppState>,
+) -> Result<Vec<DomainInfo>, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+
+    let mut summaries = summarize_resource_dir(&data_root.join("guides"), "guide")?;
+    summaries.extend(summarize_resource_dir(&data_root.join("knowledge"), "knowledge")?);
+
+    let mut domains: HashMap<String, DomainInfo> = HashMap::new();
+    for summary in summaries {
+        let domain = summary.domain
+            .map(|d| d.trim().to_string())
+            .filter(|d| !d.is_empty())
+            .unwrap_or_else(|| UNCATEGORIZED_DOMAIN.to_string());
+        let info = domains.entry(domain.clone()).or_insert_with(|| DomainInfo { domain, ..DomainInfo::default() });
+        info.file_count += 1;
+        if summary.kind == "guide" {
+            info.guide_count += 1;
+        } else {
+            info.knowledge_count += 1;
+        }
+    }
+
+    // Alphabetical, with the uncategorized bucket last
+    let mut domains: Vec<DomainInfo> = domains.into_values().collect();
+    domains.sort_by(|a, b| {
+        (a.domain == UNCATEGORIZED_DOMAIN, a.domain.to_lowercase())
+            .cmp(&(b.domain == UNCATEGORIZED_DOMAIN, b.domain.to_lowercase()))
+    });
+    Ok(domains)
+}
+
+// Directory holding each resource kind accepted by the etag commands
+fn resource_kind_dir(kind: &str) -> Result<&'static str, String> {
+    match kind {
+        "guide" => Ok("guides"),
+        "knowledge" => Ok("knowledge"),
+        "action" => Ok("actions"),
+        _ => Err(format!("Unknown resource kind '{}'. Must be 'guide', 'knowledge', or 'action'", kind)),
+    }
+}
+
+// Feed a file, or every file below a directory in sorted order, into the hasher
+fn hash_resource_into(hasher: &mut blake3::Hasher, path: &Path, relative: &str) -> Result<(), String> {
+    if path.is_dir() {
+        let mut entries: Vec<_> = fs::read_dir(path)
+            .map_err(|e| format!("Failed to read directory {:?}: {}", path, e))?
+            .flatten()
+            .collect();
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            // An action's status.json changes on every run; it isn't part of the action's content
+            if relative.is_empty() && entry.file_name() == "status.json" {
+                continue;
+            }
+            let child = format!("{}/{}", relative, entry.file_name().to_string_lossy());
+            hash_resource_into(hasher, &entry.path(), &child)?;
+        }
+    } else {
+        let content = fs::read(path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        // Length-prefix names and contents so different layouts can't hash the same
+        hasher.update(&(relative.len() as u64).to_le_bytes());
+        hasher.update(relative.as_bytes());
+        hasher.update(&(content.len() as u64).to_le_bytes());
+        hasher.update(&content);
+    }
+    Ok(())
+}
+
+// Stable content hash of a resource file or action directory
+fn resource_etag(path: &Path) -> Result<String, String> {
+    if path.is_dir() {
+        let mut hasher = blake3::Hasher::new();
+        hash_resource_into(&mut hasher, path, "")?;
+        Ok(hasher.finalize().to_hex().to_string())
+    } else {
+        let content = fs::read(path)
+            .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+        Ok(blake3::hash(&content).to_hex().to_string())
+    }
+}
+
+#[tauri::command]
+pub async fn get_resource_etag(
+    kind: String,
+    filename: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let dir = resource_kind_dir(&kind)?;
+
+    let path = if kind == "action" {
+        // Validate action name
+        if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+            return Err("Invalid action name".to_string());
+        }
+        let action_dir = data_root.join(dir).join(&filename);
+        if !action_dir.is_dir() {
+            return Err(format!("Action '{}' not found", filename));
+        }
+        action_dir
+    } else {
+        existing_resource_path(data_root, dir, &filename)?
+    };
+
+    resource_etag(&path)
+}
+
+#[tauri::command]
+pub async fn get_all_etags(
+    kind: String,
+    state: State<'_, AppState>,
+) -> Result<HashMap<String, String>, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let dir = data_root.join(resource_kind_dir(&kind)?);
+
+    let mut etags = HashMap::new();
+    if !dir.exists() {
+        return Ok(etags);
+    }
+
+    for entry in fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read {} directory: {}", kind, e))?
+    {
+        let entry = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        let is_resource = if kind == "action" { path.is_dir() } else { is_resource_file(&path) };
+
+        if is_resource {
+            etags.insert(entry.file_name().to_string_lossy().to_string(), resource_etag(&path)?);
+        }
+    }
+
+    Ok(etags)
+}
+
+#[tauri::command]
+pub async fn list_actions(
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    load_action_metas(&data_root).await
+}
+
+// Every action's meta.json, tagged with the directory it lives in
+async fn load_action_metas(data_root: &Path) -> Result<Vec<serde_json::Value>, String> {
+    let actions_dir = data_root.join("actions");
+
+    if !actions_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut dir_names = Vec::new();
+    let mut meta_files = Vec::new();
+
+    for path in list_dir_paths(&actions_dir, "actions").await? {
+        let is_dir = tokio::fs::metadata(&path).await.map(|m| m.is_dir()).unwrap_or(false);
+        let meta_file = path.join("meta.json");
+
+        if is_dir && tokio::fs::try_exists(&meta_file).await.unwrap_or(false) {
+            dir_names.push(path.file_name().and_then(|n| n.to_str()).map(|n| n.to_string()));
+            meta_files.push(meta_file);
+        }
+    }
+
+    let mut actions = read_json_files(meta_files, "action meta").await?;
+
+    // Add directory_name to the action data
+    for (json, dir_name) in actions.iter_mut().zip(dir_names) {
+        if let (Some(json_obj), Some(dir_name)) = (json.as_object_mut(), dir_name) {
+            json_obj.insert("directory_name".to_string(), serde_json::Value::String(dir_name));
+        }
+    }
+
+    Ok(actions)
+}
+
+// First field of an action's meta matching the lowercase query, as a dotted path
+fn action_match_field(meta: &serde_json::Value, query: &str) -> Option<String> {
+    let matches = |value: &serde_json::Value| value.as_str().is_some_and(|text| text.to_lowercase().contains(query));
+
+    for field in ["name", "description"] {
+        if matches(&meta[field]) {
+            return Some(field.to_string());
+        }
+    }
+    for arg in meta["arguments"].as_array().into_iter().flatten() {
+        let arg_name = arg["name"].as_str().unwrap_or_default();
+        for field in ["name", "description"] {
+            if matches(&arg[field]) {
+                return Some(format!("arguments.{}.{}", arg_name, field));
+            }
+        }
+    }
+    None
+}
+
+#[tauri::command]
+pub async fn search_actions(
+    query: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<serde_json::Value>, String> {
+    let query = query.trim().to_lowercase();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    let results = load_action_metas(&data_root).await?
+        .into_iter()
+        .filter_map(|mut meta| {
+            let field = action_match_field(&meta, &query)?;
+            if let Some(obj) = meta.as_object_mut() {
+                obj.insert("matched_field".to_string(), serde_json::Value::String(field));
+            }
+            Some(meta)
+        })
+        .collect();
+
+    Ok(results)
+}
+
+// Prompt and output cap used for provider connection tests
+const PROVIDER_TEST_PROMPT: &str = "Reply with OK.";
+const PROVIDER_TEST_MAX_TOKENS: u32 = 16;
+const PROVIDER_REQUEST_TIMEOUT_SECS: u64 = 30;
+// Maximum number of providers tested at the same time by test_all_providers
+const PROVIDER_TEST_CONCURRENCY: usize = 4;
+// Model listing should fail fast when the URL is wrong
+const MODEL_LIST_TIMEOUT_SECS: u64 = 5;
+// Outbound LLM requests in flight across the whole app, unless the user sets their own cap
+const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 8;
+const MAX_CONCURRENT_REQUESTS_LIMIT: usize = 64;
+
+// Global cap on concurrent provider requests. When the configured limit changes the one
+// semaphore is resized in place, so requests already in flight keep counting against it
+#[derive(Debug)]
+pub struct RequestLimiter(Mutex<LimiterState>);
+
+#[derive(Debug)]
+struct LimiterState {
+    limit: usize,
+    // Permits still to be removed after a shrink; they are held by requests in flight
+    owed: usize,
+    semaphore: Arc<Semaphore>,
+}
+
+impl Default for RequestLimiter {
+    fn default() -> Self {
+        Self(Mutex::new(LimiterState {
+            limit: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            owed: 0,
+            semaphore: Arc::new(Semaphore::new(DEFAULT_MAX_CONCURRENT_REQUESTS)),
+        }))
+    }
+}
+
+impl RequestLimiter {
+    fn semaphore(&self, limit: usize) -> Arc<Semaphore> {
+        let mut state = self.0.lock().unwrap();
+        let limit = limit.clamp(1, MAX_CONCURRENT_REQUESTS_LIMIT);
+        if limit > state.limit {
+            // Growing first cancels whatever a previous shrink still owes
+            let added = limit - state.limit;
+            let cancelled = added.min(state.owed);
+            state.owed -= cancelled;
+            state.semaphore.add_permits(added - cancelled);
+        } else {
+            state.owed += state.limit - limit;
+        }
+        state.limit = limit;
+        // Take back permits as in-flight requests release them
+        let forgotten = state.semaphore.forget_permits(state.owed);
+        state.owed -= forgotten;
+        state.semaphore.clone()
+    }
+}
+
+// Every outbound provider request holds a permit from this semaphore while it runs
+fn request_semaphore(state: &AppState) -> Arc<Semaphore> {
+    let limit = state.config.lock().unwrap().max_concurrent_requests
+        .unwrap_or(DEFAULT_MAX_CONCURRENT_REQUESTS);
+    state.request_limiter.semaphore(limit)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ProviderHealth {
+    pub alias: String,
+    pub ok: bool,
+    pub latency_ms: u64,
+    pub error: Option<String>,
+}
+
+// Provider kinds the real call path knows how to talk to
+const SUPPORTED_PROVIDER_KINDS: [&str; 4] = ["openai_compatible", "ollama", "anthropic", "google_gemini"];
+const ANTHROPIC_API_VERSION: &str = "2023-06-01";
+// Anthropic requires max_tokens on every request
+const ANTHROPIC_DEFAULT_MAX_TOKENS: u32 = 1024;
+// Smallest extended-thinking budget Anthropic accepts; max_tokens must exceed it
+const ANTHROPIC_THINKING_BUDGET_TOKENS: u32 = 1024;
+
+// How `think` is honored per provider kind:
+// - ollama: sends `think` and reads `message.thinking`
+// - openai_compatible: o-series/gpt-5 models get `reasoning_effort` (high or low, it can't be turned off);
+//   `reasoning_content` or a leading <think> block from other servers is split off
+// - anthropic: enables extended thinking and reads `thinking` blocks
+// - google_gemini: not supported, the flag is ignored
+// With think off, any reasoning a model still returns is dropped
+fn think_supported(provider_kind: &str) -> bool {
+    matches!(provider_kind, "ollama" | "openai_compatible" | "anthropic")
+}
+
+fn is_openai_reasoning_model(model: &str) -> bool {
+    let model = model.to_lowercase();
+    ["o1", "o3", "o4", "gpt-5"].iter().any(|prefix| model.starts_with(prefix))
+}
+
+// Custom provider headers as a HeaderMap, rejecting illegal names and control characters in values
+fn provider_header_map(provider: &LLMProvider) -> Result<reqwest::header::HeaderMap, String> {
+    let mut headers = reqwest::header::HeaderMap::new();
+    for (name, value) in provider.headers.iter().flatten() {
+        let header_name = reqwest::header::HeaderName::from_bytes(name.trim().as_bytes())
+            .map_err(|_| format!("Invalid header name: '{}'", name))?;
+        if value.chars().any(char::is_control) {
+            return Err(format!("Header '{}' contains control characters", name));
+        }
+        let header_value = reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| format!("Invalid value for header '{}'", name))?;
+        headers.insert(header_name, header_value);
+    }
+    Ok(headers)
+}
+
+// Result of a single completion call against a provider
+struct LLMCallResult {
+    text: String,
+    reasoning: Option<String>,
+    latency_ms: u64,
+    prompt_tokens: Option<u64>,
+    completion_tokens: Option<u64>,
+    // Rate-limit response headers, empty when the provider sends none
+    rate_limits: HashMap<String, String>,
+}
+
+// Response headers describing throttling: x-ratelimit-*, ratelimit-*, anthropic-ratelimit-*, retry-after
+fn rate_limit_headers(headers: &reqwest::header::HeaderMap) -> HashMap<String, String> {
+    headers.iter()
+        .filter(|(name, _)| {
+            let name = name.as_str();
+            name == "retry-after"
+                || name.starts_with("x-ratelimit-")
+                || name.starts_with("ratelimit-")
+                || name.starts_with("anthropic-ratelimit-")
+        })
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect()
+}
+
+// Check a proxy URL and return it trimmed; only http:// and https:// proxies are supported
+fn validate_proxy_url(url: &str) -> Result<String, String> {
+    let url = url.trim();
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| format!("Invalid proxy URL '{}': {}", url, e))?;
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(format!("Unsupported proxy scheme '{}'; use http:// or https://", parsed.scheme()));
+    }
+    if parsed.host_str().is_none_or(str::is_empty) {
+        return Err(format!("Proxy URL '{}' has no host", url));
+    }
+    Ok(url.to_string())
+}
+
+// HTTP client shared by every outbound provider call. With a proxy, plain HTTP goes through it
+// and HTTPS is tunnelled with CONNECT; without one reqwest's usual environment handling applies
+fn build_http_client(timeout: Duration, proxy: Option<&str>) -> Result<reqwest::Client, String> {
+    let mut builder = reqwest::Client::builder().timeout(timeout);
+    if let Some(proxy) = proxy {
+        let proxy = reqwest::Proxy::all(validate_proxy_url(proxy)?)
+            .map_err(|e| format!("Invalid proxy URL: {}", e))?;
+        builder = builder.proxy(proxy);
+    }
+    builder.build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+// Build the provider-specific HTTP request for a single-turn prompt
+fn build_llm_request(
+    client: &reqwest::Client,
+    provider: &LLMProvider,
+    prompt: &str,
+    max_tokens: Option<u32>,
+) -> Result<reqwest::RequestBuilder, String> {
+    let base_url = provider.base_url.trim_end_matches('/');
+
+    let request = match provider.provider.as_str() {
+        "openai_compatible" if is_openai_reasoning_model(&provider.model) => {
+            // Reasoning models reject temperature and take max_completion_tokens instead of max_tokens
+            let mut body = serde_json::json!({
+                "model": provider.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "reasoning_effort": if provider.think { "high" } else { "low" }
+            });
+            if let Some(max_tokens) = max_tokens {
+                body["max_completion_tokens"] = serde_json::json!(max_tokens);
+            }
+            let mut request = client.post(format!("{}/chat/completions", base_url)).json(&body);
+            if let Some(api_key) = &provider.api_key {
+                request = request.bearer_auth(api_key);
+            }
+            request
+        }
+        "openai_compatible" => {
+            let mut body = serde_json::json!({
+                "model": provider.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "temperature": provider.temperature
+            });
+            if let Some(max_tokens) = max_tokens {
+                body["max_tokens"] = serde_json::json!(max_tokens);
+            }
+            let mut request = client.post(format!("{}/chat/completions", base_url)).json(&body);
+            if let Some(api_key) = &provider.api_key {
+                request = request.bearer_auth(api_key);
+            }
+            request
+        }
+        "ollama" => {
+            let mut body = serde_json::json!({
+                "model": provider.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "stream": false,
+                "think": provider.think,
+                "options": { "temperature": provider.temperature }
+            });
+            if let Some(max_tokens) = max_tokens {
+                body["options"]["num_predict"] = serde_json::json!(max_tokens);
+            }
+            client.post(format!("{}/api/chat", base_url)).json(&body)
+        }
+        "anthropic" => {
+            let api_key = provider.api_key.as_deref()
+                .ok_or("Anthropic provider requires an API key")?;
+            let max_tokens = max_tokens.unwrap_or(ANTHROPIC_DEFAULT_MAX_TOKENS);
+            let body = if provider.think {
+                // Extended thinking requires the default temperature and room beyond the budget
+                serde_json::json!({
+                    "model": provider.model,
+                    "messages": [{ "role": "user", "content": prompt }],
+                    "max_tokens": max_tokens.max(ANTHROPIC_THINKING_BUDGET_TOKENS + ANTHROPIC_DEFAULT_MAX_TOKENS),
+                    "thinking": { "type": "enabled", "budget_tokens": ANTHROPIC_THINKING_BUDGET_TOKENS }
+                })
+            } else {
+                serde_json::json!({
+                    "model": provider.model,
+                    "messages": [{ "role": "user", "content": prompt }],
+                    "max_tokens": max_tokens,
+                    // Anthropic only accepts temperatures in [0, 1]
+                    "temperature": provider.temperature.clamp(0.0, 1.0)
+                })
+            };
+            client.post(format!("{}/messages", base_url))
+                .header("x-api-key", api_key)
+                .header("anthropic-version", ANTHROPIC_API_VERSION)
+                .json(&body)
+        }
+        "google_gemini" => {
+            let api_key = provider.api_key.as_deref()
+                .ok_or("Gemini provider requires an API key")?;
+            let mut body = serde_json::json!({
+                "contents": [{ "role": "user", "parts": [{ "text": prompt }] }],
+                "generationConfig": { "temperature": provider.temperature }
+            });
+            if let Some(max_tokens) = max_tokens {
+                body["generationConfig"]["maxOutputTokens"] = serde_json::json!(max_tokens);
+            }
+            client.post(format!("{}/models/{}:generateContent", base_url, provider.model))
+                .query(&[("key", api_key)])
+                .json(&body)
+        }
+        other => {
+            return Err(format!(
+                "Unsupported provider '{}'. Supported providers: {}",
+                other,
+                SUPPORTED_PROVIDER_KINDS.join(", ")
+            ));
+        }
+    };
+
+    Ok(request.headers(provider_header_map(provider)?))
+}
+
+// Pull the generated text out of a provider-specific response body
+fn extract_llm_text(provider_kind: &str, body: &serde_json::Value) -> String {
+    let text = match provider_kind {
+        "ollama" => body.pointer("/message/content"),
+        "anthropic" => body.get("content")
+            .and_then(|v| v.as_array())
+            .and_then(|blocks| blocks.iter().find(|b| b.get("type").and_then(|t| t.as_str()) == Some("text")))
+            .and_then(|block| block.get("text")),
+        "google_gemini" => body.pointer("/candidates/0/content/parts/0/text"),
+        _ => body.pointer("/choices/0/message/content"),
+    };
+
+    text.and_then(|v| v.as_str()).unwrap_or_default().to_string()
+}
+
+// Pull the reasoning, if any, out of a provider-specific response body
+fn extract_llm_reasoning(provider_kind: &str, body: &serde_json::Value) -> Option<String> {
+    let reasoning = match provider_kind {
+        "ollama" => body.pointer("/message/thinking")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        "anthropic" => {
+            let blocks: Vec<&str> = body.get("content")
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("thinking"))
+                .filter_map(|b| b.get("thinking").and_then(|t| t.as_str()))
+                .collect();
+            Some(blocks.join("\n\n"))
+        }
+        "openai_compatible" => body.pointer("/choices/0/message/reasoning_content")
+            .or_else(|| body.pointer("/choices/0/message/reasoning"))
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+        _ => None,
+    };
+
+    reasoning.filter(|r| !r.trim().is_empty())
+}
+
+// (prompt, completion) token counts reported in a provider-specific response body
+fn extract_llm_usage(provider_kind: &str, body: &serde_json::Value) -> (Option<u64>, Option<u64>) {
+    let (prompt, completion) = match provider_kind {
+        "ollama" => ("/prompt_eval_count", "/eval_count"),
+        "anthropic" => ("/usage/input_tokens", "/usage/output_tokens"),
+        "google_gemini" => ("/usageMetadata/promptTokenCount", "/usageMetadata/candidatesTokenCount"),
+        _ => ("/usage/prompt_tokens", "/usage/completion_tokens"),
+    };
+    (
+        body.pointer(prompt).and_then(|v| v.as_u64()),
+        body.pointer(completion).and_then(|v| v.as_u64()),
+    )
+}
+
+// Split a leading <think>...</think> block, as emitted by some local reasoning models, from the answer
+fn split_think_block(text: &str) -> (Option<String>, String) {
+    let trimmed = text.trim_start();
+    if let Some(rest) = trimmed.strip_prefix("<think>") {
+        if let Some((reasoning, answer)) = rest.split_once("</think>") {
+            let reasoning = reasoning.trim();
+            let reasoning = (!reasoning.is_empty()).then(|| reasoning.to_string());
+            return (reasoning, answer.trim_start().to_string());
+        }
+    }
+    (None, text.to_string())
+}
+
+// Field and header names whose values never reach the LLM debug log
+const REDACTED_LOG_KEYS: [&str; 4] = ["authorization", "x-api-key", "api_key", "key"];
+
+// Debug log location when debug_llm is enabled
+fn llm_debug_log_path(config: &AppConfig) -> Option<PathBuf> {
+    if config.debug_llm || debug_llm_from_env() {
+        Some(config.data_root.join("logs").join("llm.jsonl"))
+    } else {
+        None
+    }
+}
+
+fn redact_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, field) in map.iter_mut() {
+                if REDACTED_LOG_KEYS.contains(&key.to_lowercase().as_str()) {
+                    *field = serde_json::Value::String("***".to_string());
+                } else if key.eq_ignore_ascii_case("headers") && field.is_object() {
+                    // Custom provider headers often carry tokens under arbitrary names, so hide every value
+                    field.as_object_mut().unwrap().values_mut()
+                        .for_each(|value| *value = serde_json::Value::String("***".to_string()));
+                } else {
+                    redact_secrets(field);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_secrets),
+        _ => {}
+    }
+}
+
+fn llm_request_log_entry(alias: &str, request: &reqwest::Request) -> serde_json::Value {
+    let headers: serde_json::Map<String, serde_json::Value> = request.headers().iter()
+        .map(|(name, value)| (
+            name.as_str().to_string(),
+            serde_json::Value::String(value.to_str().unwrap_or("<binary>").to_string()),
+        ))
+        .collect();
+
+    // Query parameters are logged separately so they can be redacted (Gemini passes its key there)
+    let query: serde_json::Map<String, serde_json::Value> = request.url().query_pairs()
+        .map(|(name, value)| (name.into_owned(), serde_json::Value::String(value.into_owned())))
+        .collect();
+    let mut url = request.url().clone();
+    url.set_query(None);
+
+    let body = request.body()
+        .and_then(|b| b.as_bytes())
+        .and_then(|bytes| serde_json::from_slice(bytes).ok())
+        .unwrap_or(serde_json::Value::Null);
+
+    serde_json::json!({
+        "ts": chrono::Utc::now().to_rfc3339(),
+        "direction": "request",
+        "alias": alias,
+        "method": request.method().as_str(),
+        "url": url.as_str(),
+        "query": query,
+        "headers": headers,
+        "body": body
+    })
+}
+
+// Append a redacted entry to the LLM debug log; failures never affect the call itself
+fn write_llm_log(log_path: &Path, mut entry: serde_json::Value, secret: Option<&str>) {
+    redact_secrets(&mut entry);
+
+    // Catch the key anywhere else it might appear, e.g. echoed back in an error body
+    let mut line = entry.to_string();
+    if let Some(secret) = secret.filter(|s| !s.is_empty()) {
+        line = line.replace(secret, "***");
+    }
+    line.push('\n');
+
+    let result = log_path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::OpenOptions::new().create(true).append(true).open(log_path))
+        .and_then(|mut file| file.write_all(line.as_bytes()));
+
+    if let Err(e) = result {
+        println!("Failed to write LLM debug log {:?}: {}", log_path, e);
+    }
+}
+
+// Send a single-turn prompt to the provider's real endpoint
+async fn call_llm_provider(
+    provider: &LLMProvider,
+    prompt: &str,
+    max_tokens: Option<u32>,
+    debug_log: Option<&Path>,
+    proxy: Option<&str>,
+) -> Result<LLMCallResult, String> {
+    let client = build_http_client(Duration::from_secs(PROVIDER_REQUEST_TIMEOUT_SECS), proxy)?;
+
+    let max_tokens = max_tokens.or(provider.max_tokens);
+    let request = build_llm_request(&client, provider, prompt, max_tokens)?
+        .build()
+        .map_err(|e| format!("Failed to build provider request: {}", e))?;
+
+    let secret = provider.api_key.as_deref();
+    if let Some(log_path) = debug_log {
+        write_llm_log(log_path, llm_request_log_entry(&provider.alias, &request), secret);
+    }
+
+    let started = Instant::now();
+    let result = client.execute(request).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let response = match result {
+        Ok(response) => response,
+        Err(e) => {
+            if let Some(log_path) = debug_log {
+                write_llm_log(log_path, serde_json::json!({
+                    "ts": chrono::Utc::now().to_rfc3339(),
+                    "direction": "response",
+                    "alias": provider.alias,
+                    "error": e.to_string()
+                }), secret);
+            }
+            return Err(format!("Failed to reach provider: {}", e));
+        }
+    };
+
+    let status = response.status();
+    let rate_limits = rate_limit_headers(response.headers());
+    let raw_body = response.text().await
+        .map_err(|e| format!("Failed to read provider response: {}", e))?;
+
+    if let Some(log_path) = debug_log {
+        write_llm_log(log_path, serde_json::json!({
+            "ts": chrono::Utc::now().to_rfc3339(),
+            "direction": "response",
+            "alias": provider.alias,
+            "status": status.as_u16(),
+            "latency_ms": latency_ms,
+            "body": raw_body
+        }), secret);
+    }
+
+    if !status.is_success() {
+        return Err(format!("Provider returned HTTP {}: {}", status.as_u16(), raw_body));
+    }
+
+    let body: serde_json::Value = serde_json::from_str(&raw_body)
+        .map_err(|e| format!("Failed to parse provider response: {}", e))?;
+    let (inline_reasoning, text) = split_think_block(&extract_llm_text(&provider.provider, &body));
+    let reasoning = extract_llm_reasoning(&provider.provider, &body)
+        .or(inline_reasoning)
+        .filter(|_| provider.think);
+
+    let (prompt_tokens, completion_tokens) = extract_llm_usage(&provider.provider, &body);
+
+    Ok(LLMCallResult { text, reasoning, latency_ms, prompt_tokens, completion_tokens, rate_limits })
+}
+
+#[tauri::command]
+pub async fn test_llm_provider(
+    provider: LLMProvider,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    println!("Testing provider: {} ({})", provider.name, provider.alias);
+
+    let (data_root, debug_log, proxy) = {
+        let config = state.config.lock().unwrap();
+        (config.data_root.clone(), llm_debug_log_path(&config), config.http_proxy.clone())
+    };
+    record_provider_usage(&data_root, std::slice::from_ref(&provider.alias));
+    let _permit = request_semaphore(&state).acquire_owned().await;
+    let test_result = match call_llm_provider(
+        &provider,
+        PROVIDER_TEST_PROMPT,
+        Some(PROVIDER_TEST_MAX_TOKENS),
+        debug_log.as_deref(),
+        proxy.as_deref(),
+    ).await {
+        Ok(result) => {
+            let mut test_result = serde_json::json!({
+                "success": true,
+                "provider": provider.name,
+                "alias": provider.alias,
+                "model": provider.model,
+                "base_url": provider.base_url,
+                "response_time_ms": result.latency_ms,
+                "test_message": "Connection test successful",
+                "response": result.text,
+                "reasoning": result.reasoning,
+                "think_supported": think_supported(&provider.provider),
+                "timestamp": chrono::Utc::now().to_rfc3339()
+            });
+            // Only present when the provider actually sent rate-limit headers
+            if !result.rate_limits.is_empty() {
+                test_result["rate_limits"] = serde_json::json!(result.rate_limits);
+            }
+            test_result
+        }
+        Err(error) => serde_json::json!({
+            "success": false,
+            "provider": provider.name,
+            "alias": provider.alias,
+            "model": provider.model,
+            "base_url": provider.base_url,
+            "error": error,
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }),
+    };
+
+    Ok(test_result)
+}
+
+const TOOL_PROBE_PROMPT: &str = "What time is it in UTC? Use the get_current_time tool.";
+// Room for a tool call's name and JSON arguments
+const TOOL_PROBE_MAX_TOKENS: u32 = 128;
+
+// Add a single get_current_time tool to a provider request body
+fn add_probe_tool(kind: &str, body: &mut serde_json::Value) {
+    let parameters = serde_json::json!({
+        "type": "object",
+        "properties": { "timezone": { "type": "string", "description": "IANA timezone name" } },
+        "required": ["timezone"]
+    });
+    let description = "Returns the current time in a timezone";
+    match kind {
+        "anthropic" => {
+            body["tools"] = serde_json::json!([
+                { "name": "get_current_time", "description": description, "input_schema": parameters }
+            ]);
+        }
+        "google_gemini" => {
+            body["tools"] = serde_json::json!([{ "functionDeclarations": [
+                { "name": "get_current_time", "description": description, "parameters": parameters }
+            ]}]);
+        }
+        // openai_compatible and ollama share the OpenAI tools shape
+        _ => {
+            body["tools"] = serde_json::json!([{ "type": "function", "function": {
+                "name": "get_current_time", "description": description, "parameters": parameters
+            }}]);
+        }
+    }
+}
+
+fn response_has_tool_call(kind: &str, body: &serde_json::Value) -> bool {
+    let non_empty = |value: Option<&serde_json::Value>| value.and_then(|v| v.as_array()).is_some_and(|a| !a.is_empty());
+    match kind {
+        "ollama" => non_empty(body.pointer("/message/tool_calls")),
+        "anthropic" => body["content"].as_array().into_iter().flatten()
+            .any(|block| block["type"] == "tool_use"),
+        "google_gemini" => body.pointer("/candidates/0/content/parts")
+            .and_then(|parts| parts.as_array()).into_iter().flatten()
+            .any(|part| part.get("functionCall").is_some()),
+        _ => non_empty(body.pointer("/choices/0/message/tool_calls")),
+    }
+}
+
+// Send a prompt that should trigger a tool call. Ok(false) means the provider rejected or ignored
+// the tools; Err is for failures unrelated to tools (network, auth, rate limits, server errors)
+async fn probe_provider_tools(provider: &LLMProvider, proxy: Option<&str>) -> Result<bool, String> {
+    let client = build_http_client(Duration::from_secs(PROVIDER_REQUEST_TIMEOUT_SECS), proxy)?;
+    let mut request = build_llm_request(&client, provider, TOOL_PROBE_PROMPT, Some(TOOL_PROBE_MAX_TOKENS))?
+        .build()
+        .map_err(|e| format!("Failed to build provider request: {}", e))?;
+
+    let mut body: serde_json::Value = request.body()
+        .and_then(|body| body.as_bytes())
+        .and_then(|bytes| serde_json::from_slice(bytes).ok())
+        .ok_or("Failed to build provider request")?;
+    add_probe_tool(&provider.provider, &mut body);
+    *request.body_mut() = Some(body.to_string().into());
+
+    let response = client.execute(request).await
+        .map_err(|e| format!("Failed to reach provider: {}", e))?;
+    let status = response.status();
+    let raw_body = response.text().await
+        .map_err(|e| format!("Failed to read provider response: {}", e))?;
+
+    if status.is_client_error() && ![401, 403, 429].contains(&status.as_u16()) {
+        println!("Provider {} rejected the tool probe: HTTP {}", provider.alias, status.as_u16());
+        return Ok(false);
+    }
+    if !status.is_success() {
+        return Err(format!("Provider returned HTTP {}: {}", status.as_u16(), raw_body));
+    }
+
+    let body: serde_json::Value = serde_json::from_str(&raw_body)
+        .map_err(|e| format!("Failed to parse provider response: {}", e))?;
+    Ok(response_has_tool_call(&provider.provider, &body))
+}
+
+#[tauri::command]
+pub async fn probe_tool_support(
+    alias: String,
+    state: State<'_, AppState>,
+) -> Result<bool, String> {
+    let (provider, proxy) = {
+        let config = state.config.lock().unwrap();
+        let provider = config.llm_providers.iter()
+            .find(|p| alias_matches(&p.alias, &alias))
+            .cloned()
+            .ok_or_else(|| format!("Provider with alias '{}' not found", alias))?;
+        (provider, config.http_proxy.clone())
+    };
+
+    // Providers pointing at the same model share a result
+    let (base_url, model) = provider_endpoint(&provider);
+    let cache_key = format!("{}|{}|{}", provider.provider, base_url, model);
+    if let Some(supported) = state.tool_support.lock().unwrap().get(&cache_key) {
+        return Ok(*supported);
+    }
+
+    let supported = {
+        let _permit = request_semaphore(&state).acquire_owned().await;
+        probe_provider_tools(&provider, proxy.as_deref()).await?
+    };
+    state.tool_support.lock().unwrap().insert(cache_key, supported);
+    println!("Tool calling {} for {} ({})", if supported { "supported" } else { "not supported" }, alias, provider.model);
+    Ok(supported)
+}
+
+#[tauri::command]
+pub async fn test_all_providers(
+    state: State<'_, AppState>,
+) -> Result<Vec<ProviderHealth>, String> {
+    let (data_root, providers, debug_log, proxy) = {
+        let config = state.config.lock().unwrap();
+        (config.data_root.clone(), config.llm_providers.clone(), llm_debug_log_path(&config), config.http_proxy.clone())
+    };
+    let aliases: Vec<String> = providers.iter().map(|p| p.alias.clone()).collect();
+    record_provider_usage(&data_root, &aliases);
+    let limiter = Arc::new(Semaphore::new(PROVIDER_TEST_CONCURRENCY));
+    let global = request_semaphore(&state);
+
+    // Spawn every test up front; the semaphores keep only a few in flight
+    let handles: Vec<_> = providers.into_iter().map(|provider| {
+        let (limiter, global) = (limiter.clone(), global.clone());
+        let (debug_log, proxy) = (debug_log.clone(), proxy.clone());
+        tokio::spawn(async move {
+            let _permit = limiter.acquire_owned().await;
+            let _global_permit = global.acquire_owned().await;
+            let result = call_llm_provider(
+                &provider,
+                PROVIDER_TEST_PROMPT,
+                Some(PROVIDER_TEST_MAX_TOKENS),
+                debug_log.as_deref(),
+                proxy.as_deref(),
+            ).await;
+            (provider.alias, result)
+        })
+    }).collect();
+
+    let mut results = Vec::new();
+    for handle in handles {
+        let (alias, result) = handle.await
+            .map_err(|e| format!("Provider test task failed: {}", e))?;
+        results.push(match result {
+            Ok(call) => ProviderHealth {
+                alias,
+                ok: true,
+                latency_ms: call.latency_ms,
+                error: None,
+            },
+            Err(error) => ProviderHealth {
+                alias,
+                ok: false,
+                latency_ms: 0,
+                error: Some(error),
+            },
+        });
+    }
+
+    Ok(results)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CompareResult {
+    pub alias: String,
+    pub ok: bool,
+    pub response: Option<String>,
+    pub latency_ms: u64,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    pub error: Option<String>,
+}
+
+#[tauri::command]
+pub async fn compare_providers(
+    prompt: String,
+    aliases: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<CompareResult>, String> {
+    let (data_root, providers, debug_log, proxy) = {
+        let config = state.config.lock().unwrap();
+        (config.data_root.clone(), config.llm_providers.clone(), llm_debug_log_path(&config), config.http_proxy.clone())
+    };
+    if prompt.trim().is_empty() {
+        return Err("Prompt cannot be empty".to_string());
+    }
+    let used: Vec<String> = providers.iter()
+        .filter(|p| aliases.iter().any(|alias| alias_matches(&p.alias, alias)))
+        .map(|p| p.alias.clone())
+        .collect();
+    record_provider_usage(&data_root, &used);
+    let limiter = Arc::new(Semaphore::new(PROVIDER_TEST_CONCURRENCY));
+    let global = request_semaphore(&state);
+    let prompt = Arc::new(prompt);
+
+    // Same fan-out as test_all_providers; one provider failing doesn't affect the others
+    let handles: Vec<_> = aliases.into_iter().map(|alias| {
+        let provider = providers.iter().find(|p| alias_matches(&p.alias, &alias)).cloned();
+        let (limiter, global, prompt) = (limiter.clone(), global.clone(), prompt.clone());
+        let (debug_log, proxy) = (debug_log.clone(), proxy.clone());
+        tokio::spawn(async move {
+            let Some(provider) = provider else {
+                return (alias.clone(), Err(format!("Provider with alias '{}' not found", alias)));
+            };
+            let _permit = limiter.acquire_owned().await;
+            let _global_permit = global.acquire_owned().await;
+            let result = call_llm_provider(&provider, &prompt, None, debug_log.as_deref(), proxy.as_deref()).await;
+            (provider.alias, result)
+        })
+    }).collect();
+
+    let mut results = Vec::new();
+    for handle in handles {
+        let (alias, result) = handle.await
+            .map_err(|e| format!("Provider comparison task failed: {}", e))?;
+        results.push(match result {
+            Ok(call) => CompareResult {
+                alias,
+                ok: true,
+                response: Some(call.text),
+                latency_ms: call.latency_ms,
+                prompt_tokens: call.prompt_tokens,
+                completion_tokens: call.completion_tokens,
+                error: None,
+            },
+            Err(error) => CompareResult {
+                alias,
+                ok: false,
+                response: None,
+                latency_ms: 0,
+                prompt_tokens: None,
+                completion_tokens: None,
+                error: Some(error),
+            },
+        });
+    }
+
+    Ok(results)
+}
+
+#[tauri::command]
+pub async fn list_ollama_models(
+    base_url: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let proxy = state.config.lock().unwrap().http_proxy.clone();
+    let _permit = request_semaphore(&state).acquire_owned().await;
+    fetch_ollama_models(&base_url, proxy.as_deref()).await
+}
+
+async fn fetch_ollama_models(base_url: &str, proxy: Option<&str>) -> Result<Vec<String>, String> {
+    let client = build_http_client(Duration::from_secs(MODEL_LIST_TIMEOUT_SECS), proxy)?;
+    let base_url = base_url.trim().trim_end_matches('/');
+
+    let response = client.get(format!("{}/api/tags", base_url)).send().await
+        .map_err(|e| {
+            if e.is_connect() || e.is_timeout() {
+                format!("Could not connect to Ollama at {}. Is Ollama running?", base_url)
+            } else {
+                format!("Failed to query Ollama: {}", e)
+            }
+        })?;
+
+    if !response.status().is_success() {
+        return Err(format!("Ollama returned HTTP {}", response.status().as_u16()));
+    }
+
+    let body: serde_json::Value = response.json().await
+        .map_err(|e| format!("Failed to parse Ollama response: {}", e))?;
+
+    let models = body.get("models")
+        .and_then(|v| v.as_array())
+        .ok_or("Invalid Ollama response: missing models array")?
+        .iter()
+        .filter_map(|model| model.get("name").and_then(|v| v.as_str()))
+        .map(|name| name.to_string())
+        .collect();
+
+    Ok(models)
+}
+
+#[tauri::command]
+pub async fn list_provider_models(
+    base_url: String,
+    api_key: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let proxy = state.config.lock().unwrap().http_proxy.clone();
+    let _permit = request_semaphore(&state).acquire_owned().await;
+    fetch_provider_models(&base_url, api_key.as_deref(), proxy.as_deref()).await
+}
+
+async fn fetch_provider_models(base_url: &str, api_key: Option<&str>, proxy: Option<&str>) -> Result<Vec<String>, String> {
+    let client = build_http_client(Duration::from_secs(MODEL_LIST_TIMEOUT_SECS), proxy)?;
+    let base_url = base_url.trim().trim_end_matches('/');
+
+    let mut request = client.get(format!("{}/models", base_url));
+    if let Some(api_key) = api_key.filter(|k| !k.is_empty()) {
+        request = request.bearer_auth(api_key);
+    }
+
+    let response = request.send().await
+        .map_err(|e| format!("Could not connect to {}: {}", base_url, e))?;
+
+    match response.status().as_u16() {
+        401 | 403 => return Err("Invalid API key".to_string()),
+        404 => return Err(format!("No models endpoint found at {}/models", base_url)),
+        status if !response.status().is_success() => {
+            return Err(format!("Provider returned HTTP {}", status));
+        }
+        _ => {}
+    }
+
+    let body: serde_json::Value = response.json().await
+        .map_err(|e| format!("Failed to parse models response: {}", e))?;
+
+    let models = body.get("data")
+        .and_then(|v| v.as_array())
+        .ok_or("Invalid models response: missing data array")?
+        .iter()
+        .filter_map(|model| model.get("id").and_then(|v| v.as_str()))
+        .map(|id| id.to_string())
+        .collect();
+
+    Ok(models)
+}
+
+// Token estimation
+
+// Built-in USD prices per million tokens, matched by longest model-name prefix
+const BUILTIN_MODEL_PRICES: [(&str, f64, f64); 14] = [
+    ("gpt-4o-mini", 0.15, 0.60),
+    ("gpt-4o", 2.50, 10.00),
+    ("gpt-4-turbo", 10.00, 30.00),
+    ("gpt-4", 30.00, 60.00),
+    ("gpt-3.5-turbo", 0.50, 1.50),
+    ("o1-mini", 1.10, 4.40),
+    ("o1", 15.00, 60.00),
+    ("o3-mini", 1.10, 4.40),
+    ("claude-3-5-sonnet", 3.00, 15.00),
+    ("claude-3-5-haiku", 0.80, 4.00),
+    ("claude-3-opus", 15.00, 75.00),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("gemini-1.5-pro", 1.25, 5.00),
+    ("gemini-1.5-flash", 0.075, 0.30),
+];
+
+// (model prefix, context window, max output tokens); the longest prefix that matches whole
+// name segments wins, so "gpt-4" covers "gpt-4-0613" but not "gpt-4o" or "gpt-4.1"
+const BUILTIN_MODEL_LIMITS: [(&str, u32, u32); 19] = [
+    ("gpt-4.1", 1_047_576, 32_768),
+    ("gpt-4.1-mini", 1_047_576, 32_768),
+    ("gpt-4.1-nano", 1_047_576, 32_768),
+    ("gpt-4o-mini", 128_000, 16_384),
+    ("gpt-4o", 128_000, 16_384),
+    ("gpt-4-turbo", 128_000, 4_096),
+    ("gpt-4-32k", 32_768, 8_192),
+    ("gpt-4", 8_192, 8_192),
+    ("gpt-3.5-turbo", 16_385, 4_096),
+    ("o1-preview", 128_000, 32_768),
+    ("o1-mini", 128_000, 65_536),
+    ("o1", 200_000, 100_000),
+    ("o3-mini", 200_000, 100_000),
+    ("claude-3-5-sonnet", 200_000, 8_192),
+    ("claude-3-5-haiku", 200_000, 8_192),
+    ("claude-3-opus", 200_000, 4_096),
+    ("claude-3-haiku", 200_000, 4_096),
+    ("gemini-1.5-pro", 2_097_152, 8_192),
+    ("gemini-1.5-flash", 1_048_576, 8_192),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModelLimits {
+    pub model: String,
+    pub context_window: u32,
+    pub max_output_tokens: u32,
+}
+
+// `prefix` followed by the end of the name or a separator, as in "o1-2024-12-17" or "gpt-4:latest"
+fn model_segments_match(model: &str, prefix: &str) -> bool {
+    model.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['-', ':', '@']))
+}
+
+fn find_model_limits(model: &str) -> Option<ModelLimits> {
+    let model = model.to_lowercase();
+    BUILTIN_MODEL_LIMITS.iter()
+        .filter(|(prefix, _, _)| model_segments_match(&model, prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map(|(prefix, context_window, max_output_tokens)| ModelLimits {
+            model: prefix.to_string(),
+            context_window: *context_window,
+            max_output_tokens: *max_output_tokens,
+        })
+}
+
+// Err carries a warning when max_tokens exceeds the model's known output limit; unknown models pass
+fn validate_max_tokens(model: &str, max_tokens: Option<u32>) -> Result<(), String> {
+    match (find_model_limits(model), max_tokens) {
+        (Some(limits), Some(max_tokens)) if max_tokens > limits.max_output_tokens => Err(format!(
+            "max_tokens {} exceeds the {} output limit of {} tokens; calls may fail",
+            max_tokens, limits.model, limits.max_output_tokens
+        )),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub async fn get_model_limits(model: String) -> Result<Option<ModelLimits>, String> {
+    Ok(find_model_limits(&model))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenEstimate {
+    pub model: String,
+    pub tokens: usize,
+    pub method: String, // "tiktoken" or "heuristic"
+    pub estimated_cost_usd: Option<f64>,
+}
+
+// Count tokens with the model's tiktoken encoding, or approximate from words for other models
+fn count_tokens(text: &str, model: &str) -> (usize, &'static str) {
+    use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+
+    let bpe = match get_tokenizer(model) {
+        Some(Tokenizer::O200kBase) => Some(tiktoken_rs::o200k_base_singleton()),
+        Some(Tokenizer::Cl100kBase) => Some(tiktoken_rs::cl100k_base_singleton()),
+        Some(Tokenizer::P50kBase) => Some(tiktoken_rs::p50k_base_singleton()),
+        Some(Tokenizer::P50kEdit) => Some(tiktoken_rs::p50k_edit_singleton()),
+        Some(Tokenizer::R50kBase) | Some(Tokenizer::Gpt2) => Some(tiktoken_rs::r50k_base_singleton()),
+        None => None,
+    };
+
+    match bpe {
+        Some(bpe) => (bpe.lock().encode_with_special_tokens(text).len(), "tiktoken"),
+        // Roughly 4 tokens per 3 English words
+        None => ((text.split_whitespace().count() * 4).div_ceil(3), "heuristic"),
+    }
+}
+
+// Built-in prices with the user's overrides applied
+fn effective_model_prices(config: &AppConfig) -> Vec<ModelPrice> {
+    let mut prices: Vec<ModelPrice> = BUILTIN_MODEL_PRICES.iter()
+        .filter(|(model, _, _)| !config.model_prices.iter().any(|p| p.model == *model))
+        .map(|(model, input, output)| ModelPrice {
+            model: model.to_string(),
+            input_per_million: *input,
+            output_per_million: *output,
+        })
+        .collect();
+    prices.extend(config.model_prices.iter().cloned());
+    prices
+}
+
+fn find_model_price<'a>(prices: &'a [ModelPrice], model: &str) -> Option<&'a ModelPrice> {
+    let model = model.to_lowercase();
+    prices.iter()
+        .filter(|p| model.starts_with(&p.model.to_lowercase()))
+        .max_by_key(|p| p.model.len())
+}
+
+#[tauri::command]
+pub async fn estimate_tokens(
+    text: String,
+    model: String,
+    state: State<'_, AppState>,
+) -> Result<TokenEstimate, String> {
+    let prices = effective_model_prices(&state.config.lock().unwrap());
+    let (tokens, method) = count_tokens(&text, &model);
+
+    // The prompt is billed as input tokens
+    let estimated_cost_usd = find_model_price(&prices, &model)
+        .map(|price| tokens as f64 * price.input_per_million / 1_000_000.0);
+
+    Ok(TokenEstimate {
+        model,
+        tokens,
+        method: method.to_string(),
+        estimated_cost_usd,
+    })
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContextEstimate {
+    pub thread_id: String,
+    pub model: String,
+    pub tokens: usize,
+    pub method: String, // "tiktoken" or "heuristic"
+    // None when the planner model isn't in the built-in limits table
+    pub context_window: Option<u32>,
+    pub fits: bool,
+    pub overflow: usize,
+    // Selected knowledge that couldn't be found and wasn't counted
+    pub missing_knowledge: Vec<String>,
+}
+
+#[tauri::command]
+pub async fn estimate_thread_context_size(
+    thread_id: String,
+    state: State<'_, AppState>,
+) -> Result<ContextEstimate, String> {
+    let config = state.config.lock().unwrap().clone();
+    let thread = load_thread(&config.data_root, &thread_id)?;
+    let thread_config = thread.config
+        .ok_or_else(|| format!("Thread '{}' has no config", thread_id))?;
+    let planner = config.llm_providers.iter()
+        .find(|p| alias_matches(&p.alias, &thread_config.planner_llm_alias))
+        .ok_or_else(|| format!("Planner provider '{}' not found", thread_config.planner_llm_alias))?;
+
+    let index = resource_index(&config.data_root.join("knowledge"), None);
+    let (paths, missing_knowledge) = resolve_selections(&thread_config.selected_knowledge, &index);
+
+    let mut text = String::new();
+    for path in &paths {
+        let content = read_resource_text(path)
+            .map_err(|e| format!("Failed to read knowledge file {:?}: {}", path, e))?;
+        let knowledge: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse knowledge file {:?}: {}", path, e))?;
+        for entry in knowledge["entries"].as_array().into_iter().flatten() {
+            for field in ["name", "description", "content"] {
+                text.push_str(entry[field].as_str().unwrap_or_default());
+                text.push('\n');
+            }
+        }
+    }
+
+    let (tokens, method) = count_tokens(&text, &planner.model);
+    let context_window = find_model_limits(&planner.model).map(|limits| limits.context_window);
+    let overflow = context_window.map_or(0, |window| tokens.saturating_sub(window as usize));
+
+    Ok(ContextEstimate {
+        thread_id,
+        model: planner.model.clone(),
+        tokens,
+        method: method.to_string(),
+        context_window,
+        fits: overflow == 0,
+        overflow,
+        missing_knowledge,
+    })
+}
+
+#[tauri::command]
+pub async fn get_model_prices(
+    state: State<'_, AppState>,
+) -> Result<Vec<ModelPrice>, String> {
+    let config = state.config.lock().unwrap();
+    Ok(effective_model_prices(&config))
+}
+
+#[tauri::command]
+pub async fn set_model_price(
+    price: ModelPrice,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    if price.model.trim().is_empty() {
+        return Err("Model name cannot be empty".to_string());
+    }
+
+    if price.input_per_million < 0.0 || price.output_per_million < 0.0 {
+        return Err("Prices cannot be negative".to_string());
+    }
+
+    config.model_prices.retain(|p| p.model != price.model);
+    config.model_prices.push(price);
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn export_providers(
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let config = state.config.lock().unwrap();
+    let export_data = serde_json::json!({
+        "version": "1.0",
+        "exported_at": chrono::Utc::now().to_rfc3339(),
+        "providers": config.llm_providers
+    });
+
+    serde_json::to_string_pretty(&export_data)
+        .map_err(|e| format!("Failed to serialize providers: {}", e))
+}
+
+// How imported providers are merged when their alias already exists
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportStrategy {
+    // Keep the existing provider
+    #[default]
+    Skip,
+    // Replace the existing provider
+    Overwrite,
+    // Import under a free alias such as "gpt-4-2"
+    Rename,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportSummary {
+    pub imported: usize,
+    pub skipped: usize,
+    pub warnings: Vec<String>,
+    // Aliases skipped because an existing provider has the same fingerprint
+    pub fingerprint_duplicates: Vec<String>,
+}
+
+// First "{alias}-N" not used by any provider
+fn free_alias(config: &AppConfig, alias: &str) -> String {
+    (2..)
+        .map(|n| format!("{}-{}", alias.trim(), n))
+        .find(|candidate| !config.llm_providers.iter().any(|p| alias_matches(&p.alias, candidate)))
+        .unwrap()
+}
+
+// Merge providers into the config; invalid ones are skipped, and with `dedupe_by_fingerprint`
+// so are providers identical to one under a different alias
+fn merge_providers(
+    config: &mut AppConfig,
+    providers: Vec<LLMProvider>,
+    strategy: ImportStrategy,
+    dedupe_by_fingerprint: bool,
+) -> ImportSummary {
+    let mut summary = ImportSummary { imported: 0, skipped: 0, warnings: Vec::new(), fingerprint_duplicates: Vec::new() };
+
+    for mut provider in providers {
+        // Same-alias collisions are left to the import strategy
+        let fingerprint = provider_fingerprint(&provider);
+        if dedupe_by_fingerprint && config.llm_providers.iter()
+            .any(|p| !alias_matches(&p.alias, &provider.alias) && provider_fingerprint(p) == fingerprint)
+        {
+            summary.skipped += 1;
+            summary.fingerprint_duplicates.push(provider.alias);
+            continue;
+        }
+
+        let existing = config.llm_providers.iter().position(|p| alias_matches(&p.alias, &provider.alias));
+        let warnings = provider_warnings(&provider, &config.llm_providers);
+        let result = match (existing, strategy) {
+            (Some(index), ImportStrategy::Overwrite) => {
+                provider.alias = config.llm_providers[index].alias.clone();
+                ensure_unlocked(&config.llm_providers[index])
+                    .and_then(|_| clean_base_url(&provider.base_url))
+                    .map(|base_url| {
+                        provider.base_url = base_url;
+                        provider.locked &= admin_from_env();
+                        config.llm_providers[index] = provider;
+                    })
+            }
+            (Some(_), ImportStrategy::Rename) => {
+                provider.alias = free_alias(config, &provider.alias);
+                add_provider_to_config(config, provider)
+            }
+            _ => add_provider_to_config(config, provider),
+        };
+
+        match result {
+            Ok(()) => {
+                summary.imported += 1;
+                summary.warnings.extend(warnings);
+            }
+            Err(_) => summary.skipped += 1,
+        }
+    }
+
+    summary
+}
+
+#[tauri::command]
+pub async fn import_providers(
+    providers_json: String,
+    strategy: Option<ImportStrategy>,
+    dedupe_by_fingerprint: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ImportSummary, String> {
+    ensure_writable(&state)?;
+    let import_data: serde_json::Value = serde_json::from_str(&providers_json)
+        .map_err(|e| format!("Failed to parse JSON: {}", e))?;
+
+    let providers = import_data.get("providers")
+        .and_then(|p| p.as_array())
+        .ok_or("Invalid format: missing providers array")?;
+    let providers: Vec<LLMProvider> = providers.iter()
+        .filter_map(|p| serde_json::from_value(p.clone()).ok())
+        .collect();
+
+    let mut config = state.config.lock().unwrap();
+    let summary = merge_providers(
+        &mut config,
+        providers,
+        strategy.unwrap_or_default(),
+        dedupe_by_fingerprint.unwrap_or(false),
+    );
+
+    if summary.imported > 0 {
+        save_config_to_file(&*config)?;
+    }
+
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn import_providers_from_file(
+    path: String,
+    strategy: Option<ImportStrategy>,
+    dedupe_by_fingerprint: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ImportSummary, String> {
+    ensure_writable(&state)?;
+    let content = read_resource_text(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let raw: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path, e))?;
+
+    let mut config = state.config.lock().unwrap();
+
+    // Older configs are upgraded in memory the same way our own file is on load
+    let (other, _) = migrate_config(raw, &config)
+        .map_err(|e| format!("{} is not a valid pulsar-studio config: {}", path, e))?;
+    let summary = merge_providers(
+        &mut config,
+        other.llm_providers,
+        strategy.unwrap_or_default(),
+        dedupe_by_fingerprint.unwrap_or(false),
+    );
+
+    if summary.imported > 0 {
+        save_config_to_file(&config)?;
+    }
+
+    println!("Imported {} providers from {} ({} skipped)", summary.imported, path, summary.skipped);
+    Ok(summary)
+}
+
+// Knowledge Management Commands
+#[tauri::command]
+pub async fn load_knowledge(
+    filename: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let knowledge_dir = data_root.join("knowledge");
+
+    // Validate filename to prevent directory traversal
+    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    let knowledge_path = locate_resource(&knowledge_dir, &filename)
+        .ok_or_else(|| format!("Knowledge file '{}' not found", filename))?;
+
+    let content = read_resource_text(&knowledge_path)
+        .map_err(|e| format!("Failed to read knowledge file: {}", e))?;
+
+    let json: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse knowledge JSON: {}", e))?;
+
+    Ok(json)
+}
+
+#[tauri::command]
+pub async fn save_knowledge(
+    filename: String,
+    knowledge_data: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap();
+    let knowledge_dir = config.data_root.join("knowledge");
+
+    // Validate filename to prevent directory traversal
+    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    // Ensure knowledge directory exists
+    fs::create_dir_all(&knowledge_dir)
+        .map_err(|e| format!("Failed to create knowledge directory: {}", e))?;
+
+    // Validate knowledge structure
+    validate_knowledge_structure(&knowledge_data)?;
+
+    let content = serde_json::to_string_pretty(&knowledge_data)
+        .map_err(|e| format!("Failed to serialize knowledge data: {}", e))?;
+
+    // Saved as .json or .json.gz depending on the compress setting
+    let knowledge_path = write_resource(&knowledge_dir, &filename, &content, config.compress)?;
+
+    println!("Knowledge saved to: {:?}", knowledge_path);
+    Ok(())
+}
+
+// Save knowledge from editor text so syntax errors come back with their line and column
+#[tauri::command]
+pub async fn save_knowledge_text(
+    filename: String,
+    content: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    let knowledge_data = parse_json_text(&content).map_err(|e| e.describe())?;
+    save_knowledge(filename, knowledge_data, state).await
+}
+
+// ATX heading level and text, e.g. "## Setup" -> (2, "Setup")
+fn markdown_heading(line: &str) -> Option<(usize, &str)> {
+    let trimmed = line.trim_end();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    let rest = &trimmed[level..];
+    if (1..=6).contains(&level) && (rest.is_empty() || rest.starts_with(' ')) {
+        Some((level, rest.trim().trim_end_matches('#').trim()))
+    } else {
+        None
+    }
+}
+
+fn markdown_entry(name: &str, body: &[&str]) -> serde_json::Value {
+    let content = body.join("\n").trim().to_string();
+    let description = content.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or(name)
+        .to_string();
+    serde_json::json!({
+        "name": name,
+        "description": description,
+        "content": content,
+    })
+}
+
+// Split Markdown into knowledge entries at its top-level (shallowest) headings; no headings gives one entry
+fn markdown_to_knowledge_entries(markdown: &str, fallback_name: &str) -> Vec<serde_json::Value> {
+    // Headings inside fenced code blocks don't count
+    let mut in_fence = false;
+    let lines: Vec<(&str, Option<(usize, &str)>)> = markdown.lines()
+        .map(|line| {
+            if line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~") {
+                in_fence = !in_fence;
+                return (line, None);
+            }
+            (line, if in_fence { None } else { markdown_heading(line) })
+        })
+        .collect();
+
+    let Some(top_level) = lines.iter().filter_map(|(_, h)| h.map(|(level, _)| level)).min() else {
+        return vec![markdown_entry(fallback_name, &markdown.lines().collect::<Vec<_>>())];
+    };
+
+    let mut entries = Vec::new();
+    let mut current: Option<&str> = None;
+    let mut body: Vec<&str> = Vec::new();
+    for (line, heading) in lines {
+        match heading {
+            Some((level, title)) if level == top_level => {
+                // Text before the first heading becomes its own entry when there is any
+                match current {
+                    Some(name) => entries.push(markdown_entry(name, &body)),
+                    None if body.iter().any(|l| !l.trim().is_empty()) => entries.push(markdown_entry(fallback_name, &body)),
+                    None => {}
+                }
+                current = Some(if title.is_empty() { fallback_name } else { title });
+                body.clear();
+            }
+            _ => body.push(line),
+        }
+    }
+    if let Some(name) = current {
+        entries.push(markdown_entry(name, &body));
+    }
+    entries
+}
+
+#[tauri::command]
+pub async fn import_knowledge_from_markdown(
+    source_path: String,
+    target_name: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap();
+
+    // Validate target filename to prevent directory traversal
+    let target_name = target_name.trim();
+    if target_name.is_empty() || target_name.contains("..") || target_name.contains("/") || target_name.contains("\\") {
+        return Err("Invalid target filename".to_string());
+    }
+    let target_filename = resource_file_name(target_name, false);
+    let knowledge_dir = config.data_root.join("knowledge");
+    if locate_resource(&knowledge_dir, &target_filename).is_some() {
+        return Err(format!("Knowledge file '{}' already exists", target_filename));
+    }
+
+    let source = PathBuf::from(&source_path);
+    let markdown = fs::read_to_string(&source)
+        .map_err(|e| format!("Failed to read Markdown file: {}", e))?;
+    let source_stem = source.file_stem().and_then(|s| s.to_str()).unwrap_or(target_name);
+
+    let knowledge = serde_json::json!({
+        "meta": {
+            "name": target_filename.trim_end_matches(".json"),
+            "version": "1.0.0",
+            "description": format!("Imported from {}", source.file_name().unwrap_or_default().to_string_lossy()),
+        },
+        "entries": markdown_to_knowledge_entries(&markdown, source_stem),
+    });
+    validate_knowledge_structure(&knowledge)?;
+
+    fs::create_dir_all(&knowledge_dir)
+        .map_err(|e| format!("Failed to create knowledge directory: {}", e))?;
+    let content = serde_json::to_string_pretty(&knowledge)
+        .map_err(|e| format!("Failed to serialize knowledge data: {}", e))?;
+    let target_path = write_resource(&knowledge_dir, &target_filename, &content, config.compress)?;
+
+    println!("Knowledge imported from {:?} to {:?}", source, target_path);
+    Ok(target_path.file_name().unwrap_or_default().to_string_lossy().to_string())
+}
+
+// Starter templates compiled into the app: (kind, id, JSON document)
+const BUILTIN_TEMPLATES: [(&str, &str, &str); 4] = [
+    ("guide", "research-report", include_str!("templates/guides/research-report.json")),
+    ("guide", "bug-triage", include_str!("templates/guides/bug-triage.json")),
+    ("knowledge", "writing-style", include_str!("templates/knowledge/writing-style.json")),
+    ("knowledge", "data-analysis", include_str!("templates/knowledge/data-analysis.json")),
+];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateInfo {
+    pub id: String,
+    pub kind: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub domain: Option<String>,
+    pub entry_count: usize,
+}
+
+fn parse_template(id: &str, content: &str) -> Result<serde_json::Value, String> {
+    serde_json::from_str(content)
+        .map_err(|e| format!("Built-in template '{}' is invalid: {}", id, e))
+}
+
+#[tauri::command]
+pub async fn list_templates(kind: String) -> Result<Vec<TemplateInfo>, String> {
+    if kind != "guide" && kind != "knowledge" {
+        return Err(format!("Unknown template kind '{}'. Must be 'guide' or 'knowledge'", kind));
+    }
+
+    BUILTIN_TEMPLATES.iter()
+        .filter(|(template_kind, _, _)| *template_kind == kind)
+        .map(|(template_kind, id, content)| {
+            let template = parse_template(id, content)?;
+            let meta_field = |field: &str| template["meta"][field].as_str().map(String::from);
+            Ok(TemplateInfo {
+                id: id.to_string(),
+                kind: template_kind.to_string(),
+                name: meta_field("name").unwrap_or_else(|| id.to_string()),
+                description: meta_field("description"),
+                domain: meta_field("domain"),
+                entry_count: template["entries"].as_array().map_or(0, |a| a.len()),
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn install_template(
+    kind: String,
+    template_id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap();
+
+    let (_, id, content) = BUILTIN_TEMPLATES.iter()
+        .find(|(template_kind, id, _)| *template_kind == kind && *id == template_id)
+        .ok_or_else(|| format!("Template '{}' of kind '{}' not found", template_id, kind))?;
+    let template = parse_template(id, content)?;
+
+    let dir = resource_kind_dir(&kind)?;
+    if kind == "guide" {
+        validate_guide_structure(&template)?;
+    } else {
+        validate_knowledge_structure(&template)?;
+    }
+
+    let target_dir = config.data_root.join(dir);
+    if locate_resource(&target_dir, id).is_some() {
+        return Err(format!("File '{}' already exists in {}", resource_file_name(id, false), dir));
+    }
+    fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create {} directory: {}", dir, e))?;
+
+    let content = serde_json::to_string_pretty(&template)
+        .map_err(|e| format!("Failed to serialize template: {}", e))?;
+    let path = write_resource(&target_dir, id, &content, config.compress)?;
+
+    println!("Template {} installed to {:?}", id, path);
+    Ok(path.file_name().unwrap_or_default().to_string_lossy().to_string())
+}
+
+// Load a guide/knowledge document by name
+fn load_resource_document(data_root: &Path, dir: &str, filename: &str) -> Result<serde_json::Value, String> {
+    let path = existing_resource_path(data_root, dir, filename)?;
+    let content = read_resource_text(&path)
+        .map_err(|e| format!("Failed to read '{}': {}", filename, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse '{}': {}", filename, e))
+}
+
+// Title plus optional description shared by both Markdown exports
+fn markdown_document_header(document: &serde_json::Value, filename: &str) -> String {
+    let meta = &document["meta"];
+    let mut markdown = format!("# {}\n\n", meta["name"].as_str().unwrap_or(filename));
+    if let Some(description) = meta["description"].as_str().filter(|d| !d.trim().is_empty()) {
+        markdown.push_str(&format!("{}\n\n", description.trim()));
+    }
+    markdown
+}
+
+// Render each entry as a heading followed by `body(entry)`
+fn render_markdown_entries(mut markdown: String, document: &serde_json::Value, body: impl Fn(&serde_json::Value) -> String) -> String {
+    for entry in document["entries"].as_array().into_iter().flatten() {
+        markdown.push_str(&format!("## {}\n\n", entry["name"].as_str().unwrap_or_default()));
+        if let Some(description) = entry["description"].as_str().filter(|d| !d.trim().is_empty()) {
+            markdown.push_str(&format!("{}\n\n", description.trim()));
+        }
+        let body = body(entry);
+        if !body.trim().is_empty() {
+            markdown.push_str(body.trim_end());
+            markdown.push_str("\n\n");
+        }
+    }
+    format!("{}\n", markdown.trim_end())
+}
+
+fn write_markdown_export(dest_path: &str, markdown: &str, overwrite: bool) -> Result<String, String> {
+    let dest = PathBuf::from(dest_path);
+    if dest.exists() && !overwrite {
+        return Err(format!("File {:?} already exists", dest));
+    }
+    write_atomic(&dest, markdown)?;
+    Ok(dest.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn export_knowledge_to_markdown(
+    filename: String,
+    dest_path: String,
+    overwrite: bool,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let knowledge = load_resource_document(&data_root, "knowledge", &filename)?;
+
+    let markdown = render_markdown_entries(markdown_document_header(&knowledge, &filename), &knowledge, |entry| {
+        entry["content"].as_str().unwrap_or_default().to_string()
+    });
+
+    let written = write_markdown_export(&dest_path, &markdown, overwrite)?;
+    println!("Knowledge exported to Markdown: {} -> {}", filename, written);
+    Ok(written)
+}
+
+#[tauri::command]
+pub async fn export_guide_to_markdown(
+    filename: String,
+    dest_path: String,
+    overwrite: bool,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let guide = load_resource_document(&data_root, "guides", &filename)?;
+
+    let markdown = render_markdown_entries(markdown_document_header(&guide, &filename), &guide, |entry| {
+        guide_plan_steps(entry).into_iter()
+            .enumerate()
+            .map(|(i, step)| format!("{}. {}\n", i + 1, step))
+            .collect()
+    });
+
+    let written = write_markdown_export(&dest_path, &markdown, overwrite)?;
+    println!("Guide exported to Markdown: {} -> {}", filename, written);
+    Ok(written)
+}
+
+// One plan step that differs between the two versions; None where a version has no step at that position
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PlanStepDiff {
+    pub index: usize,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuideEntryChange {
+    pub name: String,
+    pub description_before: String,
+    pub description_after: String,
+    pub plan_before: Vec<String>,
+    pub plan_after: Vec<String>,
+    pub step_changes: Vec<PlanStepDiff>,
+}
+
+// Entries are matched by name; `added` are only in b, `removed` only in a
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuideDiff {
+    pub added: Vec<serde_json::Value>,
+    pub removed: Vec<serde_json::Value>,
+    pub changed: Vec<GuideEntryChange>,
+    pub unchanged: usize,
+}
+
+// A plan step as one line of text; linked steps show their action, e.g. "Run tests (action: run-tests)"
+fn plan_step_label(step: &serde_json::Value) -> Option<String> {
+    match step {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Object(_) => {
+            let text = step["text"].as_str()?;
+            Some(match step["action"].as_str() {
+                Some(action) => format!("{} (action: {})", text, action),
+                None => text.to_string(),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn guide_plan_steps(entry: &serde_json::Value) -> Vec<String> {
+    entry["plan"].as_array().into_iter().flatten()
+        .filter_map(plan_step_label)
+        .collect()
+}
+
+// Actions referenced by plan steps, each with the names of the entries that use it
+fn guide_action_references(guide: &serde_json::Value) -> Vec<(String, Vec<String>)> {
+    let mut references: Vec<(String, Vec<String>)> = Vec::new();
+    for entry in guide["entries"].as_array().into_iter().flatten() {
+        let entry_name = entry["name"].as_str().unwrap_or_default().to_string();
+        for action in entry["plan"].as_array().into_iter().flatten().filter_map(|step| step["action"].as_str()) {
+            match references.iter_mut().find(|(name, _)| name == action) {
+                Some((_, entries)) if entries.contains(&entry_name) => {}
+                Some((_, entries)) => entries.push(entry_name.clone()),
+                None => references.push((action.to_string(), vec![entry_name.clone()])),
+            }
+        }
+    }
+    references
+}
+
+// Plan steps may only link to actions that are installed
+fn check_guide_action_references(data_root: &Path, guide: &serde_json::Value) -> Result<(), String> {
+    let actions = available_resource_names(&data_root.join("actions"), Some("meta.json"));
+    let missing: Vec<String> = guide_action_references(guide).into_iter()
+        .map(|(action, _)| action)
+        .filter(|action| !actions.contains(action))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Plan steps reference unknown actions: {}", missing.join(", ")));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GuideActionReference {
+    pub action: String,
+    // Names of the guide entries whose plan uses the action
+    pub entries: Vec<String>,
+    pub exists: bool,
+}
+
+#[tauri::command]
+pub async fn list_guide_action_references(
+    filename: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<GuideActionReference>, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let guide = load_resource_document(&data_root, "guides", &filename)?;
+    let actions = available_resource_names(&data_root.join("actions"), Some("meta.json"));
+
+    Ok(guide_action_references(&guide).into_iter()
+        .map(|(action, entries)| GuideActionReference {
+            exists: actions.contains(&action),
+            action,
+            entries,
+        })
+        .collect())
+}
+
+// Compare plans position by position so the UI can line the steps up side by side
+fn diff_plan_steps(before: &[String], after: &[String]) -> Vec<PlanStepDiff> {
+    (0..before.len().max(after.len()))
+        .filter_map(|index| {
+            let (a, b) = (before.get(index), after.get(index));
+            (a != b).then(|| PlanStepDiff { index, before: a.cloned(), after: b.cloned() })
+        })
+        .collect()
+}
+
+fn diff_guide_entries(a: &serde_json::Value, b: &serde_json::Value) -> GuideDiff {
+    let entries = |guide: &serde_json::Value| guide["entries"].as_array().cloned().unwrap_or_default();
+    let (entries_a, entries_b) = (entries(a), entries(b));
+    let find = |entries: &[serde_json::Value], key: &Option<String>| {
+        entries.iter().find(|entry| key.is_some() && entry_name_key(entry) == *key).cloned()
+    };
+
+    let mut diff = GuideDiff { added: vec![], removed: vec![], changed: vec![], unchanged: 0 };
+    for entry_a in &entries_a {
+        let Some(entry_b) = find(&entries_b, &entry_name_key(entry_a)) else {
+            diff.removed.push(entry_a.clone());
+            continue;
+        };
+
+        let description = |entry: &serde_json::Value| entry["description"].as_str().unwrap_or_default().to_string();
+        let (plan_before, plan_after) = (guide_plan_steps(entry_a), guide_plan_steps(&entry_b));
+        let step_changes = diff_plan_steps(&plan_before, &plan_after);
+        if step_changes.is_empty() && description(entry_a) == description(&entry_b) {
+            diff.unchanged += 1;
+            continue;
+        }
+        diff.changed.push(GuideEntryChange {
+            name: entry_a["name"].as_str().unwrap_or_default().to_string(),
+            description_before: description(entry_a),
+            description_after: description(&entry_b),
+            plan_before,
+            plan_after,
+            step_changes,
+        });
+    }
+    diff.added = entries_b.into_iter()
+        .filter(|entry| find(&entries_a, &entry_name_key(entry)).is_none())
+        .collect();
+    diff
+}
+
+#[tauri::command]
+pub async fn diff_guides(
+    filename_a: String,
+    filename_b: String,
+    state: State<'_, AppState>,
+) -> Result<GuideDiff, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let guide_a = load_resource_document(&data_root, "guides", &filename_a)?;
+    let guide_b = load_resource_document(&data_root, "guides", &filename_b)?;
+
+    Ok(diff_guide_entries(&guide_a, &guide_b))
+}
+
+#[tauri::command]
+pub async fn delete_knowledge(
+    filename: String,
+    permanent: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let knowledge_dir = data_root.join("knowledge");
+
+    // Validate filename to prevent directory traversal
+    if filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    let knowledge_path = locate_resource(&knowledge_dir, &filename)
+        .ok_or_else(|| format!("Knowledge file '{}' not found", filename))?;
+
+    if permanent {
+        fs::remove_file(&knowledge_path)
+            .map_err(|e| format!("Failed to delete knowledge file: {}", e))?;
+    } else {
+        let stored_name = knowledge_path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        move_to_trash(data_root, "knowledge", &stored_name)?;
+    }
+
+    println!("Knowledge deleted: {:?}", knowledge_path);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn add_knowledge_entry(
+    filename: String,
+    entry: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    // Holding the config lock serializes concurrent entry edits
+    let data_root = &state.config.lock().unwrap().data_root;
+    let knowledge_path = existing_resource_path(data_root, "knowledge", &filename)?;
+
+    modify_resource_entries(&knowledge_path, validate_knowledge_structure, |entries| {
+        entries.push(entry);
+        Ok(entries.len() - 1)
+    })
+}
+
+// Clip a single entry into a knowledge file, creating the file if it doesn't exist yet
+#[tauri::command]
+pub async fn append_knowledge_entry(
+    filename: String,
+    entry: serde_json::Value,
+    allow_duplicate: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap();
+
+    // Validate filename to prevent directory traversal
+    let filename = filename.trim();
+    if filename.is_empty() || filename.contains("..") || filename.contains("/") || filename.contains("\\") {
+        return Err("Invalid filename".to_string());
+    }
+
+    let knowledge_dir = config.data_root.join("knowledge");
+    if let Some(knowledge_path) = locate_resource(&knowledge_dir, filename) {
+        let allow_duplicate = allow_duplicate.unwrap_or(false);
+        return modify_resource_entries(&knowledge_path, validate_knowledge_structure, |entries| {
+            if let Some(key) = entry_name_key(&entry).filter(|_| !allow_duplicate) {
+                if entries.iter().any(|existing| entry_name_key(existing).as_ref() == Some(&key)) {
+                    return Err(format!(
+                        "An entry named '{}' already exists in {}",
+                        entry["name"].as_str().unwrap_or_default(),
+                        filename
+                    ));
+                }
+            }
+            entries.push(entry);
+            Ok(entries.len() - 1)
+        });
+    }
+
+    let target_filename = resource_file_name(filename, false);
+    let knowledge = serde_json::json!({
+        "meta": {
+            "name": target_filename.trim_end_matches(".json"),
+            "version": "1.0.0",
+        },
+        "entries": [entry],
+    });
+    validate_knowledge_structure(&knowledge)?;
+
+    fs::create_dir_all(&knowledge_dir)
+        .map_err(|e| format!("Failed to create knowledge directory: {}", e))?;
+    let content = serde_json::to_string_pretty(&knowledge)
+        .map_err(|e| format!("Failed to serialize knowledge data: {}", e))?;
+    let knowledge_path = write_resource(&knowledge_dir, &target_filename, &content, config.compress)?;
+
+    println!("Knowledge file created for appended entry: {:?}", knowledge_path);
+    Ok(0)
+}
+
+#[tauri::command]
+pub async fn update_knowledge_entry(
+    filename: String,
+    entry_index: usize,
+    entry: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let knowledge_path = existing_resource_path(data_root, "knowledge", &filename)?;
+
+    modify_resource_entries(&knowledge_path, validate_knowledge_structure, |entries| {
+        check_entry_index(entries, entry_index)?;
+        entries[entry_index] = entry;
+        Ok(())
+    })
+}
+
+#[tauri::command]
+pub async fn delete_knowledge_entry(
+    filename: String,
+    entry_index: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let knowledge_path = existing_resource_path(data_root, "knowledge", &filename)?;
+
+    modify_resource_entries(&knowledge_path, validate_knowledge_structure, |entries| {
+        check_entry_index(entries, entry_index)?;
+
+        // A knowledge file must keep at least one entry
+        if entries.len() == 1 {
+            return Err(format!(
+                "Cannot delete the last entry of '{}'; delete the whole knowledge file instead",
+                filename
+            ));
+        }
+
+        entries.remove(entry_index);
+        Ok(())
+    })
+}
+
+// Parse a guide/knowledge file, returning the raw bytes too so a failed multi-file write can restore them
+fn read_resource_document(path: &Path) -> Result<(Vec<u8>, serde_json::Value), String> {
+    let raw = fs::read(path)
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let content = decode_resource(path, raw.clone())
+        .map_err(|e| format!("Failed to read {:?}: {}", path, e))?;
+    let document = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {:?}: {}", path, e))?;
+    Ok((raw, document))
+}
+
+fn encode_resource_document(path: &Path, document: &serde_json::Value) -> Result<Vec<u8>, String> {
+    let content = serde_json::to_string_pretty(document)
+        .map_err(|e| format!("Failed to serialize {:?}: {}", path, e))?;
+    encode_resource(path, &content)
+        .map_err(|e| format!("Failed to compress {:?}: {}", path, e))
+}
+
+#[tauri::command]
+pub async fn move_knowledge_entry(
+    source_filename: String,
+    entry_index: usize,
+    target_filename: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    // Holding the config lock serializes concurrent entry edits
+    let data_root = &state.config.lock().unwrap().data_root;
+    let source_path = existing_resource_path(data_root, "knowledge", &source_filename)?;
+    let target_path = existing_resource_path(data_root, "knowledge", &target_filename)?;
+    if source_path == target_path {
+        return Err("Source and target knowledge files are the same".to_string());
+    }
+
+    let (source_raw, mut source) = read_resource_document(&source_path)?;
+    let (_, mut target) = read_resource_document(&target_path)?;
+
+    let source_entries = source.get_mut("entries")
+        .and_then(|v| v.as_array_mut())
+        .ok_or_else(|| format!("Knowledge file '{}' has no 'entries' array", source_filename))?;
+    check_entry_index(source_entries, entry_index)?;
+    // A knowledge file must keep at least one entry
+    if source_entries.len() == 1 {
+        return Err(format!(
+            "Cannot move the last entry of '{}'; a knowledge file needs at least one entry",
+            source_filename
+        ));
+    }
+    let entry = source_entries.remove(entry_index);
+
+    let target_entries = target.get_mut("entries")
+        .and_then(|v| v.as_array_mut())
+        .ok_or_else(|| format!("Knowledge file '{}' has no 'entries' array", target_filename))?;
+    target_entries.push(entry);
+    let new_index = target_entries.len() - 1;
+
+    validate_knowledge_structure(&source)
+        .map_err(|e| format!("'{}' would be invalid: {}", source_filename, e))?;
+    validate_knowledge_structure(&target)
+        .map_err(|e| format!("'{}' would be invalid: {}", target_filename, e))?;
+
+    let source_bytes = encode_resource_document(&source_path, &source)?;
+    let target_bytes = encode_resource_document(&target_path, &target)?;
+
+    write_atomic(&source_path, source_bytes)?;
+    if let Err(e) = write_atomic(&target_path, target_bytes) {
+        // Put the entry back so it isn't lost from both files
+        if let Err(rollback) = write_atomic(&source_path, source_raw) {
+            return Err(format!("{} (rollback of '{}' also failed: {})", e, source_filename, rollback));
+        }
+        return Err(e);
+    }
+
+    println!(
+        "Moved knowledge entry {} from {} to {} (now entry {})",
+        entry_index, source_filename, target_filename, new_index
+    );
+    Ok(new_index)
+}
+
+#[tauri::command]
+pub async fn dedupe_knowledge(
+    filename: String,
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let knowledge_path = existing_resource_path(data_root, "knowledge", &filename)?;
+
+    let removed = modify_resource_entries(&knowledge_path, validate_knowledge_structure, |entries| {
+        Ok(dedupe_entries_by_name(entries))
+    })?;
+
+    println!("Removed {} duplicate entries from knowledge {}", removed, filename);
+    Ok(removed)
+}
+
+#[tauri::command]
+pub async fn merge_knowledge(
+    filenames: Vec<String>,
+    target_name: String,
+    delete_sources: bool,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    ensure_writable(&state)?;
+    let config = state.config.lock().unwrap();
+    let data_root = &config.data_root;
+
+    // Validate target filename to prevent directory traversal
+    let target_name = target_name.trim();
+    if target_name.is_empty() || target_name.contains("..") || target_name.contains("/") || target_name.contains("\\") {
+        return Err("Invalid target filename".to_string());
+    }
+    if filenames.is_empty() {
+        return Err("No knowledge files to merge".to_string());
+    }
+
+    // Compare names without extension so "a", "a.json" and "a.json.gz" all match
+    let target_filename = resource_file_name(target_name, false);
+    if filenames.iter().any(|f| resource_file_name(f, false) == target_filename) {
+        return Err(format!("Cannot merge '{}' into itself", target_filename));
+    }
+    let knowledge_dir = data_root.join("knowledge");
+    if locate_resource(&knowledge_dir, &target_filename).is_some() {
+        return Err(format!("Knowledge file '{}' already exists", target_filename));
+    }
+
+    let mut entries = Vec::new();
+    let mut domains = HashSet::new();
+    let mut source_paths = Vec::new();
+    for filename in &filenames {
+        let path = existing_resource_path(data_root, "knowledge", filename)?;
+        if source_paths.contains(&path) {
+            continue;
+        }
+
+        let content = read_resource_text(&path)
+            .map_err(|e| format!("Failed to read knowledge file '{}': {}", filename, e))?;
+        let knowledge: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse knowledge file '{}': {}", filename, e))?;
+
+        let source_entries = knowledge.get("entries")
+            .and_then(|v| v.as_array())
+            .ok_or_else(|| format!("Knowledge file '{}' has no 'entries' array", filename))?;
+        entries.extend(source_entries.iter().cloned());
+        domains.insert(knowledge["meta"]["domain"].as_str().map(String::from));
+        source_paths.push(path);
+    }
+    let removed = dedupe_entries_by_name(&mut entries);
+
+    let mut meta = serde_json::json!({
+        "name": target_filename.trim_end_matches(".json"),
+        "version": "1.0.0",
+        "description": format!("Merged from {}", filenames.join(", ")),
+    });
+    // Keep the domain only when every source agrees on it
+    if let (1, Some(Some(domain))) = (domains.len(), domains.iter().next()) {
+        meta["domain"] = serde_json::json!(domain);
+    }
+
+    let merged = serde_json::json!({
+        "meta": meta,
+        "entries": entries,
+    });
+    validate_knowledge_structure(&merged)?;
+
+    let content = serde_json::to_string_pretty(&merged)
+        .map_err(|e| format!("Failed to serialize knowledge data: {}", e))?;
+    let target_path = write_resource(&knowledge_dir, &target_filename, &content, config.compress)?;
+
+    // Sources go to the trash so a mistaken merge can be undone
+    if delete_sources {
+        for path in &source_paths {
+            let name = path.file_name().and_then(|n| n.to_str()).ok_or("Invalid knowledge path")?;
+            move_to_trash(data_root, "knowledge", name)?;
+        }
+    }
+
+    println!(
+        "Merged {} knowledge files into {:?} ({} duplicate entries dropped)",
+        source_paths.len(), target_path, removed
+    );
+    Ok(target_path.file_name().unwrap_or_default().to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn create_knowledge_directory(
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let knowledge_dir = data_root.join("knowledge");
+
+    fs::create_dir_all(&knowledge_dir)
+        .map_err(|e| format!("Failed to create knowledge directory: {}", e))?;
+
+    // Create a default knowledge entry if directory is empty
+    let default_knowledge = serde_json::json!({
+        "meta": {
+            "name": "Software Development Knowledge",
+            "version": "1.0.0",
+            "domain": "development"
+        },
+        "entries": [
+            {
+                "name": "React Best Practices",
+                "description": "Essential practices for React development",
+                "content": "Use functional components with hooks, follow component composition patterns, implement proper error handling, use React.memo for performance optimization, follow proper state management patterns."
+            },
+            {
+                "name": "TypeScript Guidelines",
+                "description": "TypeScript development standards and conventions",
+                "content": "Use strict type checking, prefer interfaces over types for object shapes, use generics for reusable code, avoid 'any' type, implement proper error boundaries, use type guards for runtime type checking."
+            },
+            {
+                "name": "Git Workflow",
+                "description": "Version control best practices and workflows",
+                "content": "Use feature branches for new development, write clear commit messages, use pull requests for code review, maintain clean commit history, resolve conflicts properly, use semantic versioning."
+            }
+        ]
+    });
+
+    let default_path = knowledge_dir.join("software-development.json");
+
+    if !default_path.exists() {
+        let content = serde_json::to_string_pretty(&default_knowledge)
+            .map_err(|e| format!("Failed to serialize default knowledge: {}", e))?;
+
+        fs::write(&default_path, content)
+            .map_err(|e| format!("Failed to write default knowledge: {}", e))?;
+
+        println!("Default knowledge created at: {:?}", default_path);
+    }
+
+    Ok(())
+}
+
+// Helper function to validate knowledge structure
+fn validate_knowledge_structure(knowledge: &serde_json::Value) -> Result<(), String> {
+    validate_knowledge_structure_with(knowledge, StructureOptions::default())
+}
+
+fn validate_knowledge_structure_with(knowledge: &serde_json::Value, options: StructureOptions) -> Result<(), String> {
+    validate_against_schema(compiled_validator(&KNOWLEDGE_VALIDATOR, knowledge_schema), knowledge)?;
+    check_structure_options(knowledge, options)
+}
+
+// Action Management Commands
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionStatus {
+    pub status: String, // "healthy", "error", "disabled"
+    pub last_error: Option<ActionError>,
+    pub error_count: u32,
+    pub last_success: Option<String>, // ISO8601 timestamp
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionError {
+    pub message: String,
+    pub timestamp: String, // ISO8601
+    pub execution_id: String,
+}
+
+// One lock per action so concurrent status.json read-modify-writes can't lose updates
+#[derive(Debug, Default)]
+pub struct ActionLocks(Mutex<HashMap<String, Arc<Mutex<()>>>>);
+
+impl ActionLocks {
+    fn get(&self, action_name: &str) -> Arc<Mutex<()>> {
+        self.0.lock().unwrap()
+            .entry(action_name.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+#[tauri::command]
+pub async fn import_action_directory(
+    source_path: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    ensure_writable(&state)?;
+    let actions_dir = state.config.lock().unwrap().data_root.join("actions");
+
+    // Ensure actions directory exists
+    fs::create_dir_all(&actions_dir)
+        .map_err(|e| format!("Failed to create actions directory: {}", e))?;
+
+    // Validate the source directory
+    let validation_result = validate_action_directory_internal(&source_path, false).await?;
+    if !validation_result.is_valid {
+        return Err(format!("Invalid action directory: {}", validation_result.error.unwrap_or_else(|| "Unknown error".to_string())));
+    }
+
+    // Get action name from meta.json
+    let meta_path = PathBuf::from(&source_path).join("meta.json");
+    let meta_content = read_resource_text(&meta_path)
+        .map_err(|e| format!("Failed to read meta.json: {}", e))?;
+    let meta: serde_json::Value = serde_json::from_str(&meta_content)
+        .map_err(|e| format!("Failed to parse meta.json: {}", e))?;
+
+    let action_name = meta.get("name")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing action name in meta.json")?;
+
+    // Create target directory
+    let target_dir = actions_dir.join(action_name);
+    if target_dir.exists() {
+        return Err(format!("Action '{}' already exists", action_name));
+    }
+
+    // Copy directory recursively
+    copy_directory(&source_path, target_dir.to_str().ok_or("Invalid target path")?)?;
+
+    // Initialize action status as healthy
+    let status_data = ActionStatus {
+        status: "healthy".to_string(),
+        last_error: None,
+        error_count: 0,
+        last_success: Some(chrono::Utc::now().to_rfc3339()),
+    };
+
+    write_action_status(&target_dir, &status_data)?;
+
+    println!("Action imported: {:?} -> {:?}", source_path, target_dir);
+    Ok(action_name.to_string())
+}
+
+// Action library archives: every action directory under actions/ plus a manifest.json
+// listing them. status.json is left out, as in thread bundles
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionArchiveManifest {
+    pub format_version: u32,
+    pub exported_at: String,
+    pub app_version: String,
+    pub actions: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionImportResult {
+    pub name: String,
+    // "imported", "overwritten", "skipped" or "invalid"
+    pub status: String,
+    pub error: Option<String>,
+}
+
+fn write_action_archive(actions_dir: &Path, dest: &Path) -> Result<Vec<String>, String> {
+    let mut actions: Vec<String> = fs::read_dir(actions_dir)
+        .map_err(|e| format!("Failed to read actions directory: {}", e))?
+        .flatten()
+        .filter(|entry| entry.path().join("meta.json").is_file())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    actions.sort();
+
+    let file = fs::File::create(dest)
+        .map_err(|e| format!("Failed to create archive file: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    for action in &actions {
+        add_bundle_dir(&mut zip, &actions_dir.join(action), &format!("actions/{}", action), true)?;
+    }
+
+    let manifest = ActionArchiveManifest {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        actions: actions.clone(),
+    };
+    let content = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize archive manifest: {}", e))?;
+    add_bundle_file(&mut zip, "manifest.json", &content)?;
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+    Ok(actions)
+}
+
+#[tauri::command]
+pub async fn export_all_actions(
+    dest_path: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let actions_dir = state.config.lock().unwrap().data_root.join("actions");
+    let dest = PathBuf::from(&dest_path);
+
+    // Don't leave a half-written archive behind
+    let actions = write_action_archive(&actions_dir, &dest).inspect_err(|_| {
+        let _ = fs::remove_file(&dest);
+    })?;
+
+    println!("Exported {} actions to {:?}", actions.len(), dest);
+    Ok(actions)
+}
+
+// Unpack every actions/{name}/... entry of an archive below `staging`
+fn extract_action_archive(archive: &mut zip::ZipArchive<fs::File>, staging: &Path) -> Result<(), String> {
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        // enclosed_name rejects absolute paths and ".." components
+        let Some(relative) = entry.enclosed_name().map(|p| p.to_path_buf()) else {
+            continue;
+        };
+        let Ok(inside) = relative.strip_prefix("actions") else {
+            continue;
+        };
+        if inside.components().count() < 2 {
+            continue;
+        }
+
+        let target = staging.join(inside);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create directory {:?}: {}", parent, e))?;
+        }
+        let mut content = Vec::new();
+        std::io::Read::read_to_end(&mut entry, &mut content)
+            .map_err(|e| format!("Failed to read {:?} from archive: {}", relative, e))?;
+        fs::write(&target, content)
+            .map_err(|e| format!("Failed to write {:?}: {}", target, e))?;
+    }
+    Ok(())
+}
+
+// Move one staged action into the library according to `strategy`
+async fn install_staged_action(
+    data_root: &Path,
+    staged: &Path,
+    name: &str,
+    strategy: ImportStrategy,
+) -> Result<&'static str, String> {
+    let validation = validate_action_directory_internal(&staged.to_string_lossy(), false).await?;
+    if !validation.is_valid {
+        return Err(validation.error.unwrap_or_else(|| "Unknown error".to_string()));
+    }
+
+    let target_dir = data_root.join("actions").join(name);
+    let status = if target_dir.exists() {
+        if strategy == ImportStrategy::Skip {
+            return Ok("skipped");
+        }
+        // The replaced action goes to the trash rather than being lost
+        move_to_trash(data_root, "actions", name)?;
+        "overwritten"
+    } else {
+        "imported"
+    };
+
+    fs::rename(staged, &target_dir)
+        .map_err(|e| format!("Failed to move action into place: {}", e))?;
+    write_action_status(&target_dir, &ActionStatus {
+        status: "healthy".to_string(),
+        last_error: None,
+        error_count: 0,
+        last_success: Some(chrono::Utc::now().to_rfc3339()),
+    })?;
+    Ok(status)
+}
+
+// Action archives and thread bundles are unpacked into "{prefix}{uuid}" under data_root and the
+// directory is removed afterwards
+const ACTION_IMPORT_STAGING_PREFIX: &str = ".action-import-";
+
+#[tauri::command]
+pub async fn import_all_actions(
+    archive_path: String,
+    strategy: ImportStrategy,
+    state: State<'_, AppState>,
+) -> Result<Vec<ActionImportResult>, String> {
+    ensure_writable(&state)?;
+    if strategy == ImportStrategy::Rename {
+        return Err("Action archives can only be imported with the skip or overwrite strategy".to_string());
+    }
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    fs::create_dir_all(data_root.join("actions"))
+        .map_err(|e| format!("Failed to create actions directory: {}", e))?;
+
+    let mut archive = open_bundle(Path::new(&archive_path))?;
+    let manifest: ActionArchiveManifest = read_bundle_json(&mut archive, "manifest.json")?;
+    if manifest.format_version > BUNDLE_FORMAT_VERSION {
+        return Err(format!("Archive format {} is newer than supported format {}", manifest.format_version, BUNDLE_FORMAT_VERSION));
+    }
+
+    // Unpack next to the library so moving an action into place is a rename
+    let staging = data_root.join(format!("{}{}", ACTION_IMPORT_STAGING_PREFIX, uuid::Uuid::new_v4()));
+    let extracted = extract_action_archive(&mut archive, &staging);
+    let mut staged: Vec<PathBuf> = match &extracted {
+        Ok(()) => fs::read_dir(&staging).into_iter().flatten().flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .collect(),
+        Err(_) => vec![],
+    };
+    staged.sort();
+
+    let mut results = Vec::new();
+    for path in &staged {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        results.push(match install_staged_action(&data_root, path, &name, strategy).await {
+            Ok(status) => ActionImportResult { name, status: status.to_string(), error: None },
+            Err(error) => ActionImportResult { name, status: "invalid".to_string(), error: Some(error) },
+        });
+    }
+
+    if let Err(e) = fs::remove_dir_all(&staging) {
+        println!("Failed to remove import staging directory {:?}: {}", staging, e);
+    }
+    extracted?;
+
+    println!("Imported action archive {:?}: {} actions", archive_path, results.len());
+    Ok(results)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionPreview {
+    pub name: String,
+    pub description: String,
+    pub arguments: Vec<ActionArgument>,
+    pub timeout_sec: u64,
+    // Paths relative to the source directory, as import_action_directory would copy them
+    pub files: Vec<String>,
+    pub already_exists: bool,
+}
+
+// Every file below `root` as a sorted list of "/"-separated relative paths
+fn list_relative_files(root: &Path) -> Result<Vec<String>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read directory {:?}: {}", dir, e))? {
+            let path = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                files.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+#[tauri::command]
+pub async fn preview_action_import(
+    source_path: String,
+    state: State<'_, AppState>,
+) -> Result<ActionPreview, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    // Same checks as import_action_directory, but nothing is written
+    let validation_result = validate_action_directory_internal(&source_path, false).await?;
+    if !validation_result.is_valid {
+        return Err(format!("Invalid action directory: {}", validation_result.error.unwrap_or_else(|| "Unknown error".to_string())));
+    }
+
+    let source_dir = PathBuf::from(&source_path);
+    let meta = load_action_meta(&source_dir)?;
+    let name = meta["name"].as_str().unwrap_or_default().to_string();
+    let arguments: Vec<ActionArgument> = serde_json::from_value(meta["arguments"].clone())
+        .map_err(|e| format!("Failed to parse action arguments: {}", e))?;
+
+    Ok(ActionPreview {
+        already_exists: data_root.join("actions").join(&name).exists(),
+        name,
+        description: meta["description"].as_str().unwrap_or_default().to_string(),
+        arguments,
+        timeout_sec: meta["timeout_sec"].as_u64().unwrap_or_default(),
+        files: list_relative_files(&source_dir)?,
+    })
+}
+
+#[tauri::command]
+pub async fn validate_action_directory(
+    path: String,
+    probe_exports: Option<bool>,
+) -> Result<serde_json::Value, String> {
+    let result = validate_action_directory_internal(&path, probe_exports.unwrap_or(false)).await?;
+
+    let json_result = serde_json::json!({
+        "is_valid": result.is_valid,
+        "error": result.error,
+        "missing_files": result.missing_files
+    });
+
+    Ok(json_result)
+}
+
+struct ValidationResult {
+    is_valid: bool,
+    error: Option<String>,
+    missing_files: Vec<String>,
+}
+
+// `probe_exports` also loads perform.js in node to check its export. That executes the
+// script, so it's only done when the caller explicitly asks for it
+async fn validate_action_directory_internal(path: &str, probe_exports: bool) -> Result<ValidationResult, String> {
+    let path_buf = PathBuf::from(path);
+
+    if !path_buf.exists() || !path_buf.is_dir() {
+        return Ok(ValidationResult {
+            is_valid: false,
+            error: Some("Directory does not exist".to_string()),
+            missing_files: vec![],
+        });
+    }
+
+    let mut missing_files = Vec::new();
+
+    // Check for required files
+    let required_files = ["meta.json", "perform.js"];
+    for file in &required_files {
+        if !path_buf.join(file).exists() {
+            missing_files.push(file.to_string());
+        }
+    }
+
+    if !missing_files.is_empty() {
+        return Ok(ValidationResult {
+            is_valid: false,
+            error: Some(format!("Missing required files: {}", missing_files.join(", "))),
+            missing_files,
+        });
+    }
+
+    // Validate meta.json structure
+    let meta_path = path_buf.join("meta.json");
+    let meta_content = read_resource_text(&meta_path)
+        .map_err(|e| format!("Failed to read meta.json: {}", e))?;
+
+    let meta: serde_json::Value = serde_json::from_str(&meta_content)
+        .map_err(|e| format!("Failed to parse meta.json: {}", e))?;
+
+    // Validate required meta fields
+    let validation_error = validate_action_meta_structure(&meta);
+    if let Some(error) = validation_error {
+        return Ok(ValidationResult {
+            is_valid: false,
+            error: Some(error),
+            missing_files: vec![],
+        });
+    }
+
+    // Make sure perform.js parses (and, if asked, exports what the runner expects)
+    if let Some(error) = check_perform_script(&path_buf, probe_exports).await {
+        return Ok(ValidationResult {
+            is_valid: false,
+            error: Some(error),
+            missing_files: vec![],
+        });
+    }
+
+    Ok(ValidationResult {
+        is_valid: true,
+        error: None,
+        missing_files: vec![],
+    })
+}
+
+const NODE_CHECK_TIMEOUT_SECS: u64 = 10;
+
+// Loads perform.js the way the action runner does and reports how the export looks
+const PERFORM_EXPORT_PROBE: &str = r#"
+const m = require(require('path').resolve('perform.js'));
+if (m && typeof m.Perform === 'function') process.exit(0);
+if (typeof m === 'function' || (m && typeof m.perform === 'function')) process.exit(0);
+process.exit(3);
+"#;
+
+// Run node with the given arguments, killing it after the timeout. Returns None when node is not installed.
+// Both pipes are drained while node runs, so a chatty script can't stall on a full pipe
+async fn run_node(args: &[&str], cwd: &Path, timeout: Duration) -> Result<Option<std::process::Output>, String> {
+    let output = TokioCommand::new("node")
+        .args(args)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output();
+
+    match tokio::time::timeout(timeout, output).await {
+        Err(_) => Err(format!("node did not finish within {} seconds", timeout.as_secs())),
+        Ok(Ok(output)) => Ok(Some(output)),
+        Ok(Err(e)) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Ok(Err(e)) => Err(format!("Failed to run node: {}", e)),
+    }
+}
+
+// Turn `node --check` stderr ("<file>:<line>", source excerpt, "SyntaxError: ...") into one line
+fn describe_syntax_error(stderr: &str) -> String {
+    let line = stderr.lines()
+        .next()
+        .and_then(|first| first.rsplit(':').next())
+        .and_then(|n| n.trim().parse::<usize>().ok());
+    let message = stderr.lines()
+        .map(str::trim)
+        .find(|l| l.contains("Error:") && !l.starts_with("at "))
+        .unwrap_or("invalid JavaScript");
+
+    match line {
+        Some(line) => format!("perform.js has a syntax error at line {}: {}", line, message),
+        None => format!("perform.js has a syntax error: {}", message),
+    }
+}
+
+// Syntax-check perform.js without running it; with `probe_exports`, also load it to confirm it
+// exports a `Perform` class (or a `perform` function).
+// Skipped when node is not installed, since the action could not run here anyway.
+async fn check_perform_script(action_dir: &Path, probe_exports: bool) -> Option<String> {
+    let timeout = Duration::from_secs(NODE_CHECK_TIMEOUT_SECS);
+
+    let output = match run_node(&["--check", "perform.js"], action_dir, timeout).await {
+        Ok(Some(output)) => output,
+        Ok(None) => {
+            println!("node not found, skipping perform.js check for {:?}", action_dir);
+            return None;
+        }
+        Err(e) => return Some(format!("Failed to check perform.js: {}", e)),
+    };
+    if !output.status.success() {
+        return Some(describe_syntax_error(&String::from_utf8_lossy(&output.stderr)));
+    }
+    if !probe_exports {
+        return None;
+    }
+
+    match run_node(&["-e", PERFORM_EXPORT_PROBE], action_dir, timeout).await {
+        Ok(Some(output)) if output.status.success() => None,
+        Ok(Some(output)) if output.status.code() == Some(3) => {
+            Some("perform.js must export a Perform class or a perform function".to_string())
+        }
+        Ok(Some(output)) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let message = stderr.lines()
+                .map(str::trim)
+                .find(|l| l.contains("Error"))
+                .unwrap_or("unknown error");
+            Some(format!("perform.js failed to load: {}", message))
+        }
+        Ok(None) => None,
+        Err(e) => Some(format!("Failed to load perform.js: {}", e)),
+    }
+}
+
+fn validate_action_meta_structure(meta: &serde_json::Value) -> Option<String> {
+    validate_against_schema(compiled_validator(&ACTION_META_VALIDATOR, action_meta_schema), meta)
+        .and_then(|_| validate_timeout_bounds(meta))
+        .and_then(|_| validate_argument_constraints(meta))
+        .err()
+}
+
+// A zero timeout would fail every run instantly; anything past an hour is almost certainly a typo
+const MIN_ACTION_TIMEOUT_SECS: u64 = 1;
+const MAX_ACTION_TIMEOUT_SECS: u64 = 3600;
+
+fn validate_timeout_bounds(meta: &serde_json::Value) -> Result<(), String> {
+    let timeout = meta["timeout_sec"].as_u64().unwrap_or_default();
+    if !(MIN_ACTION_TIMEOUT_SECS..=MAX_ACTION_TIMEOUT_SECS).contains(&timeout) {
+        return Err(format!(
+            "timeout_sec must be between {} and {} seconds, got {}",
+            MIN_ACTION_TIMEOUT_SECS, MAX_ACTION_TIMEOUT_SECS, timeout
+        ));
+    }
+    Ok(())
+}
+
+// Whether a JSON value fits an argument's declared type; unknown types accept anything
+fn value_matches_type(value: &serde_json::Value, arg_type: &str) -> bool {
+    match arg_type {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+// Check that `default` and `enum` values agree with each argument's declared type
+fn validate_argument_constraints(meta: &serde_json::Value) -> Result<(), String> {
+    for arg in meta["arguments"].as_array().into_iter().flatten() {
+        let name = arg["name"].as_str().unwrap_or_default();
+        let arg_type = arg["type"].as_str().unwrap_or_default();
+
+        if let Some(allowed) = arg.get("enum").and_then(|v| v.as_array()) {
+            if let Some(bad) = allowed.iter().find(|v| !value_matches_type(v, arg_type)) {
+                return Err(format!("Argument '{}': enum value {} is not of type {}", name, bad, arg_type));
+            }
+        }
+        if let Some(default) = arg.get("default") {
+            check_argument_value(arg, default)
+                .map_err(|e| format!("Argument '{}' default: {}", name, e))?;
+        }
+    }
+    Ok(())
+}
+
+// Check a single value against an argument's declared type and enum
+fn check_argument_value(arg: &serde_json::Value, value: &serde_json::Value) -> Result<(), String> {
+    let arg_type = arg["type"].as_str().unwrap_or_default();
+    if !value_matches_type(value, arg_type) {
+        return Err(format!("expected {}, got {}", arg_type, value));
+    }
+    if let Some(allowed) = arg.get("enum").and_then(|v| v.as_array()) {
+        if !allowed.contains(value) {
+            let allowed: Vec<String> = allowed.iter().map(|v| v.to_string()).collect();
+            return Err(format!("{} is not one of the allowed values: {}", value, allowed.join(", ")));
+        }
+    }
+    Ok(())
+}
+
+fn write_action_status(action_dir: &Path, status: &ActionStatus) -> Result<(), String> {
+    let status_path = action_dir.join("status.json");
+    let content = serde_json::to_string_pretty(status)
+        .map_err(|e| format!("Failed to serialize action status: {}", e))?;
+
+    // Atomic so a concurrent get_action_status never sees a half-written file
+    write_atomic(&status_path, content)
+        .map_err(|e| format!("Failed to write action status: {}", e))
+}
+
+fn copy_directory(source: &str, target: &str) -> Result<(), String> {
+    let source_path = PathBuf::from(source);
+    let target_path = PathBuf::from(target);
+
+    fs::create_dir_all(&target_path)
+        .map_err(|e| format!("Failed to create target directory: {}", e))?;
+
+    for entry in fs::read_dir(&source_path)
+        .map_err(|e| format!("Failed to read source directory: {}", e))?
+    {
+        let entry = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let source_file = entry.path();
+        let target_file = target_path.join(entry.file_name());
+
+        if source_file.is_dir() {
+            copy_directory(source_file.to_str().unwrap(), target_file.to_str().unwrap())?;
+        } else {
+            fs::copy(&source_file, &target_file)
+                .map_err(|e| format!("Failed to copy file: {}", e))?;
+        }
+    }
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_action(
+    action_name: String,
+    permanent: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let actions_dir = data_root.join("actions");
+    let action_dir = actions_dir.join(&action_name);
+
+    // Validate action name to prevent directory traversal
+    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+
+    if !action_dir.exists() {
+        return Err(format!("Action '{}' not found", action_name));
+    }
+
+    if permanent {
+        // Remove directory recursively
+        fs::remove_dir_all(&action_dir)
+            .map_err(|e| format!("Failed to delete action directory: {}", e))?;
+    } else {
+        move_to_trash(data_root, "actions", &action_name)?;
+    }
+
+    println!("Action deleted: {:?}", action_dir);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn copy_action(
+    source_name: String,
+    new_name: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let actions_dir = data_root.join("actions");
+
+    // Validate both names to prevent directory traversal
+    for name in [&source_name, &new_name] {
+        if name.is_empty() || name.contains("..") || name.contains("/") || name.contains("\\") {
+            return Err(format!("Invalid action name '{}'", name));
+        }
+    }
+
+    let source_dir = actions_dir.join(&source_name);
+    let target_dir = actions_dir.join(&new_name);
+
+    if !source_dir.exists() {
+        return Err(format!("Action '{}' not found", source_name));
+    }
+
+    if target_dir.exists() {
+        return Err(format!("Action '{}' already exists", new_name));
+    }
+
+    copy_directory(
+        source_dir.to_str().ok_or("Invalid source path")?,
+        target_dir.to_str().ok_or("Invalid target path")?,
+    )?;
+
+    // Point the cloned meta at its new name
+    let meta_path = target_dir.join("meta.json");
+    let meta_content = read_resource_text(&meta_path)
+        .map_err(|e| format!("Failed to read meta.json: {}", e))?;
+    let mut meta: serde_json::Value = serde_json::from_str(&meta_content)
+        .map_err(|e| format!("Failed to parse meta.json: {}", e))?;
+    meta["name"] = serde_json::Value::String(new_name.clone());
+
+    let meta_content = serde_json::to_string_pretty(&meta)
+        .map_err(|e| format!("Failed to serialize meta.json: {}", e))?;
+    fs::write(&meta_path, meta_content)
+        .map_err(|e| format!("Failed to write meta.json: {}", e))?;
+
+    // The copy starts with a clean history
+    let status_data = ActionStatus {
+        status: "healthy".to_string(),
+        last_error: None,
+        error_count: 0,
+        last_success: Some(chrono::Utc::now().to_rfc3339()),
+    };
+    write_action_status(&target_dir, &status_data)?;
+
+    println!("Action copied: {:?} -> {:?}", source_dir, target_dir);
+    Ok(new_name)
+}
+
+#[tauri::command]
+pub async fn update_action_status(
+    action_name: String,
+    status: String,
+    error_message: Option<String>,
+    execution_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+
+    // Validate action name
+    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+
+    record_action_status(&state.action_locks, data_root, &action_name, &status, error_message, execution_id)
+}
+
+fn record_action_status(
+    locks: &ActionLocks,
+    data_root: &Path,
+    action_name: &str,
+    status: &str,
+    error_message: Option<String>,
+    execution_id: Option<String>,
+) -> Result<(), String> {
+    let lock = locks.get(action_name);
+    let _guard = lock.lock().unwrap();
+
+    let actions_dir = data_root.join("actions");
+    let action_dir = actions_dir.join(action_name);
+    let status_path = action_dir.join("status.json");
+
+    // Load existing status or create new one
+    let mut current_status: ActionStatus = if status_path.exists() {
+        let content = read_resource_text(&status_path)
+            .map_err(|e| format!("Failed to read action status: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse action status: {}", e))?
+    } else {
+        ActionStatus {
+            status: "healthy".to_string(),
+            last_error: None,
+            error_count: 0,
+            last_success: None,
+        }
+    };
+
+    // Update status
+    current_status.status = status.to_string();
+
+    if status == "error" {
+        current_status.error_count += 1;
+        if let (Some(msg), Some(exec_id)) = (error_message, execution_id) {
+            current_status.last_error = Some(ActionError {
+                message: msg,
+                timestamp: chrono::Utc::now().to_rfc3339(),
+                execution_id: exec_id,
+            });
+        }
+    } else if status == "healthy" {
+        current_status.last_success = Some(chrono::Utc::now().to_rfc3339());
+    }
+
+    // Save updated status
+    write_action_status(&action_dir, &current_status)?;
+
+    println!("Action status updated: {} -> {}", action_name, status);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_action_status(
+    action_name: String,
+    state: State<'_, AppState>,
+) -> Result<ActionStatus, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let actions_dir = data_root.join("actions");
+    let action_dir = actions_dir.join(&action_name);
+    let status_path = action_dir.join("status.json");
+
+    // Validate action name
+    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+
+    if !status_path.exists() {
+        // Return default healthy status if no status file exists
+        return Ok(ActionStatus {
+            status: "healthy".to_string(),
+            last_error: None,
+            error_count: 0,
+            last_success: Some(chrono::Utc::now().to_rfc3339()),
+        });
+    }
+
+    let content = read_resource_text(&status_path)
+        .map_err(|e| format!("Failed to read action status: {}", e))?;
+
+    let status: ActionStatus = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse action status: {}", e))?;
+
+    Ok(status)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActionArgument {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub arg_type: String,
+    pub description: String,
+    pub required: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<serde_json::Value>,
+    #[serde(default, rename = "enum", skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<serde_json::Value>>,
+}
+
+// Read and schema-check an action's meta.json
+fn load_action_meta(action_dir: &Path) -> Result<serde_json::Value, String> {
+    let meta_content = read_resource_text(action_dir.join("meta.json"))
+        .map_err(|e| format!("Failed to read action meta: {}", e))?;
+    let meta: serde_json::Value = serde_json::from_str(&meta_content)
+        .map_err(|e| format!("Failed to parse action meta: {}", e))?;
+
+    if let Some(error) = validate_action_meta_structure(&meta) {
+        return Err(format!("Invalid action meta: {}", error));
+    }
+
+    Ok(meta)
+}
+
+#[tauri::command]
+pub async fn get_action_arguments(
+    action_name: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<ActionArgument>, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+
+    // Validate action name
+    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+
+    let meta = load_action_meta(&data_root.join("actions").join(&action_name))?;
+    serde_json::from_value(meta["arguments"].clone())
+        .map_err(|e| format!("Failed to parse action arguments: {}", e))
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LintWarning {
+    pub kind: String, // "unused_argument" or "undeclared_argument"
+    pub argument: String,
+    pub message: String,
+}
+
+// Objects perform.js conventionally reads its arguments from; run/perform parameter names are added
+const PERFORM_ARGS_NAMES: [&str; 3] = ["args", "kwargs", "params"];
+
+fn is_js_ident_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b == b'$'
+}
+
+fn js_identifier_at(source: &str, start: usize) -> Option<&str> {
+    let bytes = source.as_bytes().get(start..)?;
+    let len = bytes.iter().take_while(|b| is_js_ident_byte(**b)).count();
+    (len > 0 && !bytes[0].is_ascii_digit()).then(|| &source[start..start + len])
+}
+
+fn skip_js_whitespace(source: &str, i: usize) -> usize {
+    i + source.as_bytes()[i..].iter().take_while(|b| b.is_ascii_whitespace()).count()
+}
+
+// Start offsets of `word` where it appears as a whole identifier
+fn js_word_offsets<'a>(source: &'a str, word: &'a str) -> impl Iterator<Item = usize> + 'a {
+    let bytes = source.as_bytes();
+    source.match_indices(word)
+        .map(|(i, _)| i)
+        .filter(move |&i| {
+            let before = i.checked_sub(1).map(|j| bytes[j]);
+            let after = bytes.get(i + word.len()).copied();
+            !before.is_some_and(is_js_ident_byte) && !after.is_some_and(is_js_ident_byte)
+        })
+}
+
+// Offset of the bracket matching the one at `open`, scanning forward or backward
+fn matching_bracket(source: &str, open: usize, forward: bool) -> Option<usize> {
+    let bytes = source.as_bytes();
+    let (inc, dec) = if forward { (b'{', b'}') } else { (b'}', b'{') };
+    let mut depth = 0usize;
+    let mut i = open;
+    loop {
+        if bytes[i] == inc {
+            depth += 1;
+        } else if bytes[i] == dec {
+            depth -= 1;
+            if depth == 0 {
+                return Some(i);
+            }
+        }
+        i = if forward { i + 1 } else { i.checked_sub(1)? };
+        if i >= bytes.len() {
+            return None;
+        }
+    }
+}
+
+// Names bound by a destructuring pattern body like `a, b: renamed, c = 1, ...rest`
+fn destructured_names(pattern: &str, used: &mut BTreeSet<String>, dynamic: &mut bool) {
+    for part in pattern.split(',').map(str::trim) {
+        if part.starts_with("...") {
+            *dynamic = true;
+        } else if let Some(name) = js_identifier_at(part, 0) {
+            used.insert(name.to_string());
+        }
+    }
+}
+
+// Blank out comments and the contents of string literals, keeping quotes, newlines and byte
+// offsets, so text like "args.x" inside them isn't mistaken for code. `${...}` in template
+// literals stays code
+fn mask_js_literals(source: &str) -> String {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Scan { Code, LineComment, BlockComment, Quoted(u8), Template }
+
+    let bytes = source.as_bytes();
+    let mut masked = bytes.to_vec();
+    let mut blank = |range: std::ops::Range<usize>| {
+        for b in &mut masked[range] {
+            if *b != b'\n' {
+                *b = b' ';
+            }
+        }
+    };
+    let mut scan = Scan::Code;
+    // Brace depth at which each open `${` started
+    let mut interpolations: Vec<usize> = Vec::new();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        let next = bytes.get(i + 1).copied();
+        match scan {
+            Scan::Code => match b {
+                b'/' if next == Some(b'/') => {
+                    scan = Scan::LineComment;
+                    blank(i..i + 2);
+                    i += 1;
+                }
+                b'/' if next == Some(b'*') => {
+                    scan = Scan::BlockComment;
+                    blank(i..i + 2);
+                    i += 1;
+                }
+                b'"' | b'\'' => scan = Scan::Quoted(b),
+                b'`' => scan = Scan::Template,
+                b'{' => depth += 1,
+                b'}' if interpolations.last() == Some(&depth) => {
+                    interpolations.pop();
+                    depth -= 1;
+                    scan = Scan::Template;
+                }
+                b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            },
+            Scan::LineComment if b == b'\n' => scan = Scan::Code,
+            Scan::BlockComment if b == b'*' && next == Some(b'/') => {
+                blank(i..i + 2);
+                scan = Scan::Code;
+                i += 1;
+            }
+            Scan::Quoted(_) | Scan::Template if b == b'\\' => {
+                blank(i..(i + 2).min(bytes.len()));
+                i += 1;
+            }
+            Scan::Quoted(q) if b == q || b == b'\n' => scan = Scan::Code,
+            Scan::Template if b == b'`' => scan = Scan::Code,
+            Scan::Template if b == b'$' && next == Some(b'{') => {
+                depth += 1;
+                interpolations.push(depth);
+                scan = Scan::Code;
+                i += 1;
+            }
+            _ => blank(i..i + 1),
+        }
+        i += 1;
+    }
+    // Only ASCII bytes were replaced, and multi-byte characters are blanked whole or not at all
+    String::from_utf8(masked).unwrap_or_else(|_| source.to_string())
+}
+
+// Best-effort scan of perform.js for argument names it reads: `args.x`, `args?.x`, `args["x"]`,
+// `const { x } = args` and `run({ x })`. `dynamic` is set when access can't be resolved statically
+fn perform_argument_usage(script: &str) -> (BTreeSet<String>, bool) {
+    let masked = mask_js_literals(script);
+    let source = masked.as_str();
+    let mut used = BTreeSet::new();
+    let mut dynamic = false;
+    let mut objects: Vec<String> = PERFORM_ARGS_NAMES.iter().map(|s| s.to_string()).collect();
+
+    for function in ["run", "perform"] {
+        for offset in js_word_offsets(source, function).collect::<Vec<_>>() {
+            // Also `perform = async (x) =>` and `run: function (x)`
+            let mut open = skip_js_whitespace(source, offset + function.len());
+            if matches!(source.as_bytes().get(open), Some(b'=' | b':')) && source.as_bytes().get(open + 1) != Some(&b'=') {
+                open = skip_js_whitespace(source, open + 1);
+            }
+            for keyword in ["async", "function"] {
+                if js_identifier_at(source, open) == Some(keyword) {
+                    open = skip_js_whitespace(source, open + keyword.len());
+                }
+            }
+            if source.as_bytes().get(open) != Some(&b'(') {
+                continue;
+            }
+            let start = skip_js_whitespace(source, open + 1);
+            match source.as_bytes().get(start) {
+                Some(b'{') => {
+                    if let Some(end) = matching_bracket(source, start, true) {
+                        destructured_names(&source[start + 1..end], &mut used, &mut dynamic);
+                    }
+                }
+                _ => {
+                    if let Some(name) = js_identifier_at(source, start) {
+                        if !objects.iter().any(|o| o == name) {
+                            objects.push(name.to_string());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    for object in &objects {
+        // Skip properties of other objects such as `this.args`
+        for offset in js_word_offsets(source, object).filter(|&i| !source[..i].ends_with('.')) {
+            let mut i = skip_js_whitespace(source, offset + object.len());
+            let rest = &source[i..];
+            if rest.starts_with("?.[") || rest.starts_with('[') {
+                i = skip_js_whitespace(source, i + if rest.starts_with('[') { 1 } else { 3 });
+                let quote = source.as_bytes().get(i).copied();
+                match quote {
+                    Some(q @ (b'"' | b'\'' | b'`')) => {
+                        // The key itself is blanked in the masked copy
+                        let key = script[i + 1..].split(q as char).next().unwrap_or_default();
+                        used.insert(key.to_string());
+                    }
+                    _ => dynamic = true,
+                }
+            } else if rest.starts_with("?.") || rest.starts_with('.') {
+                let start = skip_js_whitespace(source, i + if rest.starts_with('.') { 1 } else { 2 });
+                if let Some(name) = js_identifier_at(source, start) {
+                    used.insert(name.to_string());
+                }
+            } else {
+                // `{ a, b } = args`
+                let before = source[..offset].trim_end();
+                let Some(before) = before.strip_suffix('=').filter(|b| !b.ends_with(['=', '!', '<', '>'])) else {
+                    continue;
+                };
+                let before = before.trim_end();
+                if before.ends_with('}') {
+                    if let Some(open) = matching_bracket(source, before.len() - 1, false) {
+                        destructured_names(&source[open + 1..before.len() - 1], &mut used, &mut dynamic);
+                    }
+                }
+            }
+        }
+    }
+
+    (used, dynamic)
+}
+
+#[tauri::command]
+pub async fn lint_action(
+    action_name: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<LintWarning>, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    // Validate action name
+    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+
+    let action_dir = data_root.join("actions").join(&action_name);
+    let meta = load_action_meta(&action_dir)?;
+    let declared: Vec<ActionArgument> = serde_json::from_value(meta["arguments"].clone())
+        .map_err(|e| format!("Failed to parse action arguments: {}", e))?;
+    let source = fs::read_to_string(action_dir.join("perform.js"))
+        .map_err(|e| format!("Failed to read perform.js: {}", e))?;
+
+    let (used, dynamic) = perform_argument_usage(&source);
+    let mut warnings = Vec::new();
+
+    // With computed or rest access any declared argument might be read, so don't call any unused
+    if !dynamic {
+        for argument in declared.iter().filter(|a| !used.contains(&a.name)) {
+            warnings.push(LintWarning {
+                kind: "unused_argument".to_string(),
+                argument: argument.name.clone(),
+                message: format!("Argument '{}' is declared in meta.json but perform.js never reads it", argument.name),
+            });
+        }
+    }
+    for name in used.iter().filter(|name| !declared.iter().any(|a| &a.name == *name)) {
+        warnings.push(LintWarning {
+            kind: "undeclared_argument".to_string(),
+            argument: name.clone(),
+            message: format!("perform.js reads '{}' but meta.json doesn't declare it", name),
+        });
+    }
+
+    Ok(warnings)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ErroringAction {
+    pub name: String,
+    pub error_count: u32,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HealthSummary {
+    pub total: usize,
+    // Number of actions per status; unreadable status files count as "unknown"
+    pub counts: HashMap<String, usize>,
+    pub erroring: Vec<ErroringAction>,
+}
+
+#[tauri::command]
+pub async fn get_actions_health_summary(
+    state: State<'_, AppState>,
+) -> Result<HealthSummary, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let actions_dir = data_root.join("actions");
+
+    let mut summary = HealthSummary {
+        total: 0,
+        counts: HashMap::new(),
+        erroring: vec![],
+    };
+    if !actions_dir.exists() {
+        return Ok(summary);
+    }
+
+    for entry in fs::read_dir(&actions_dir)
+        .map_err(|e| format!("Failed to read actions directory: {}", e))?
+    {
+        let entry = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let action_dir = entry.path();
+        if !action_dir.join("meta.json").exists() {
+            continue;
+        }
+
+        let name = entry.file_name().to_string_lossy().to_string();
+        let status_path = action_dir.join("status.json");
+
+        // Actions without a status file are healthy, as in get_action_status
+        let status: Option<ActionStatus> = if status_path.exists() {
+            read_resource_text(&status_path).ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+        } else {
+            Some(ActionStatus {
+                status: "healthy".to_string(),
+                last_error: None,
+                error_count: 0,
+                last_success: None,
+            })
+        };
+
+        summary.total += 1;
+        let label = status.as_ref().map_or("unknown", |s| s.status.as_str());
+        *summary.counts.entry(label.to_string()).or_insert(0) += 1;
+
+        if let Some(status) = status.filter(|s| s.status == "error") {
+            summary.erroring.push(ErroringAction {
+                name,
+                error_count: status.error_count,
+                last_error: status.last_error.as_ref().map(|e| e.message.clone()),
+                last_error_at: status.last_error.map(|e| e.timestamp),
+            });
+        }
+    }
+
+    summary.erroring.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(summary)
+}
+
+#[tauri::command]
+pub async fn reset_action_status(
+    action_name: String,
+    state: State<'_, AppState>,
+) -> Result<ActionStatus, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let action_dir = data_root.join("actions").join(&action_name);
+
+    // Validate action name
+    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+
+    if !action_dir.exists() {
+        return Err(format!("Action '{}' not found", action_name));
+    }
+
+    let status = ActionStatus {
+        status: "healthy".to_string(),
+        last_error: None,
+        error_count: 0,
+        last_success: Some(chrono::Utc::now().to_rfc3339()),
+    };
+    let lock = state.action_locks.get(&action_name);
+    let _guard = lock.lock().unwrap();
+    write_action_status(&action_dir, &status)?;
+
+    println!("Action status reset: {}", action_name);
+    Ok(status)
+}
+
+// Action Execution
+
+// Bytes of stdout/stderr kept per run; the rest is read and dropped
+const MAX_ACTION_OUTPUT_BYTES: usize = 1024 * 1024;
+// Only these variables from the app's environment reach perform.js
+const ACTION_ENV_ALLOWLIST: [&str; 9] = [
+    "PATH", "HOME", "USER", "SHELL", "NODE_PATH", "NODE_ENV", "SYSTEMROOT", "TEMP", "TMP",
+];
+// Prefix of the stdout line carrying the action's JSON result
+const ACTION_RESULT_MARKER: &str = "__PULSAR_ACTION_RESULT__";
+
+// Loads perform.js from the working directory, runs it with the JSON arguments in argv[1]
+// and prints the result on a marked line
+const ACTION_RUNNER_SCRIPT: &str = r#"
+const args = JSON.parse(process.argv[1] || '{}');
+Promise.resolve().then(() => {
+  const m = require(require('path').resolve('perform.js'));
+  if (m && typeof m.Perform === 'function') return new m.Perform({}).run(args);
+  const perform = typeof m === 'function' ? m : m.perform;
+  return perform(args);
+}).then(output => {
+  if (typeof output !== 'object' || output === null) output = { output: String(output) };
+  process.stdout.write('\n__PULSAR_ACTION_RESULT__' + JSON.stringify(output) + '\n');
+  process.exit(0);
+}).catch(error => {
+  process.stderr.write(String((error && error.stack) || error) + '\n');
+  process.exit(1);
+});
+"#;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ActionRunResult {
+    pub run_id: String,
+    pub action_name: String,
+    pub status: String, // "success", "error", "timeout"
+    pub output: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub stdout: String,
+    pub stderr: String,
+    pub output_truncated: bool,
+    pub duration_ms: u64,
+}
+
+// Run log entries for the complete, non-blank lines in `text`
+fn run_log_lines(stream: &str, text: &[u8]) -> Vec<serde_json::Value> {
+    let now = chrono::Utc::now().to_rfc3339();
+    String::from_utf8_lossy(text).lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::json!({ "timestamp": now, "stream": stream, "message": line }))
+        .collect()
+}
+
+// Read a child pipe to the end, keeping at most `limit` bytes. Kept lines are appended to the
+// run log as they arrive, so a run that hangs or takes the app down still leaves its output
+async fn read_capped<R: tokio::io::AsyncRead + Unpin>(
+    mut reader: R,
+    limit: usize,
+    log_path: PathBuf,
+    stream: &'static str,
+) -> (Vec<u8>, bool) {
+    use tokio::io::AsyncReadExt;
+
+    let mut kept = Vec::new();
+    let mut truncated = false;
+    // Start of the line not yet written to the log
+    let mut logged = 0;
+    let mut chunk = [0u8; 8192];
+    loop {
+        match reader.read(&mut chunk).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => {
+                let room = limit.saturating_sub(kept.len());
+                if n > room {
+                    truncated = true;
+                }
+                kept.extend_from_slice(&chunk[..n.min(room)]);
+                if let Some(newline) = kept[logged..].iter().rposition(|b| *b == b'\n') {
+                    append_run_log(&log_path, &run_log_lines(stream, &kept[logged..logged + newline]));
+                    logged += newline + 1;
+                }
+            }
+        }
+    }
+    append_run_log(&log_path, &run_log_lines(stream, &kept[logged..]));
+    (kept, truncated)
+}
+
+// Kill the action's whole process group so helpers it spawned go down with it
+fn kill_process_tree(pid: u32) {
+    #[cfg(unix)]
+    let mut command = {
+        let mut command = Command::new("kill");
+        command.args(["-KILL", &format!("-{}", pid)]);
+        command
+    };
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = Command::new("taskkill");
+        command.args(["/T", "/F", "/PID", &pid.to_string()]);
+        command
+    };
+
+    if let Err(e) = command.stdout(Stdio::null()).stderr(Stdio::null()).status() {
+        println!("Failed to kill action process {}: {}", pid, e);
+    }
+}
+
+// Live action processes by run id, so they can be stopped before they finish
+#[derive(Debug, Default)]
+pub struct RunningActions(Mutex<HashMap<String, u32>>);
+
+impl RunningActions {
+    fn insert(&self, run_id: &str, pid: u32) {
+        self.0.lock().unwrap().insert(run_id.to_string(), pid);
+    }
+
+    // False when the run was already taken out by cancel_all
+    fn remove(&self, run_id: &str) -> bool {
+        self.0.lock().unwrap().remove(run_id).is_some()
+    }
+
+    // Kill every tracked process tree; returns how many were stopped
+    pub fn cancel_all(&self) -> usize {
+        let runs: Vec<(String, u32)> = self.0.lock().unwrap().drain().collect();
+        for (run_id, pid) in &runs {
+            println!("Cancelling action run {} (pid {})", run_id, pid);
+            kill_process_tree(*pid);
+        }
+        runs.len()
+    }
+}
+
+#[tauri::command]
+pub async fn cancel_all_runs(state: State<'_, AppState>) -> Result<usize, String> {
+    Ok(state.running_actions.cancel_all())
+}
+
+#[tauri::command]
+pub async fn execute_action(
+    action_name: String,
+    args: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<ActionRunResult, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    // Validate action name
+    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
+        return Err("Invalid action name".to_string());
+    }
+
+    let action_dir = data_root.join("actions").join(&action_name);
+    let meta = load_action_meta(&action_dir)?;
+
+    // Check argument values, filling in declared defaults
+    let mut args = if args.is_null() { serde_json::json!({}) } else { args };
+    let provided = args.as_object_mut().ok_or("Action arguments must be a JSON object")?;
+    for arg in meta["arguments"].as_array().into_iter().flatten() {
+        let required = arg.get("required").and_then(|v| v.as_bool()).unwrap_or(false);
+        let name = arg.get("name").and_then(|v| v.as_str()).unwrap_or_default();
+        match provided.get(name) {
+            Some(value) => check_argument_value(arg, value)
+                .map_err(|e| format!("Invalid value for argument '{}': {}", name, e))?,
+            None => match arg.get("default") {
+                Some(default) => {
+                    provided.insert(name.to_string(), default.clone());
+                }
+                None if required => return Err(format!("Required argument missing: {}", name)),
+                None => {}
+            },
+        }
+    }
+
+    // load_action_meta has already checked the bounds
+    let timeout_secs = meta["timeout_sec"].as_u64().ok_or("Action meta has no timeout_sec")?;
+
+    let mut command = TokioCommand::new("node");
+    command
+        .arg("-e")
+        .arg(ACTION_RUNNER_SCRIPT)
+        .arg(args.to_string())
+        .current_dir(&action_dir)
+        .env_clear()
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    for key in ACTION_ENV_ALLOWLIST {
+        if let Ok(value) = std::env::var(key) {
+            command.env(key, value);
+        }
+    }
+    command
+        .env("PULSAR_ACTION_HOME", &action_dir)
+        .env("PULSAR_ACTION_NAME", &action_name);
+
+    // Own process group, so a timeout can kill everything the action started
+    #[cfg(unix)]
+    command.process_group(0);
+
+    let run_id = uuid::Uuid::new_v4().to_string();
+    let log_path = run_log_path(&data_root, &run_id);
+    let started = Instant::now();
+    let mut child = command.spawn()
+        .map_err(|e| format!("Failed to start node: {}", e))?;
+    // Keep a per-run log the UI can page through (and follow while it grows) with get_run_log
+    append_run_log(&log_path, &[serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(), "stream": "status", "message": format!("Started action {}", action_name)
+    })]);
+    let tracked = match child.id() {
+        Some(pid) => {
+            state.running_actions.insert(&run_id, pid);
+            true
+        }
+        None => false,
+    };
+
+    let stdout_reader = tokio::spawn(read_capped(child.stdout.take().ok_or("Failed to capture stdout")?, MAX_ACTION_OUTPUT_BYTES, log_path.clone(), "stdout"));
+    let stderr_reader = tokio::spawn(read_capped(child.stderr.take().ok_or("Failed to capture stderr")?, MAX_ACTION_OUTPUT_BYTES, log_path.clone(), "stderr"));
+
+    let exit = tokio::time::timeout(Duration::from_secs(timeout_secs), child.wait()).await;
+    let timed_out = exit.is_err();
+    if timed_out {
+        if let Some(pid) = child.id() {
+            kill_process_tree(pid);
+        }
+        let _ = child.kill().await;
+    }
+    let cancelled = tracked && !state.running_actions.remove(&run_id);
+
+    let (stdout, stdout_truncated) = stdout_reader.await.unwrap_or_default();
+    let (stderr, stderr_truncated) = stderr_reader.await.unwrap_or_default();
+    let stdout = String::from_utf8_lossy(&stdout).into_owned();
+    let stderr = String::from_utf8_lossy(&stderr).into_owned();
+
+    let output = stdout.lines()
+        .rev()
+        .find_map(|line| line.strip_prefix(ACTION_RESULT_MARKER))
+        .and_then(|json| serde_json::from_str(json).ok());
+
+    let (status, error) = match exit {
+        Ok(_) if cancelled => ("cancelled", Some("Action run was cancelled".to_string())),
+        Err(_) => ("timeout", Some(format!("Action timed out after {} seconds", timeout_secs))),
+        Ok(Err(e)) => ("error", Some(format!("Failed to wait for action: {}", e))),
+        Ok(Ok(exit_status)) if exit_status.success() => ("success", None),
+        Ok(Ok(exit_status)) => {
+            let last_line = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or_default();
+            ("error", Some(format!("Action exited with {}: {}", exit_status, last_line.trim())))
+        }
+    };
+
+    // Timeouts and failures count against the action's health
+    let recorded = match &error {
+        Some(message) => record_action_status(&state.action_locks, &data_root, &action_name, "error", Some(message.clone()), Some(run_id.clone())),
+        None => record_action_status(&state.action_locks, &data_root, &action_name, "healthy", None, None),
+    };
+    if let Err(e) = recorded {
+        println!("Failed to record status for action {}: {}", action_name, e);
+    }
+
+    append_run_log(&log_path, &[serde_json::json!({
+        "timestamp": chrono::Utc::now().to_rfc3339(), "stream": "status", "message": error.clone().unwrap_or_else(|| status.to_string())
+    })]);
+
+    println!("Action {} finished: {} ({})", action_name, status, run_id);
+    Ok(ActionRunResult {
+        run_id,
+        action_name,
+        status: status.to_string(),
+        output,
+        error,
+        stdout,
+        stderr,
+        output_truncated: stdout_truncated || stderr_truncated,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+// Run Logs
+
+// Lines returned by get_run_log when no limit is given, and the most it will return at once
+const DEFAULT_RUN_LOG_LIMIT: usize = 500;
+const MAX_RUN_LOG_LIMIT: usize = 5000;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunLogPage {
+    pub run_id: String,
+    pub total_lines: usize,
+    pub offset: usize,
+    pub lines: Vec<serde_json::Value>,
+}
+
+// Each run logs to logs/runs/{run_id}.jsonl, one JSON object per line
+fn run_log_path(data_root: &Path, run_id: &str) -> PathBuf {
+    data_root.join("logs").join("runs").join(format!("{}.jsonl", run_id))
+}
+
+fn append_run_log(log_path: &Path, entries: &[serde_json::Value]) {
+    if entries.is_empty() {
+        return;
+    }
+    let mut content = String::new();
+    for entry in entries {
+        content.push_str(&entry.to_string());
+        content.push('\n');
+    }
+
+    let result = log_path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::OpenOptions::new().create(true).append(true).open(log_path))
+        .and_then(|mut file| file.write_all(content.as_bytes()));
+
+    if let Err(e) = result {
+        println!("Failed to write run log {:?}: {}", log_path, e);
+    }
+}
+
+#[tauri::command]
+pub async fn get_run_log(
+    run_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    tail: Option<usize>,
+    state: State<'_, AppState>,
+) -> Result<RunLogPage, String> {
+    use std::io::BufRead;
+
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    // Validate run id to prevent directory traversal
+    if run_id.contains("..") || run_id.contains("/") || run_id.contains("\\") {
+        return Err("Invalid run id".to_string());
+    }
+
+    let log_path = run_log_path(&data_root, &run_id);
+    if !log_path.exists() {
+        return Err(format!("No log found for run '{}'", run_id));
+    }
+
+    let open = || fs::File::open(&log_path)
+        .map(std::io::BufReader::new)
+        .map_err(|e| format!("Failed to open run log: {}", e));
+
+    // First pass only counts, so large logs are never held in memory whole
+    let total_lines = open()?.lines().count();
+
+    // Tail mode returns the last N lines and ignores offset/limit; N is capped first so the
+    // page really ends at the last line
+    let (offset, limit) = match tail {
+        Some(n) => {
+            let n = n.min(MAX_RUN_LOG_LIMIT);
+            (total_lines.saturating_sub(n), n)
+        }
+        None => (offset.unwrap_or(0), limit.unwrap_or(DEFAULT_RUN_LOG_LIMIT).min(MAX_RUN_LOG_LIMIT)),
+    };
+
+    let lines = open()?.lines()
+        .skip(offset)
+        .take(limit)
+        .map(|line| {
+            let line = line.map_err(|e| format!("Failed to read run log: {}", e))?;
+            // Keep lines that aren't valid JSON visible instead of failing the page
+            Ok(serde_json::from_str(&line).unwrap_or(serde_json::Value::String(line)))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Ok(RunLogPage {
+        run_id,
+        total_lines,
+        offset,
+        lines,
+    })
+}
+
+// Data Directory Check
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileReport {
+    pub path: String, // relative to data_root
+    pub kind: String, // "config", "guide", "knowledge", "action", "thread"
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationReport {
+    pub checked_files: usize,
+    pub error_count: usize,
+    pub warning_count: usize,
+    // Only files with at least one error or warning
+    pub files: Vec<FileReport>,
+}
+
+impl ValidationReport {
+    fn add(&mut self, report: FileReport) {
+        self.checked_files += 1;
+        self.error_count += report.errors.len();
+        self.warning_count += report.warnings.len();
+        if !report.errors.is_empty() || !report.warnings.is_empty() {
+            self.files.push(report);
+        }
+    }
+}
+
+fn new_file_report(path: String, kind: &str) -> FileReport {
+    FileReport {
+        path,
+        kind: kind.to_string(),
+        errors: vec![],
+        warnings: vec![],
+    }
+}
+
+// Entry names that appear more than once (case-insensitive, trimmed)
+fn duplicate_entry_names(document: &serde_json::Value) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut duplicates = Vec::new();
+    for entry in document["entries"].as_array().into_iter().flatten() {
+        if let Some(key) = entry_name_key(entry) {
+            if !seen.insert(key.clone()) && !duplicates.contains(&key) {
+                duplicates.push(key);
+            }
+        }
+    }
+    duplicates
+}
+
+// Schema-check every JSON file of a guide/knowledge directory
+fn check_resource_files(
+    report: &mut ValidationReport,
+    data_root: &Path,
+    dir: &str,
+    kind: &str,
+    validator: &jsonschema::Validator,
+    options: StructureOptions,
+) {
+    let Ok(entries) = fs::read_dir(data_root.join(dir)) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| is_resource_file(p)).collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut file = new_file_report(format!("{}/{}", dir, name), kind);
+
+        match read_resource_text(&path).map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string()))
+        {
+            Ok(document) => {
+                file.errors.extend(schema_violations(validator, &document));
+                // Duplicates are only errors when unique names are required
+                let duplicates = if options.unique_entry_names { &mut file.errors } else { &mut file.warnings };
+                for name in duplicate_entry_names(&document) {
+                    duplicates.push(format!("Duplicate entry name '{}'", name));
+                }
+            }
+            Err(e) => file.errors.push(format!("Invalid JSON: {}", e)),
+        }
+        report.add(file);
+    }
+}
+
+fn check_action_dirs(report: &mut ValidationReport, data_root: &Path) {
+    let Ok(entries) = fs::read_dir(data_root.join("actions")) else {
+        return;
+    };
+    let mut dirs: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    dirs.sort();
+
+    for dir in dirs {
+        let name = dir.file_name().unwrap_or_default().to_string_lossy().to_string();
+        let mut file = new_file_report(format!("actions/{}", name), "action");
+
+        match read_resource_text(dir.join("meta.json")).map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).map_err(|e| e.to_string()))
+        {
+            Ok(meta) => {
+                file.errors.extend(schema_violations(compiled_validator(&ACTION_META_VALIDATOR, action_meta_schema), &meta));
+                if let Some(meta_name) = meta["name"].as_str().filter(|meta_name| *meta_name != name) {
+                    file.warnings.push(format!("meta.json name '{}' differs from the directory name", meta_name));
+                }
+            }
+            Err(e) => file.errors.push(format!("meta.json: {}", e)),
+        }
+
+        if !dir.join("perform.js").is_file() {
+            file.errors.push("Missing perform.js".to_string());
+        }
+
+        let status_path = dir.join("status.json");
+        if status_path.exists() {
+            let status = read_resource_text(&status_path).ok()
+                .and_then(|content| serde_json::from_str::<ActionStatus>(&content).ok());
+            match status {
+                Some(status) if status.status == "error" => file.warnings.push(format!(
+                    "Action is in error state: {}",
+                    status.last_error.map(|e| e.message).unwrap_or_default()
+                )),
+                Some(_) => {}
+                None => file.warnings.push("status.json is unreadable".to_string()),
+            }
+        }
+
+        report.add(file);
+    }
+}
+
+fn check_threads(report: &mut ValidationReport, data_root: &Path, providers: &[LLMProvider]) {
+    let Ok(entries) = fs::read_dir(data_root.join("saves")) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|e| e.path()).filter(|p| is_json_file(p)).collect();
+    paths.sort();
+
+    let guides = available_resource_names(&data_root.join("guides"), None);
+    let knowledge = available_resource_names(&data_root.join("knowledge"), None);
+    let actions = available_resource_names(&data_root.join("actions"), Some("meta.json"));
+
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let mut file = new_file_report(format!("saves/{}", name), "thread");
+
+        let thread = read_resource_text(&path).map_err(|e| e.to_string())
+            .and_then(|content| serde_json::from_str::<Thread>(&content).map_err(|e| e.to_string()));
+        match thread {
+            Ok(thread) => {
+                if let Some(config) = thread.config {
+                    for (role, alias) in [("planner", &config.planner_llm_alias), ("decider", &config.decider_llm_alias)] {
+                        if !providers.iter().any(|p| alias_matches(&p.alias, alias)) {
+                            file.errors.push(format!("{} provider '{}' does not exist", role, alias));
+                        }
+                    }
+                    for (kind, selected, available) in [
+                        ("guide", &config.selected_guides, &guides),
+                        ("knowledge", &config.selected_knowledge, &knowledge),
+                        ("action", &config.selected_actions, &actions),
+                    ] {
+                        for missing in selected.iter().filter(|s| !available.contains(*s)) {
+                            file.warnings.push(format!("Selected {} '{}' does not exist", kind, missing));
+                        }
+                    }
+                }
+            }
+            Err(e) => file.errors.push(format!("Invalid thread file: {}", e)),
+        }
+        report.add(file);
+    }
+}
+
+fn check_providers(report: &mut ValidationReport, providers: &[LLMProvider]) {
+    let mut file = new_file_report("configs.json".to_string(), "config");
+    let mut aliases = HashSet::new();
+
+    for provider in providers {
+        if !SUPPORTED_PROVIDER_KINDS.contains(&provider.provider.as_str()) {
+            file.errors.push(format!("Provider '{}' has unsupported kind '{}'", provider.alias, provider.provider));
+        }
+        if let Err(e) = clean_base_url(&provider.base_url) {
+            file.errors.push(format!("Provider '{}': {}", provider.alias, e));
+        }
+        if !aliases.insert(provider.alias.trim().to_lowercase()) {
+            file.errors.push(format!("Alias '{}' is used by more than one provider", provider.alias));
+        }
+        if provider.model.trim().is_empty() {
+            file.warnings.push(format!("Provider '{}' has no model", provider.alias));
+        }
+    }
+    report.add(file);
+}
+
+#[tauri::command]
+pub async fn validate_all(
+    unique_entry_names: Option<bool>,
+    state: State<'_, AppState>,
+) -> Result<ValidationReport, String> {
+    let options = StructureOptions { unique_entry_names: unique_entry_names.unwrap_or(false) };
+    let (data_root, providers) = {
+        let config = state.config.lock().unwrap();
+        (config.data_root.clone(), config.llm_providers.clone())
+    };
+
+    let mut report = ValidationReport {
+        checked_files: 0,
+        error_count: 0,
+        warning_count: 0,
+        files: vec![],
+    };
+
+    check_providers(&mut report, &providers);
+    check_resource_files(&mut report, &data_root, "guides", "guide", compiled_validator(&GUIDE_VALIDATOR, guide_schema), options);
+    check_resource_files(&mut report, &data_root, "knowledge", "knowledge", compiled_validator(&KNOWLEDGE_VALIDATOR, knowledge_schema), options);
+    check_action_dirs(&mut report, &data_root);
+    check_threads(&mut report, &data_root, &providers);
+
+    println!(
+        "Validated {} files: {} errors, {} warnings",
+        report.checked_files, report.error_count, report.warning_count
+    );
+    Ok(report)
+}
+
+// Integrity Check
+
+// Event carrying an IntegrityReport, emitted after every scan
+pub const DATA_INTEGRITY_EVENT: &str = "data-integrity";
+
+// Directories the scan doesn't descend into: already quarantined, or third-party files.
+// Action import staging directories are skipped as well
+const INTEGRITY_SKIP_DIRS: [&str; 2] = [TRASH_DIR, "node_modules"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorruptFile {
+    // Relative to data_root
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    pub checked_files: usize,
+    pub corrupt_files: Vec<CorruptFile>,
+}
+
+fn scan_json_files(data_root: &Path, dir: &Path, report: &mut IntegrityReport) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+
+    for path in paths {
+        let name = path.file_name().unwrap_or_default().to_string_lossy().to_string();
+        if path.is_dir() {
+            if !INTEGRITY_SKIP_DIRS.contains(&name.as_str()) && !name.starts_with(ACTION_IMPORT_STAGING_PREFIX) {
+                scan_json_files(data_root, &path, report);
+            }
+            continue;
+        }
+        if !is_resource_file(&path) {
+            continue;
+        }
+
+        report.checked_files += 1;
+        let error = match read_resource_text(&path) {
+            Ok(content) => parse_json_text(&content).err().map(|e| e.describe()),
+            Err(e) => Some(format!("Failed to read file: {}", e)),
+        };
+        if let Some(error) = error {
+            report.corrupt_files.push(CorruptFile {
+                path: path.strip_prefix(data_root).unwrap_or(&path).to_string_lossy().to_string(),
+                error,
+            });
+        }
+    }
+}
+
+// Try to parse every JSON file under data_root and tell the UI which ones are broken
+pub fn check_data_integrity(app_handle: &tauri::AppHandle, data_root: &Path) -> IntegrityReport {
+    let mut report = IntegrityReport { checked_files: 0, corrupt_files: vec![] };
+    scan_json_files(data_root, data_root, &mut report);
+
+    println!(
+        "Integrity check: {} JSON files, {} unparseable",
+        report.checked_files, report.corrupt_files.len()
+    );
+    *app_handle.state::<AppState>().integrity_report.lock().unwrap() = Some(report.clone());
+    if let Err(e) = app_handle.emit_all(DATA_INTEGRITY_EVENT, &report) {
+        println!("Failed to emit {} event: {}", DATA_INTEGRITY_EVENT, e);
+    }
+    report
+}
+
+// Latest scan result, or None while the startup scan is still running
+#[tauri::command]
+pub async fn get_integrity_report(
+    state: State<'_, AppState>,
+) -> Result<Option<IntegrityReport>, String> {
+    Ok(state.integrity_report.lock().unwrap().clone())
+}
+
+#[tauri::command]
+pub async fn run_integrity_check(
+    state: State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+) -> Result<IntegrityReport, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    tokio::task::spawn_blocking(move || check_data_integrity(&app_handle, &data_root))
+        .await
+        .map_err(|e| format!("Integrity check failed: {}", e))
+}
+
+// Trash Commands
+
+// Deleted resources live in .trash/ as "{original}.{resource_dir}.{timestamp}"
+const TRASH_DIR: &str = ".trash";
+const TRASHABLE_DIRS: [&str; 4] = ["guides", "knowledge", "actions", "saves"];
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub name: String,
+    pub resource_dir: String,
+    pub original_name: String,
+    pub deleted_at: String,
+}
+
+// Move data_root/{resource_dir}/{name} into the trash, returning the trash entry name
+fn move_to_trash(data_root: &Path, resource_dir: &str, name: &str) -> Result<String, String> {
+    let trash_dir = data_root.join(TRASH_DIR);
+    fs::create_dir_all(&trash_dir)
+        .map_err(|e| format!("Failed to create trash directory: {}", e))?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ");
+    let trash_name = format!("{}.{}.{}", name, resource_dir, timestamp);
+
+    move_path(&data_root.join(resource_dir).join(name), &trash_dir.join(&trash_name))?;
+
+    println!("Moved to trash: {}/{} -> {}", resource_dir, name, trash_name);
+    Ok(trash_name)
+}
+
+fn parse_trash_name(trash_name: &str) -> Option<TrashEntry> {
+    let mut parts = trash_name.rsplitn(3, '.');
+    let deleted_at = parts.next()?;
+    let resource_dir = parts.next()?;
+    let original_name = parts.next()?;
+
+    if !TRASHABLE_DIRS.contains(&resource_dir) || original_name.is_empty() {
+        return None;
+    }
+
+    Some(TrashEntry {
+        name: trash_name.to_string(),
+        resource_dir: resource_dir.to_string(),
+        original_name: original_name.to_string(),
+        deleted_at: deleted_at.to_string(),
+    })
+}
+
+#[tauri::command]
+pub async fn list_trash(
+    state: State<'_, AppState>,
+) -> Result<Vec<TrashEntry>, String> {
+    let data_root = &state.config.lock().unwrap().data_root;
+    let trash_dir = data_root.join(TRASH_DIR);
+
+    if !trash_dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut entries = Vec::new();
+
+    for entry in fs::read_dir(&trash_dir).map_err(|e| format!("Failed to read trash directory: {}", e))? {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        if let Some(trash_entry) = parse_trash_name(&entry.file_name().to_string_lossy()) {
+            entries.push(trash_entry);
+        }
+    }
+
+    entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(entries)
+}
+
+#[tauri::command]
+pub async fn restore_from_trash(
+    name: String,
+    state: State<'_, AppState>,
+) -> Result<TrashEntry, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+
+    // Validate name to prevent directory traversal
+    if name.contains("..") || name.contains("/") || name.contains("\\") {
+        return Err("Invalid trash entry name".to_string());
+    }
+
+    let trash_path = data_root.join(TRASH_DIR).join(&name);
+    let entry = parse_trash_name(&name)
+        .filter(|_| trash_path.exists())
+        .ok_or_else(|| format!("Trash entry '{}' not found", name))?;
+
+    let target_dir = data_root.join(&entry.resource_dir);
+    let target_path = target_dir.join(&entry.original_name);
+    if target_path.exists() {
+        return Err(format!(
+            "Cannot restore '{}': {}/{} already exists",
+            name, entry.resource_dir, entry.original_name
+        ));
+    }
+
+    fs::create_dir_all(&target_dir)
+        .map_err(|e| format!("Failed to create {} directory: {}", entry.resource_dir, e))?;
+    move_path(&trash_path, &target_path)?;
+
+    println!("Restored from trash: {} -> {:?}", name, target_path);
+    Ok(entry)
+}
+
+#[tauri::command]
+pub async fn empty_trash(
+    state: State<'_, AppState>,
+) -> Result<usize, String> {
+    ensure_writable(&state)?;
+    let data_root = &state.config.lock().unwrap().data_root;
+    let trash_dir = data_root.join(TRASH_DIR);
+
+    if !trash_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut removed = 0;
+
+    for entry in fs::read_dir(&trash_dir).map_err(|e| format!("Failed to read trash directory: {}", e))? {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+
+        let result = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else {
+            fs::remove_file(&path)
+        };
+        result.map_err(|e| format!("Failed to remove {:?}: {}", path, e))?;
+        removed += 1;
+    }
+
+    println!("Trash emptied: {} entries removed", removed);
+    Ok(removed)
+}
+
+const BUILTIN_THEMES: [&str; 3] = ["light", "dark", "system"];
+
+// Custom themes are stored as themes/{name}.json
+fn custom_theme_path(data_root: &Path, name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid {
+        return Err(format!("Invalid theme name '{}'. Use letters, digits, '-' and '_'", name));
+    }
+    Ok(data_root.join("themes").join(format!("{}.json", name)))
+}
+
+fn load_custom_theme(data_root: &Path, name: &str) -> Result<serde_json::Value, String> {
+    let path = custom_theme_path(data_root, name)?;
+    let content = read_resource_text(&path)
+        .map_err(|e| format!("Failed to read theme '{}': {}", name, e))?;
+    let theme: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse theme '{}': {}", name, e))?;
+    validate_against_schema(compiled_validator(&THEME_VALIDATOR, theme_schema), &theme)
+        .map_err(|e| format!("Invalid theme '{}': {}", name, e))?;
+    Ok(theme)
+}
+
+#[tauri::command]
+pub async fn save_custom_theme(
+    name: String,
+    theme_json: serde_json::Value,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    let name = name.trim();
+    if BUILTIN_THEMES.contains(&name) {
+        return Err(format!("'{}' is a built-in theme name", name));
+    }
+    let path = custom_theme_path(&data_root, name)?;
+    validate_against_schema(compiled_validator(&THEME_VALIDATOR, theme_schema), &theme_json)?;
+
+    fs::create_dir_all(data_root.join("themes"))
+        .map_err(|e| format!("Failed to create themes directory: {}", e))?;
+    let content = serde_json::to_string_pretty(&theme_json)
+        .map_err(|e| format!("Failed to serialize theme: {}", e))?;
+    write_atomic(&path, content)?;
+
+    println!("Custom theme saved: {:?}", path);
+    Ok(())
+}
+
+// Names of the saved custom themes that pass validation
+#[tauri::command]
+pub async fn list_custom_themes(
+    state: State<'_, AppState>,
+) -> Result<Vec<String>, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    let Ok(entries) = fs::read_dir(data_root.join("themes")) else {
+        return Ok(vec![]);
+    };
+
+    let mut names: Vec<String> = entries.flatten()
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                return None;
+            }
+            let name = path.file_stem()?.to_str()?.to_string();
+            match load_custom_theme(&data_root, &name) {
+                Ok(_) => Some(name),
+                Err(e) => {
+                    println!("Skipping theme {:?}: {}", path, e);
+                    None
+                }
+            }
+        })
+        .collect();
+    names.sort();
+    Ok(names)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct EffectiveTheme {
+    pub name: String,
+    // Built-in theme the frontend applies first: the theme itself, or a custom theme's base
+    pub base: String,
+    pub custom: bool,
+    // Token overrides on top of `base`; empty for built-in themes
+    pub colors: BTreeMap<String, String>,
+}
+
+#[tauri::command]
+pub async fn get_effective_theme(
+    state: State<'_, AppState>,
+) -> Result<EffectiveTheme, String> {
+    let (data_root, name) = {
+        let config = state.config.lock().unwrap();
+        (config.data_root.clone(), config.theme.clone())
+    };
+
+    if BUILTIN_THEMES.contains(&name.as_str()) {
+        return Ok(EffectiveTheme { base: name.clone(), name, custom: false, colors: BTreeMap::new() });
+    }
+
+    // A custom theme deleted or broken since it was selected falls back to light
+    match load_custom_theme(&data_root, &name) {
+        Ok(theme) => Ok(EffectiveTheme {
+            base: theme["base"].as_str().unwrap_or("light").to_string(),
+            colors: serde_json::from_value(theme["colors"].clone())
+                .map_err(|e| format!("Invalid theme colors: {}", e))?,
+            name,
+            custom: true,
+        }),
+        Err(e) => {
+            println!("{}; falling back to the light theme", e);
+            Ok(EffectiveTheme { name, base: "light".to_string(), custom: false, colors: BTreeMap::new() })
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn set_theme(
+    theme: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    // Validate theme value
+    if !BUILTIN_THEMES.contains(&theme.as_str()) && load_custom_theme(&config.data_root, &theme).is_err() {
+        return Err(format!(
+            "Invalid theme '{}'. Must be 'light', 'dark', 'system', or a saved custom theme",
+            theme
+        ));
+    }
+
+    config.theme = theme.clone();
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+// Translations shipped with the frontend; a file in locales/ with the same code overrides them
+const BUILTIN_LOCALES: [(&str, &str); 2] = [
+    ("en", include_str!("../../src/i18n/locales/en.json")),
+    ("zh", include_str!("../../src/i18n/locales/zh.json")),
+];
+
+// Display names for common codes; a locale file can set its own under "_meta.name"
+const LOCALE_DISPLAY_NAMES: [(&str, &str); 12] = [
+    ("en", "English"), ("zh", "中文"), ("ja", "日本語"), ("ko", "한국어"),
+    ("fr", "Français"), ("de", "Deutsch"), ("es", "Español"), ("pt", "Português"),
+    ("it", "Italiano"), ("ru", "Русский"), ("ar", "العربية"), ("hi", "हिन्दी"),
+];
+
+// Codes like "en", "pt-BR" or "zh-Hant"
+fn is_valid_locale_code(code: &str) -> bool {
+    let mut parts = code.split('-');
+    let language = parts.next().unwrap_or_default();
+    (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && parts.all(|part| (2..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+fn locale_path(data_root: &Path, code: &str) -> Result<PathBuf, String> {
+    if !is_valid_locale_code(code) {
+        return Err(format!("Invalid locale code '{}'", code));
+    }
+    Ok(data_root.join("locales").join(format!("{}.json", code)))
+}
+
+// Translation map for a locale code, from locales/ or the built-in set
+fn load_locale(data_root: &Path, code: &str) -> Result<serde_json::Value, String> {
+    let path = locale_path(data_root, code)?;
+    let content = match read_resource_text(&path) {
+        Ok(content) => content,
+        Err(_) => BUILTIN_LOCALES.iter()
+            .find(|(builtin, _)| *builtin == code)
+            .map(|(_, content)| content.to_string())
+            .ok_or_else(|| format!("Locale '{}' not found", code))?,
+    };
+
+    let strings: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse locale '{}': {}", code, e))?;
+    if !strings.is_object() {
+        return Err(format!("Locale '{}' must be a JSON object", code));
+    }
+    Ok(strings)
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LanguageInfo {
+    pub code: String,
+    pub name: String,
+    pub builtin: bool,
+}
+
+// Built-in locales plus every readable locales/{code}.json, sorted by code
+fn available_locales(data_root: &Path) -> Vec<LanguageInfo> {
+    let mut codes: BTreeSet<String> = BUILTIN_LOCALES.iter().map(|(code, _)| code.to_string()).collect();
+    if let Ok(entries) = fs::read_dir(data_root.join("locales")) {
+        codes.extend(entries.flatten().filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("json") {
+                return None;
+            }
+            path.file_stem()?.to_str().filter(|code| is_valid_locale_code(code)).map(String::from)
+        }));
+    }
+
+    codes.into_iter()
+        .filter_map(|code| {
+            let strings = match load_locale(data_root, &code) {
+                Ok(strings) => strings,
+                Err(e) => {
+                    println!("Skipping locale {}: {}", code, e);
+                    return None;
+                }
+            };
+            let primary = code.split('-').next().unwrap_or_default();
+            let name = strings["_meta"]["name"].as_str().map(String::from)
+                .or_else(|| LOCALE_DISPLAY_NAMES.iter().find(|(c, _)| *c == primary).map(|(_, n)| n.to_string()))
+                .unwrap_or_else(|| code.clone());
+            Some(LanguageInfo {
+                builtin: BUILTIN_LOCALES.iter().any(|(builtin, _)| *builtin == code),
+                code,
+                name,
+            })
+        })
+        .collect()
+}
+
+#[tauri::command]
+pub async fn list_languages(
+    state: State<'_, AppState>,
+) -> Result<Vec<LanguageInfo>, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    Ok(available_locales(&data_root))
+}
+
+#[tauri::command]
+pub async fn get_locale_strings(
+    lang: String,
+    state: State<'_, AppState>,
+) -> Result<serde_json::Value, String> {
+    let data_root = state.config.lock().unwrap().data_root.clone();
+    load_locale(&data_root, &lang)
+}
+
+// Dotted paths of every string in a translation map ("settings.llm.title"), skipping "_meta"
+fn locale_keys(strings: &serde_json::Value, prefix: &str, keys: &mut BTreeSet<String>) {
+    for (key, value) in strings.as_object().into_iter().flatten() {
+        if prefix.is_empty() && key == "_meta" {
+            continue;
+        }
+        let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+        match value {
+            serde_json::Value::Object(_) => locale_keys(value, &path, keys),
+            _ => {
+                keys.insert(path);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LocaleImportReport {
+    pub code: String,
+    // Keys of the English baseline this locale doesn't translate; the UI falls back to English
+    pub missing_keys: Vec<String>,
+    // Keys the English baseline doesn't have; they are kept but never shown
+    pub extra_keys: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+// Install a locale file as locales/{code}.json, where the code is the file name (e.g. fr.json)
+#[tauri::command]
+pub async fn import_locale(
+    source_path: String,
+    state: State<'_, AppState>,
+) -> Result<LocaleImportReport, String> {
+    ensure_writable(&state)?;
+    let data_root = state.config.lock().unwrap().data_root.clone();
+
+    let source = PathBuf::from(&source_path);
+    let code = source.file_stem()
+        .and_then(|stem| stem.to_str())
+        .ok_or_else(|| format!("Invalid locale file path: {}", source_path))?
+        .to_string();
+    let target_path = locale_path(&data_root, &code)
+        .map_err(|e| format!("{}; name the file after its language code, e.g. fr.json or pt-BR.json", e))?;
+
+    let content = read_resource_text(&source)
+        .map_err(|e| format!("Failed to read {}: {}", source_path, e))?;
+    let strings = parse_json_text(&content).map_err(|e| e.describe())?;
+    if !strings.is_object() {
+        return Err("A locale file must be a JSON object".to_string());
+    }
+
+    let mut keys = BTreeSet::new();
+    locale_keys(&strings, "", &mut keys);
+    if keys.is_empty() {
+        return Err("The locale file has no translations".to_string());
+    }
+    let mut baseline = BTreeSet::new();
+    locale_keys(&load_locale(&data_root, "en")?, "", &mut baseline);
+
+    let missing_keys: Vec<String> = baseline.difference(&keys).cloned().collect();
+    let extra_keys: Vec<String> = keys.difference(&baseline).cloned().collect();
+    let mut warnings = Vec::new();
+    if !missing_keys.is_empty() {
+        warnings.push(format!(
+            "{} of {} keys are untranslated and will show in English: {}",
+            missing_keys.len(), baseline.len(), missing_keys.join(", ")
+        ));
+    }
+    if !extra_keys.is_empty() {
+        warnings.push(format!("{} keys are not used by the app: {}", extra_keys.len(), extra_keys.join(", ")));
+    }
+
+    fs::create_dir_all(data_root.join("locales"))
+        .map_err(|e| format!("Failed to create locales directory: {}", e))?;
+    write_atomic(&target_path, content)?;
+
+    println!("Locale {} imported to {:?} ({} missing keys)", code, target_path, missing_keys.len());
+    Ok(LocaleImportReport { code, missing_keys, extra_keys, warnings })
+}
+
+#[tauri::command]
+pub async fn set_language(
+    language: String,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    // Validate language value
+    if load_locale(&config.data_root, &language).is_err() {
+        let available: Vec<String> = available_locales(&config.data_root).into_iter().map(|l| l.code).collect();
+        return Err(format!("Invalid language '{}'. Available: {}", language, available.join(", ")));
+    }
+
+    config.language = language.clone();
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_theme(
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let config = state.config.lock().unwrap();
+    Ok(config.theme.clone())
+}
+
+#[tauri::command]
+pub async fn get_language(
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let config = state.config.lock().unwrap();
+    Ok(config.language.clone())
+}
+
+#[tauri::command]
+pub async fn set_debug_llm(
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    config.debug_llm = enabled;
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_compress(
+    enabled: bool,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    // Applies to guides and knowledge as they are next saved
+    config.compress = enabled;
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+// Version and Update Commands
+
+const DEFAULT_UPDATE_ENDPOINT: &str = "https://api.github.com/repos/pulsar-agent-turbo/pulsar-studio/releases/latest";
+const UPDATE_CHECK_TIMEOUT_SECS: u64 = 10;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateInfo {
+    pub current_version: String,
+    pub latest_version: Option<String>,
+    pub update_available: bool,
+    pub download_url: Option<String>,
+    // Set when the endpoint could not be reached or understood
+    pub error: Option<String>,
+}
+
+// Numeric components of a version such as "v1.2.10" -> [1, 2, 10]; pre-release suffixes are ignored
+fn version_components(version: &str) -> Vec<u64> {
+    version.trim()
+        .trim_start_matches('v')
+        .split(['-', '+'])
+        .next()
+        .unwrap_or_default()
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+fn is_newer_version(candidate: &str, current: &str) -> bool {
+    let mut candidate = version_components(candidate);
+    let mut current = version_components(current);
+    let len = candidate.len().max(current.len());
+    candidate.resize(len, 0);
+    current.resize(len, 0);
+    candidate > current
+}
+
+// Fetch the latest release as (version, download URL). Accepts GitHub's release shape
+// ({tag_name, html_url}) or a plain {version, url} document.
+async fn fetch_latest_release(endpoint: &str, proxy: Option<&str>) -> Result<(String, Option<String>), String> {
+    let client = build_http_client(Duration::from_secs(UPDATE_CHECK_TIMEOUT_SECS), proxy)?;
+    let response = client.get(endpoint)
+        .header("User-Agent", format!("pulsar-studio/{}", env!("CARGO_PKG_VERSION")))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|e| format!("Could not reach {}: {}", endpoint, e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Release endpoint returned HTTP {}", response.status().as_u16()));
+    }
+
+    let body: serde_json::Value = response.json().await
+        .map_err(|e| format!("Failed to parse release info: {}", e))?;
+
+    let version = body.get("tag_name")
+        .or_else(|| body.get("version"))
+        .and_then(|v| v.as_str())
+        .ok_or("Release info has no version")?;
+    let url = body.get("html_url")
+        .or_else(|| body.get("url"))
+        .and_then(|v| v.as_str())
+        .map(String::from);
+
+    Ok((version.trim_start_matches('v').to_string(), url))
+}
+
+#[tauri::command]
+pub async fn get_app_version() -> Result<String, String> {
+    Ok(env!("CARGO_PKG_VERSION").to_string())
+}
+
+#[tauri::command]
+pub async fn check_for_updates(
+    state: State<'_, AppState>,
+) -> Result<UpdateInfo, String> {
+    let (endpoint, proxy) = {
+        let config = state.config.lock().unwrap();
+        let endpoint = config.update_endpoint.clone().unwrap_or_else(|| DEFAULT_UPDATE_ENDPOINT.to_string());
+        (endpoint, config.http_proxy.clone())
+    };
+    let current_version = env!("CARGO_PKG_VERSION").to_string();
+
+    // A failed check is reported in the result so it never blocks startup
+    match fetch_latest_release(&endpoint, proxy.as_deref()).await {
+        Ok((latest_version, download_url)) => Ok(UpdateInfo {
+            update_available: is_newer_version(&latest_version, &current_version),
+            current_version,
+            latest_version: Some(latest_version),
+            download_url,
+            error: None,
+        }),
+        Err(e) => {
+            println!("Could not check for updates: {}", e);
+            Ok(UpdateInfo {
+                current_version,
+                latest_version: None,
+                update_available: false,
+                download_url: None,
+                error: Some(format!("Could not check for updates: {}", e)),
+            })
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn set_update_endpoint(
+    endpoint: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    // An empty value falls back to the default endpoint
+    config.update_endpoint = endpoint
+        .map(|e| e.trim().to_string())
+        .filter(|e| !e.is_empty());
+
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_http_proxy(
+    url: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
+
+    // An empty value turns the proxy off
+    config.http_proxy = url
+        .filter(|u| !u.trim().is_empty())
+        .map(|u| validate_proxy_url(&u))
+        .transpose()?;
+
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn set_max_concurrent_requests(
+    n: usize,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    if n == 0 || n > MAX_CONCURRENT_REQUESTS_LIMIT {
+        return Err(format!("Maximum concurrent requests must be between 1 and {}", MAX_CONCURRENT_REQUESTS_LIMIT));
+    }
+
+    let mut config = state.config.lock().unwrap();
+    config.max_concurrent_requests = Some(n);
+    save_config_to_file(&config)?;
+    Ok(())
+}
+
+const NODE_NOT_FOUND_HINT: &str =
+    "Node.js was not found on PATH. Install Node.js 18 or newer from https://nodejs.org and restart the app";
+
+// Support bundles include logs touched within this window, each trimmed to its newest bytes
+const SUPPORT_BUNDLE_LOG_DAYS: u64 = 7;
+const SUPPORT_BUNDLE_MAX_LOG_BYTES: usize = 2 * 1024 * 1024;
+
+// Replace every known secret in `text`, and redact secret-named fields of JSON lines
+fn scrub_support_text(text: &str, secrets: &[String]) -> String {
+    let mut scrubbed: String = text.lines()
+        .map(|line| match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(mut value) if value.is_object() => {
+                redact_secrets(&mut value);
+                value.to_string()
+            }
+            _ => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    for secret in secrets {
+        scrubbed = scrubbed.replace(secret.as_str(), "***");
+    }
+    scrubbed
+}
+
+// The newest `max` bytes of a log, starting at a line boundary so no secret is cut in half
+// (a partial key wouldn't be recognized by the scrubber)
+fn log_tail(bytes: &[u8], max: usize) -> &[u8] {
+    if bytes.len() <= max {
+        return bytes;
+    }
+    let tail = &bytes[bytes.len() - max..];
+    match tail.iter().position(|b| *b == b'\n') {
+        Some(newline) => &tail[newline + 1..],
+        None => &[],
+    }
+}
+
+// Recently modified files under logs/, relative to the logs directory
+fn recent_log_files(logs_dir: &Path, max_age: Duration) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+    let mut pending = vec![logs_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+                continue;
+            }
+            let recent = entry.metadata().ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age <= max_age);
+            if let (true, Ok(relative)) = (recent, path.strip_prefix(logs_dir)) {
+                files.push((relative.to_string_lossy().replace('\\', "/"), path));
+            }
+        }
+    }
+    files.sort();
+    files
+}
+
+fn write_support_bundle(config: &AppConfig, dest: &Path) -> Result<(), String> {
+    let file = fs::File::create(dest)
+        .map_err(|e| format!("Failed to create support bundle: {}", e))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    // Every entry goes through the scrubber so no key can slip into the archive
+    let secrets: Vec<String> = config.llm_providers.iter()
+        .filter_map(|p| p.api_key.clone())
+        .filter(|key| !key.trim().is_empty())
+        .collect();
+    let mut add = |name: &str, text: &str| add_bundle_file(&mut zip, name, scrub_support_text(text, &secrets).as_bytes());
+    let to_json = |value: &serde_json::Value| serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize support data: {}", e));
+
+    let mut config_json = serde_json::to_value(config)
+        .map_err(|e| format!("Failed to serialize config: {}", e))?;
+    redact_secrets(&mut config_json);
+    add("config.json", &to_json(&config_json)?)?;
+
+    add("system.json", &to_json(&serde_json::json!({
+        "app_version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "os_family": std::env::consts::FAMILY,
+        "arch": std::env::consts::ARCH,
+        "created_at": chrono::Utc::now().to_rfc3339()
+    }))?)?;
+
+    let mut statuses = serde_json::Map::new();
+    for (name, dir) in resource_index(&config.data_root.join("actions"), Some("meta.json")) {
+        let status = fs::read_to_string(dir.join("status.json")).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or(serde_json::Value::Null);
+        statuses.insert(name, status);
+    }
+    add("action_statuses.json", &to_json(&serde_json::Value::Object(statuses))?)?;
+
+    let max_age = Duration::from_secs(SUPPORT_BUNDLE_LOG_DAYS * 24 * 60 * 60);
+    for (name, path) in recent_log_files(&config.data_root.join("logs"), max_age) {
+        let Ok(bytes) = fs::read(&path) else {
+            continue;
+        };
+        let tail = log_tail(&bytes, SUPPORT_BUNDLE_MAX_LOG_BYTES);
+        add(&format!("logs/{}", name), &String::from_utf8_lossy(tail))?;
+    }
+
+    zip.finish()
+        .map_err(|e| format!("Failed to finish support bundle: {}", e))?;
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn validate_action_directory(
-    path: String,
-) -> Result<serde_json::Value, String> {
-    let result = validate_action_directory_internal(&path)?;
-
-    let json_result = serde_json::json!({
-        "is_valid": result.is_valid,
-        "error": result.error,
-        "missing_files": result.missing_files
-    });
+pub async fn create_support_bundle(
+    dest_path: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let config = state.config.lock().unwrap().clone();
+    let dest = PathBuf::from(&dest_path);
+    let file_name = dest.file_name()
+        .and_then(|n| n.to_str())
+        .ok_or("Invalid bundle path")?;
+
+    // Build the bundle next to `dest` and rename it into place, so a failure never leaves a
+    // half-written bundle or removes a file that was already there
+    let temp_path = dest.with_file_name(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+    let result = write_support_bundle(&config, &temp_path)
+        .and_then(|_| fs::rename(&temp_path, &dest)
+            .map_err(|e| format!("Failed to write support bundle {:?}: {}", dest, e)));
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
 
-    Ok(json_result)
+    println!("Support bundle written: {:?}", dest);
+    Ok(dest.to_string_lossy().to_string())
 }
 
-struct ValidationResult {
-    is_valid: bool,
-    error: Option<String>,
-    missing_files: Vec<String>,
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AgentRuntimeInfo {
+    pub node_path: String,
+    pub node_version: String,
+    pub script_path: String,
 }
 
-fn validate_action_directory_internal(path: &str) -> Result<ValidationResult, String> {
-    let path_buf = PathBuf::from(path);
-
-    if !path_buf.exists() || !path_buf.is_dir() {
-        return Ok(ValidationResult {
-            is_valid: false,
-            error: Some("Directory does not exist".to_string()),
-            missing_files: vec![],
-        });
-    }
-
-    let mut missing_files = Vec::new();
+// First executable named `name` on PATH
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    let candidates: Vec<String> = if cfg!(windows) {
+        vec![format!("{}.exe", name), format!("{}.cmd", name), name.to_string()]
+    } else {
+        vec![name.to_string()]
+    };
+    std::env::split_paths(&path)
+        .flat_map(|dir| candidates.iter().map(move |c| dir.join(c)))
+        .find(|p| p.is_file())
+}
 
-    // Check for required files
-    let required_files = ["meta.json", "perform.js"];
-    for file in &required_files {
-        if !path_buf.join(file).exists() {
-            missing_files.push(file.to_string());
-        }
+#[tauri::command]
+pub async fn check_agent_runtime(state: State<'_, AppState>) -> Result<AgentRuntimeInfo, String> {
+    let agent_script = state.config.lock().unwrap().agent_script.clone();
+
+    let node_path = find_on_path("node").ok_or(NODE_NOT_FOUND_HINT)?;
+
+    let cwd = std::env::temp_dir();
+    let output = run_node(&["--version"], &cwd, Duration::from_secs(NODE_CHECK_TIMEOUT_SECS)).await?
+        .ok_or(NODE_NOT_FOUND_HINT)?;
+    if !output.status.success() {
+        return Err(format!(
+            "node --version failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
     }
+    let node_version = String::from_utf8_lossy(&output.stdout).trim().to_string();
 
-    if !missing_files.is_empty() {
-        return Ok(ValidationResult {
-            is_valid: false,
-            error: Some(format!("Missing required files: {}", missing_files.join(", "))),
-            missing_files,
-        });
+    let script_path = agent_script
+        .ok_or("No agent script configured. Set the agent script path in settings")?;
+    if !script_path.is_file() {
+        return Err(format!("Agent script not found: {}", script_path.display()));
     }
 
-    // Validate meta.json structure
-    let meta_path = path_buf.join("meta.json");
-    let meta_content = fs::read_to_string(&meta_path)
-        .map_err(|e| format!("Failed to read meta.json: {}", e))?;
+    Ok(AgentRuntimeInfo {
+        node_path: node_path.to_string_lossy().to_string(),
+        node_version,
+        script_path: script_path.to_string_lossy().to_string(),
+    })
+}
 
-    let meta: serde_json::Value = serde_json::from_str(&meta_content)
-        .map_err(|e| format!("Failed to parse meta.json: {}", e))?;
+#[tauri::command]
+pub async fn set_agent_script(
+    script_path: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<(), String> {
+    ensure_writable(&state)?;
+    let mut config = state.config.lock().unwrap();
 
-    // Validate required meta fields
-    let validation_error = validate_action_meta_structure(&meta);
-    if let Some(error) = validation_error {
-        return Ok(ValidationResult {
-            is_valid: false,
-            error: Some(error),
-            missing_files: vec![],
-        });
-    }
+    // An empty value clears the setting
+    config.agent_script = script_path
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .map(PathBuf::from);
 
-    Ok(ValidationResult {
-        is_valid: true,
-        error: None,
-        missing_files: vec![],
-    })
+    // Save to file
+    save_config_to_file(&config)?;
+    Ok(())
 }
 
-fn validate_action_meta_structure(meta: &serde_json::Value) -> Option<String> {
-    // Check required fields
-    if !meta.get("name").and_then(|v| v.as_str()).is_some() {
-        return Some("Missing or invalid 'name' field".to_string());
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_provider(alias: &str) -> LLMProvider {
+        LLMProvider {
+            name: alias.to_string(),
+            provider: "openai_compatible".to_string(),
+            base_url: "https://api.openai.com/v1".to_string(),
+            model: "gpt-4".to_string(),
+            api_key: None,
+            temperature: 0.7,
+            max_tokens: Some(4000),
+            think: false,
+            alias: alias.to_string(),
+            headers: None,
+            locked: false,
+        }
     }
 
-    if !meta.get("description").and_then(|v| v.as_str()).is_some() {
-        return Some("Missing or invalid 'description' field".to_string());
+    fn empty_config() -> AppConfig {
+        AppConfig {
+            llm_providers: vec![],
+            ..AppConfig::default()
+        }
     }
 
-    if !meta.get("arguments").and_then(|v| v.as_array()).is_some() {
-        return Some("Missing or invalid 'arguments' array".to_string());
-    }
+    #[test]
+    fn llm_debug_log_never_contains_api_key() {
+        let secret = "sk-test-secret-1234567890";
+        let header_secret = "hdr-test-secret-0987654321";
+        let log_path = std::env::temp_dir()
+            .join(format!("pulsar-llm-log-{}", uuid::Uuid::new_v4()))
+            .join("llm.jsonl");
+        let client = reqwest::Client::new();
+
+        for kind in SUPPORTED_PROVIDER_KINDS {
+            let mut provider = test_provider(kind);
+            provider.provider = kind.to_string();
+            provider.api_key = Some(secret.to_string());
+            provider.headers = Some(HashMap::from([("X-Gateway-Token".to_string(), header_secret.to_string())]));
+
+            let request = build_llm_request(&client, &provider, "hello", Some(8))
+                .unwrap()
+                .build()
+                .unwrap();
+            write_llm_log(&log_path, llm_request_log_entry(&provider.alias, &request), Some(secret));
+        }
+
+        // An error body echoing the key must be scrubbed too
+        write_llm_log(&log_path, serde_json::json!({
+            "direction": "response",
+            "body": format!("invalid key {}", secret),
+            "api_key": secret
+        }), Some(secret));
 
-    if !meta.get("timeout_sec").and_then(|v| v.as_u64()).is_some() {
-        return Some("Missing or invalid 'timeout_sec' field".to_string());
+        let logged = fs::read_to_string(&log_path).unwrap();
+        let _ = fs::remove_dir_all(log_path.parent().unwrap());
+
+        assert_eq!(logged.lines().count(), SUPPORTED_PROVIDER_KINDS.len() + 1);
+        assert!(!logged.contains(secret));
+        assert!(!logged.contains(header_secret));
+        assert!(logged.contains("***"));
     }
 
-    // Validate arguments structure
-    if let Some(arguments) = meta.get("arguments").and_then(|v| v.as_array()) {
-        for (index, arg) in arguments.iter().enumerate() {
-            let arg_path = format!("arguments[{}]", index);
+    #[test]
+    fn alias_uniqueness_is_case_insensitive() {
+        let mut config = empty_config();
 
-            if !arg.get("name").and_then(|v| v.as_str()).is_some() {
-                return Some(format!("Missing or invalid '{}.name' field", arg_path));
-            }
+        add_provider_to_config(&mut config, test_provider("gpt-4")).unwrap();
+        let result = add_provider_to_config(&mut config, test_provider("GPT-4"));
 
-            if !arg.get("type").and_then(|v| v.as_str()).is_some() {
-                return Some(format!("Missing or invalid '{}.type' field", arg_path));
-            }
+        assert!(result.is_err());
+        assert_eq!(config.llm_providers.len(), 1);
+    }
 
-            if !arg.get("description").and_then(|v| v.as_str()).is_some() {
-                return Some(format!("Missing or invalid '{}.description' field", arg_path));
-            }
+    #[test]
+    fn alias_is_trimmed_before_storing() {
+        let mut config = empty_config();
 
-            if !arg.get("required").and_then(|v| v.as_bool()).is_some() {
-                return Some(format!("Missing or invalid '{}.required' field", arg_path));
-            }
-        }
+        add_provider_to_config(&mut config, test_provider("  local-llama ")).unwrap();
+
+        assert_eq!(config.llm_providers[0].alias, "local-llama");
+        assert!(add_provider_to_config(&mut config, test_provider("Local-Llama")).is_err());
     }
 
-    None
-}
+    #[test]
+    fn imported_providers_are_not_locked_outside_admin_mode() {
+        let mut config = empty_config();
+        config.llm_providers.push(test_provider("shared"));
 
-fn copy_directory(source: &str, target: &str) -> Result<(), String> {
-    let source_path = PathBuf::from(source);
-    let target_path = PathBuf::from(target);
+        let mut added = test_provider("team");
+        added.locked = true;
+        let mut overwrite = test_provider("shared");
+        overwrite.locked = true;
+        merge_providers(&mut config, vec![added, overwrite], ImportStrategy::Overwrite, false);
 
-    fs::create_dir_all(&target_path)
-        .map_err(|e| format!("Failed to create target directory: {}", e))?;
+        assert_eq!(config.llm_providers.len(), 2);
+        assert!(config.llm_providers.iter().all(|provider| !provider.locked));
+    }
 
-    for entry in fs::read_dir(&source_path)
-        .map_err(|e| format!("Failed to read source directory: {}", e))?
-    {
-        let entry = entry
-            .map_err(|e| format!("Failed to read directory entry: {}", e))?;
-        let source_file = entry.path();
-        let target_file = target_path.join(entry.file_name());
+    #[test]
+    fn base_url_trailing_slash_is_removed() {
+        assert_eq!(clean_base_url("https://api.openai.com/v1/").unwrap(), "https://api.openai.com/v1");
+    }
 
-        if source_file.is_dir() {
-            copy_directory(source_file.to_str().unwrap(), target_file.to_str().unwrap())?;
-        } else {
-            fs::copy(&source_file, &target_file)
-                .map_err(|e| format!("Failed to copy file: {}", e))?;
-        }
+    #[test]
+    fn base_url_without_scheme_gets_https() {
+        assert_eq!(clean_base_url("api.openai.com").unwrap(), "https://api.openai.com");
+        assert_eq!(clean_base_url("localhost:11434").unwrap(), "http://localhost:11434");
     }
 
-    Ok(())
-}
+    #[test]
+    fn base_url_endpoint_suffix_is_stripped() {
+        assert_eq!(
+            clean_base_url("https://api.openai.com/v1/chat/completions").unwrap(),
+            "https://api.openai.com/v1"
+        );
+        assert_eq!(clean_base_url("http://localhost:11434/api/chat/").unwrap(), "http://localhost:11434");
+    }
 
-#[tauri::command]
-pub async fn delete_action(
-    action_name: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let actions_dir = data_root.join("actions");
-    let action_dir = actions_dir.join(&action_name);
+    #[test]
+    fn base_url_rejects_garbage() {
+        assert!(clean_base_url("   ").is_err());
+        assert!(clean_base_url("ftp://example.com").is_err());
+        assert!(clean_base_url("https://").is_err());
+    }
 
-    // Validate action name to prevent directory traversal
-    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
-        return Err("Invalid action name".to_string());
+    fn names(used: &BTreeSet<String>) -> Vec<&str> {
+        used.iter().map(String::as_str).collect()
     }
 
-    if !action_dir.exists() {
-        return Err(format!("Action '{}' not found", action_name));
+    #[test]
+    fn js_word_offsets_match_whole_identifiers_only() {
+        let source = "args margs args_ $args args";
+        assert_eq!(js_word_offsets(source, "args").collect::<Vec<_>>(), vec![0, 23]);
     }
 
-    // Remove directory recursively
-    fs::remove_dir_all(&action_dir)
-        .map_err(|e| format!("Failed to delete action directory: {}", e))?;
+    #[test]
+    fn matching_bracket_skips_nested_pairs() {
+        let source = "{ a: { b: {} }, c }";
+        assert_eq!(matching_bracket(source, 0, true), Some(source.len() - 1));
+        assert_eq!(matching_bracket(source, source.len() - 1, false), Some(0));
+        assert_eq!(matching_bracket(source, 5, true), Some(13));
+        assert_eq!(matching_bracket("{ a: {", 0, true), None);
+    }
 
-    println!("Action deleted: {:?}", action_dir);
-    Ok(())
-}
+    #[test]
+    fn destructured_names_reads_renames_defaults_and_rest() {
+        let (mut used, mut dynamic) = (BTreeSet::new(), false);
+        destructured_names(" path, mode: renamed, retries = 3 ", &mut used, &mut dynamic);
+        assert_eq!(names(&used), vec!["mode", "path", "retries"]);
+        assert!(!dynamic);
 
-#[tauri::command]
-pub async fn update_action_status(
-    action_name: String,
-    status: String,
-    error_message: Option<String>,
-    execution_id: Option<String>,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let actions_dir = data_root.join("actions");
-    let action_dir = actions_dir.join(&action_name);
-    let status_path = action_dir.join("status.json");
+        destructured_names("...rest", &mut used, &mut dynamic);
+        assert!(dynamic);
+    }
 
-    // Validate action name
-    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
-        return Err("Invalid action name".to_string());
+    #[test]
+    fn perform_argument_usage_reads_destructured_arguments() {
+        let source = r#"
+            class Perform {
+                async run({ path, options: { depth }, retries = 3 }) {
+                    const { mode, target: dest } = args;
+                    return path + dest;
+                }
+            }
+        "#;
+        let (used, dynamic) = perform_argument_usage(source);
+        assert_eq!(names(&used), vec!["mode", "options", "path", "retries", "target"]);
+        assert!(!dynamic);
     }
 
-    // Load existing status or create new one
-    let mut current_status: ActionStatus = if status_path.exists() {
-        let content = fs::read_to_string(&status_path)
-            .map_err(|e| format!("Failed to read action status: {}", e))?;
-        serde_json::from_str(&content)
-            .map_err(|e| format!("Failed to parse action status: {}", e))?
-    } else {
-        ActionStatus {
-            status: "healthy".to_string(),
-            last_error: None,
-            error_count: 0,
-            last_success: None,
-        }
-    };
+    #[test]
+    fn perform_argument_usage_ignores_strings_and_comments() {
+        let source = r#"
+            // args.commented
+            /* args.blocked, args["also"] */
+            const perform = async (params) => {
+                console.log("args.quoted", 'params.single { }');
+                const label = `${params.interpolated} args.templated`;
+                return params.real + params["bracketed"];
+            };
+        "#;
+        let (used, dynamic) = perform_argument_usage(source);
+        assert_eq!(names(&used), vec!["bracketed", "interpolated", "real"]);
+        assert!(!dynamic);
+    }
 
-    // Update status
-    current_status.status = status.clone();
+    #[test]
+    fn perform_argument_usage_bails_out_on_dynamic_access() {
+        let (used, dynamic) = perform_argument_usage("function perform(input) { return input?.['name'] + input[key]; }");
+        assert_eq!(names(&used), vec!["name"]);
+        assert!(dynamic);
 
-    if status == "error" {
-        current_status.error_count += 1;
-        if let (Some(msg), Some(exec_id)) = (error_message, execution_id) {
-            current_status.last_error = Some(ActionError {
-                message: msg,
-                timestamp: chrono::Utc::now().to_rfc3339(),
-                execution_id: exec_id,
-            });
-        }
-    } else if status == "healthy" {
-        current_status.last_success = Some(chrono::Utc::now().to_rfc3339());
+        let (_, dynamic) = perform_argument_usage("async run({ first, ...rest }) {}");
+        assert!(dynamic);
     }
 
-    // Save updated status
-    let content = serde_json::to_string_pretty(&current_status)
-        .map_err(|e| format!("Failed to serialize action status: {}", e))?;
+    #[test]
+    fn model_limits_match_whole_name_segments() {
+        let context = |model: &str| find_model_limits(model).map(|limits| limits.context_window);
+        assert_eq!(context("gpt-4"), Some(8_192));
+        assert_eq!(context("gpt-4-0613"), Some(8_192));
+        assert_eq!(context("GPT-4.1"), Some(1_047_576));
+        assert_eq!(context("gpt-4.1-2025-04-14"), Some(1_047_576));
+        assert_eq!(context("gpt-4o-2024-08-06"), Some(128_000));
+        assert_eq!(context("o1-preview"), Some(128_000));
+        assert_eq!(context("o1-2024-12-17"), Some(200_000));
+        assert_eq!(context("claude-3-5-sonnet@20240620"), Some(200_000));
+        assert_eq!(context("o10"), None);
+        assert_eq!(context("gpt-4.5-preview"), None);
+    }
 
-    fs::write(&status_path, content)
-        .map_err(|e| format!("Failed to write action status: {}", e))?;
+    #[test]
+    fn support_bundle_contains_no_keys() {
+        use std::io::Read;
+
+        let data_root = std::env::temp_dir().join(format!("pulsar-support-bundle-{}", uuid::Uuid::new_v4()));
+        let logs_dir = data_root.join("logs");
+        fs::create_dir_all(&logs_dir).unwrap();
+        let secret = "sk-bundle-secret-TAILMARK";
+        let header_secret = "hdr-bundle-secret-4242";
+
+        let mut provider = test_provider("prod");
+        provider.api_key = Some(secret.to_string());
+        provider.headers = Some(HashMap::from([("X-Gateway-Token".to_string(), header_secret.to_string())]));
+        let config = AppConfig {
+            llm_providers: vec![provider],
+            data_root: data_root.clone(),
+            ..AppConfig::default()
+        };
+
+        // The trimmed log's cut lands inside the key on its first line
+        let first_line = format!("leaked {}\n", secret);
+        let filler_len = SUPPORT_BUNDLE_MAX_LOG_BYTES - "LMARK\n".len();
+        let filler = "y".repeat(99) + "\n";
+        let mut log = first_line + &filler.repeat(filler_len / filler.len());
+        log.push_str(&"z".repeat(filler_len % filler.len()));
+        fs::write(logs_dir.join("app.log"), &log).unwrap();
+        fs::write(logs_dir.join("llm.jsonl"), format!(
+            "{}\n{}\n",
+            serde_json::json!({ "headers": { "x-gateway-token": header_secret }, "api_key": secret }),
+            serde_json::json!({ "body": format!("invalid key {}", secret) })
+        )).unwrap();
+
+        let dest = data_root.join("bundle.zip");
+        write_support_bundle(&config, &dest).unwrap();
+        let mut archive = open_bundle(&dest).unwrap();
+        let mut contents = String::new();
+        for i in 0..archive.len() {
+            archive.by_index(i).unwrap().read_to_string(&mut contents).unwrap();
+        }
+        let _ = fs::remove_dir_all(&data_root);
 
-    println!("Action status updated: {} -> {}", action_name, status);
-    Ok(())
-}
+        assert!(contents.contains("yyyy"), "trimmed log missing from bundle");
+        assert!(!contents.contains(secret));
+        assert!(!contents.contains("LMARK"));
+        assert!(!contents.contains(header_secret));
+    }
 
-#[tauri::command]
-pub async fn get_action_status(
-    action_name: String,
-    state: State<'_, AppState>,
-) -> Result<ActionStatus, String> {
-    let data_root = &state.config.lock().unwrap().data_root;
-    let actions_dir = data_root.join("actions");
-    let action_dir = actions_dir.join(&action_name);
-    let status_path = action_dir.join("status.json");
+    #[test]
+    fn config_snapshots_never_keep_credentials() {
+        let data_root = std::env::temp_dir().join(format!("pulsar-config-history-{}", uuid::Uuid::new_v4()));
+        let history_dir = data_root.join(CONFIG_HISTORY_DIR);
+        fs::create_dir_all(&history_dir).unwrap();
+        let secret = "sk-history-secret-1234";
+        let header_secret = "hdr-history-secret-5678";
+
+        let mut provider = test_provider("prod");
+        provider.api_key = Some(secret.to_string());
+        provider.headers = Some(HashMap::from([("X-Gateway-Token".to_string(), header_secret.to_string())]));
+        let config = AppConfig {
+            llm_providers: vec![provider],
+            data_root: data_root.clone(),
+            ..AppConfig::default()
+        };
+        let content = serde_json::to_string_pretty(&config).unwrap();
+        // Written by an older build, before snapshots were stripped
+        fs::write(history_dir.join("20240101T000000.000Z.json"), &content).unwrap();
+
+        let mut edited = config.clone();
+        edited.llm_providers[0].model = "gpt-4o".to_string();
+        write_config_snapshot(&data_root, &serde_json::to_string_pretty(&edited).unwrap()).unwrap();
+
+        let timestamps = config_snapshot_timestamps(&data_root);
+        let history: String = timestamps.iter()
+            .map(|timestamp| fs::read_to_string(history_dir.join(format!("{}.json", timestamp))).unwrap())
+            .collect();
+        let mut restored = load_config_snapshot(&config, &timestamps[0]).unwrap();
+        keep_provider_credentials(&config.llm_providers, &mut restored.llm_providers);
+        let _ = fs::remove_dir_all(&data_root);
+
+        assert_eq!(timestamps.len(), 2);
+        assert!(!history.contains(secret));
+        assert!(!history.contains(header_secret));
+        assert_eq!(restored.llm_providers[0].api_key.as_deref(), Some(secret));
+        assert!(restored.llm_providers[0].headers.is_some());
+    }
 
-    // Validate action name
-    if action_name.contains("..") || action_name.contains("/") || action_name.contains("\\") {
-        return Err("Invalid action name".to_string());
+    #[test]
+    fn restoring_a_snapshot_keeps_locked_providers() {
+        let mut locked = test_provider("Prod");
+        locked.locked = true;
+        locked.model = "gpt-4o".to_string();
+        let mut stale = test_provider("prod");
+        stale.model = "gpt-3.5-turbo".to_string();
+        let mut removed = test_provider("Audit");
+        removed.locked = true;
+
+        let mut restored = vec![stale, test_provider("scratch")];
+        keep_locked_providers(&[locked.clone(), removed.clone(), test_provider("other")], &mut restored);
+
+        let aliases: Vec<&str> = restored.iter().map(|provider| provider.alias.as_str()).collect();
+        assert_eq!(aliases, vec!["Prod", "scratch", "Audit"]);
+        assert_eq!(restored[0].model, "gpt-4o");
+        assert!(restored[0].locked && restored[2].locked);
     }
 
-    if !status_path.exists() {
-        // Return default healthy status if no status file exists
-        return Ok(ActionStatus {
-            status: "healthy".to_string(),
-            last_error: None,
-            error_count: 0,
-            last_success: Some(chrono::Utc::now().to_rfc3339()),
-        });
+    #[test]
+    fn request_limiter_resizes_without_losing_in_flight_permits() {
+        let limiter = RequestLimiter::default();
+        let semaphore = limiter.semaphore(2);
+        let first = semaphore.clone().try_acquire_owned().unwrap();
+        let second = semaphore.clone().try_acquire_owned().unwrap();
+
+        // Shrinking below what's in flight admits nothing new until enough requests finish
+        assert_eq!(limiter.semaphore(1).available_permits(), 0);
+        drop(first);
+        assert_eq!(limiter.semaphore(1).available_permits(), 0);
+        drop(second);
+        assert_eq!(limiter.semaphore(1).available_permits(), 1);
+
+        assert_eq!(limiter.semaphore(3).available_permits(), 3);
+        assert!(Arc::ptr_eq(&semaphore, &limiter.semaphore(3)));
+        // A zero limit never reaches the semaphore
+        assert_eq!(limiter.semaphore(0).available_permits(), 1);
     }
 
-    let content = fs::read_to_string(&status_path)
-        .map_err(|e| format!("Failed to read action status: {}", e))?;
+    #[test]
+    fn create_thread_leaves_no_file_when_write_fails() {
+        let data_root = std::env::temp_dir().join(format!("pulsar-create-thread-{}", uuid::Uuid::new_v4()));
+        let config = AppConfig {
+            llm_providers: vec![test_provider("planner")],
+            data_root: data_root.clone(),
+            ..AppConfig::default()
+        };
+        let request = CreateThreadRequest {
+            name: "Broken".to_string(),
+            working_dir: data_root.clone(),
+            planner_llm_alias: Some("planner".to_string()),
+            decider_llm_alias: Some("planner".to_string()),
+            selected_knowledge: vec![],
+            selected_guides: vec![],
+            selected_actions: vec![],
+            execution_mode: ExecutionMode::default(),
+        };
+
+        // A non-empty directory squatting on the save path makes the final rename fail
+        let thread_id = uuid::Uuid::new_v4().to_string();
+        let blocker = data_root.join("saves").join(format!("{}.json", thread_id));
+        fs::create_dir_all(blocker.join("occupied")).unwrap();
+
+        let result = create_thread_internal(&config, request, thread_id);
+        let leftovers: Vec<String> = fs::read_dir(data_root.join("saves")).unwrap()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .collect();
+        let blocker_is_dir = blocker.is_dir();
+        let _ = fs::remove_dir_all(&data_root);
+
+        assert!(result.unwrap_err().starts_with("Failed to save thread"));
+        assert!(blocker_is_dir);
+        assert_eq!(leftovers.len(), 1, "stray files in saves/: {:?}", leftovers);
+    }
 
-    let status: ActionStatus = serde_json::from_str(&content)
-        .map_err(|e| format!("Failed to parse action status: {}", e))?;
+    #[test]
+    fn create_thread_stores_the_provider_alias_spelling() {
+        let data_root = std::env::temp_dir().join(format!("pulsar-thread-alias-{}", uuid::Uuid::new_v4()));
+        let config = AppConfig {
+            llm_providers: vec![test_provider("Planner")],
+            data_root: data_root.clone(),
+            ..AppConfig::default()
+        };
+        let request = CreateThreadRequest {
+            name: "Casing".to_string(),
+            working_dir: data_root.clone(),
+            planner_llm_alias: Some("PLANNER".to_string()),
+            decider_llm_alias: Some("planner".to_string()),
+            selected_knowledge: vec![],
+            selected_guides: vec![],
+            selected_actions: vec![],
+            execution_mode: ExecutionMode::default(),
+        };
+
+        let result = create_thread_internal(&config, request, uuid::Uuid::new_v4().to_string());
+        let _ = fs::remove_dir_all(&data_root);
+
+        let thread_config = result.unwrap().config.unwrap();
+        assert_eq!(thread_config.planner_llm_alias, "Planner");
+        assert_eq!(thread_config.decider_llm_alias, "Planner");
+    }
 
-    Ok(status)
-}
+    #[test]
+    fn concurrent_action_error_updates_are_not_lost() {
+        let data_root = std::env::temp_dir().join(format!("pulsar-action-status-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(data_root.join("actions").join("flaky")).unwrap();
+        let locks = ActionLocks::default();
+        let updates = 50;
+
+        std::thread::scope(|scope| {
+            for i in 0..updates {
+                let (locks, data_root) = (&locks, &data_root);
+                scope.spawn(move || {
+                    record_action_status(locks, data_root, "flaky", "error", Some(format!("failure {}", i)), Some(i.to_string()))
+                        .unwrap();
+                });
+            }
+        });
 
-#[tauri::command]
-pub async fn set_theme(
-    theme: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let mut config = state.config.lock().unwrap();
+        let content = fs::read_to_string(data_root.join("actions").join("flaky").join("status.json")).unwrap();
+        let _ = fs::remove_dir_all(&data_root);
+        let status: ActionStatus = serde_json::from_str(&content).unwrap();
 
-    // Validate theme value
-    if !["light", "dark", "system"].contains(&theme.as_str()) {
-        return Err(format!("Invalid theme '{}'. Must be 'light', 'dark', or 'system'", theme));
+        assert_eq!(status.error_count, updates);
+        assert_eq!(status.status, "error");
     }
 
-    config.theme = theme.clone();
-
-    // Save to file
-    save_config_to_file(&config)?;
-    Ok(())
-}
-
-#[tauri::command]
-pub async fn set_language(
-    language: String,
-    state: State<'_, AppState>,
-) -> Result<(), String> {
-    let mut config = state.config.lock().unwrap();
+    #[tokio::test]
+    async fn action_output_is_logged_as_it_is_read() {
+        let log_path = std::env::temp_dir()
+            .join(format!("pulsar-run-log-{}", uuid::Uuid::new_v4()))
+            .join("run.jsonl");
+        let output: &[u8] = b"first\r\nsecond\n\npartial line cut";
+
+        let (kept, truncated) = read_capped(output, 24, log_path.clone(), "stdout").await;
+        let logged = fs::read_to_string(&log_path).unwrap();
+        let _ = fs::remove_dir_all(log_path.parent().unwrap());
+
+        assert_eq!(kept, &output[..24]);
+        assert!(truncated);
+        let messages: Vec<String> = logged.lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .inspect(|entry| assert_eq!(entry["stream"], "stdout"))
+            .map(|entry| entry["message"].as_str().unwrap().to_string())
+            .collect();
+        assert_eq!(messages, vec!["first", "second", "partial l"]);
+    }
 
-    // Validate language value
-    if !["en", "zh"].contains(&language.as_str()) {
-        return Err(format!("Invalid language '{}'. Must be 'en' or 'zh'", language));
+    #[tokio::test]
+    async fn bom_prefixed_guide_file_is_read() {
+        let guides_dir = std::env::temp_dir().join(format!("pulsar-bom-guide-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&guides_dir).unwrap();
+        let guide = serde_json::json!({
+            "meta": { "name": "Windows guide", "version": "1.0.0" },
+            "entries": [{ "name": "Setup", "description": "Saved by Notepad", "plan": ["Open the file"] }]
+        });
+        let bom_path = guides_dir.join("windows.json");
+        fs::write(&bom_path, format!("\u{feff}{}", guide)).unwrap();
+        let latin1_path = guides_dir.join("latin1.json");
+        fs::write(&latin1_path, b"{\"meta\": {\"name\": \"Caf\xe9\"}}").unwrap();
+
+        let listed = read_json_files(vec![bom_path.clone()], "guide").await;
+        let loaded = read_resource_text(&bom_path);
+        let latin1 = read_resource_text(&latin1_path);
+        let _ = fs::remove_dir_all(&guides_dir);
+
+        assert_eq!(listed.unwrap(), vec![guide.clone()]);
+        let loaded: serde_json::Value = serde_json::from_str(&loaded.unwrap()).unwrap();
+        assert!(validate_guide_structure(&loaded).is_ok());
+        let error = latin1.unwrap_err().to_string();
+        assert!(error.contains("latin1.json is not valid UTF-8"), "unexpected error: {}", error);
     }
 
-    config.language = language.clone();
+    #[tokio::test]
+    async fn http_client_sends_requests_through_configured_proxy() {
+        use std::io::{Read, Write};
+
+        // A fake proxy that records the first request line and answers 200
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let proxy_url = format!("http://{}", listener.local_addr().unwrap());
+        let proxy = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0u8; 1024];
+            let read = stream.read(&mut buffer).unwrap();
+            stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n").unwrap();
+            String::from_utf8_lossy(&buffer[..read]).lines().next().unwrap_or_default().to_string()
+        });
 
-    // Save to file
-    save_config_to_file(&config)?;
-    Ok(())
-}
+        let client = build_http_client(Duration::from_secs(5), Some(&proxy_url)).unwrap();
+        let status = client.get("http://provider.invalid/v1/models").send().await.unwrap().status();
 
-#[tauri::command]
-pub async fn get_theme(
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let config = state.config.lock().unwrap();
-    Ok(config.theme.clone())
+        assert!(status.is_success());
+        assert_eq!(proxy.join().unwrap(), "GET http://provider.invalid/v1/models HTTP/1.1");
+        assert!(build_http_client(Duration::from_secs(5), Some("socks5://127.0.0.1:1080")).is_err());
+    }
 }
-
-#[tauri::command]
-pub async fn get_language(
-    state: State<'_, AppState>,
-) -> Result<String, String> {
-    let config = state.config.lock().unwrap();
-    Ok(config.language.clone())
-}
\ No newline at end of file
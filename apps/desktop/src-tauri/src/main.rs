@@ -4,7 +4,9 @@
 mod commands;
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
 use std::sync::Mutex;
 use tauri::{State, Manager};
 
@@ -14,20 +16,71 @@ pub use commands::*;
 #[derive(Debug)]
 pub struct AppState {
     pub config: Mutex<AppConfig>,
+    // Set at startup when data_root can't be written; mutating commands then refuse up front
+    pub read_only: AtomicBool,
+    pub action_locks: commands::ActionLocks,
+    // probe_tool_support results by provider kind, endpoint and model
+    pub tool_support: Mutex<HashMap<String, bool>>,
+    pub running_actions: commands::RunningActions,
+    pub request_limiter: commands::RequestLimiter,
+    // Result of the latest data integrity scan; the startup scan finishes before the UI can listen
+    pub integrity_report: Mutex<Option<commands::IntegrityReport>>,
 }
 
+// Shape version of configs.json; bump it and add a migration step when the layout changes
+pub const CONFIG_VERSION: u32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
+    // Files written before versioning have no config_version and load as version 0
+    #[serde(default)]
+    pub config_version: u32,
     pub llm_providers: Vec<LLMProvider>,
     pub data_root: PathBuf,
     pub theme: String,
     pub language: String,
+    // Write provider requests/responses (secrets redacted) to logs/llm.jsonl
+    #[serde(default)]
+    pub debug_llm: bool,
+    // User overrides for the built-in model price table
+    #[serde(default)]
+    pub model_prices: Vec<ModelPrice>,
+    // Release endpoint queried by check_for_updates; None uses the project's GitHub releases
+    #[serde(default)]
+    pub update_endpoint: Option<String>,
+    // Pre-fill for new threads; create_thread uses these when the request omits an alias
+    #[serde(default)]
+    pub default_planner_alias: Option<String>,
+    #[serde(default)]
+    pub default_decider_alias: Option<String>,
+    // Save guides and knowledge gzip-compressed as .json.gz
+    #[serde(default)]
+    pub compress: bool,
+    // Entry script of the Node.js agent runtime, checked by check_agent_runtime
+    #[serde(default)]
+    pub agent_script: Option<PathBuf>,
+    // Proxy for every outbound HTTP(S) request, e.g. http://proxy.corp:8080
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+    // Most recently opened thread ids, newest first. Saved in recent_threads.json so opening a
+    // thread never rewrites configs.json; only read from configs.json written by older versions
+    #[serde(default, skip_serializing)]
+    pub recent_threads: Vec<String>,
+    // Cap on outbound LLM requests in flight at once; None uses the built-in default
+    #[serde(default)]
+    pub max_concurrent_requests: Option<usize>,
+}
+
+// Default location of the data directory (~/.pulsar-studio)
+pub fn default_data_root() -> PathBuf {
+    let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+    home.join(".pulsar-studio")
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
-        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
         Self {
+            config_version: CONFIG_VERSION,
             llm_providers: vec![
                 LLMProvider {
                     name: "GPT-4".to_string(),
@@ -39,6 +92,8 @@ impl Default for AppConfig {
                     max_tokens: Some(4000),
                     think: false,
                     alias: "gpt-4".to_string(),
+                    headers: None,
+                    locked: false,
                 },
                 LLMProvider {
                     name: "Local Ollama".to_string(),
@@ -50,15 +105,35 @@ impl Default for AppConfig {
                     max_tokens: Some(2000),
                     think: true,
                     alias: "local-llama".to_string(),
+                    headers: None,
+                    locked: false,
                 }
             ],
-            data_root: home.join(".pulsar-studio"),
+            data_root: default_data_root(),
             theme: "light".to_string(),
             language: "en".to_string(),
+            debug_llm: false,
+            model_prices: vec![],
+            update_endpoint: None,
+            default_planner_alias: None,
+            default_decider_alias: None,
+            compress: false,
+            agent_script: None,
+            http_proxy: None,
+            recent_threads: vec![],
+            max_concurrent_requests: None,
         }
     }
 }
 
+// USD price per million tokens for models whose name starts with `model`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub model: String,
+    pub input_per_million: f64,
+    pub output_per_million: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LLMProvider {
     pub name: String,
@@ -70,6 +145,12 @@ pub struct LLMProvider {
     pub max_tokens: Option<u32>,
     pub think: bool,
     pub alias: String,
+    // Extra headers sent with every request, e.g. HTTP-Referer/X-Title for OpenRouter
+    #[serde(default)]
+    pub headers: Option<HashMap<String, String>>,
+    // Locked providers can't be edited or deleted; only set_provider_locked (admin mode) changes this
+    #[serde(default)]
+    pub locked: bool,
 }
 
 // Thread and Agent structures
@@ -82,15 +163,63 @@ pub struct Thread {
     pub updated_at: String,
     pub agent_state: Option<AgentState>,
     pub config: Option<ThreadConfig>,
+    // Free-form labels for organizing threads; older thread files have none
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ThreadConfig {
+    // Saved thread files use the frontend's camelCase keys
+    #[serde(alias = "plannerLlmAlias")]
     pub planner_llm_alias: String,
+    #[serde(alias = "deciderLlmAlias")]
     pub decider_llm_alias: String,
+    #[serde(alias = "selectedKnowledge")]
     pub selected_knowledge: Vec<String>,
+    #[serde(alias = "selectedGuides")]
     pub selected_guides: Vec<String>,
+    #[serde(alias = "selectedActions")]
     pub selected_actions: Vec<String>,
+    // Mode the UI pre-selects when asking the agent in this thread
+    #[serde(default, alias = "executionMode")]
+    pub execution_mode: ExecutionMode,
+    // Sent to the agent with every request in this thread; older thread files have none
+    #[serde(default, alias = "systemPrompt")]
+    pub system_prompt: Option<String>,
+}
+
+// How the agent runs actions: asking for confirmation first, or on its own
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionMode {
+    #[default]
+    Interactive,
+    Auto,
+}
+
+impl ExecutionMode {
+    pub const ALL: [ExecutionMode; 2] = [ExecutionMode::Interactive, ExecutionMode::Auto];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionMode::Interactive => "interactive",
+            ExecutionMode::Auto => "auto",
+        }
+    }
+}
+
+impl std::str::FromStr for ExecutionMode {
+    type Err = String;
+
+    fn from_str(mode: &str) -> Result<Self, Self::Err> {
+        ExecutionMode::ALL.into_iter()
+            .find(|m| m.as_str() == mode.trim())
+            .ok_or_else(|| {
+                let valid: Vec<&str> = ExecutionMode::ALL.iter().map(|m| m.as_str()).collect();
+                format!("Unknown execution mode '{}'. Valid modes: {}", mode, valid.join(", "))
+            })
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,47 +231,192 @@ pub struct AgentState {
 }
 
 fn main() {
+    let mut config = AppConfig::default();
+    if let Some(data_root) = commands::data_root_from_env().or_else(commands::load_data_root_override) {
+        config.data_root = data_root;
+    }
+    let read_only = commands::is_data_root_read_only(&config.data_root);
+
     tauri::Builder::default()
         .manage(AppState {
-            config: Mutex::new(AppConfig::default()),
+            config: Mutex::new(config),
+            read_only: AtomicBool::new(read_only),
+            action_locks: commands::ActionLocks::default(),
+            tool_support: Mutex::new(HashMap::new()),
+            running_actions: commands::RunningActions::default(),
+            request_limiter: commands::RequestLimiter::default(),
+            integrity_report: Mutex::new(None),
+        })
+        // Scan data_root for unparseable JSON in the background; the UI picks the result up with
+        // get_integrity_report, later scans also arrive as data-integrity events
+        .setup(|app| {
+            let app_handle = app.handle();
+            let data_root = app.state::<AppState>().config.lock().unwrap().data_root.clone();
+            std::thread::spawn(move || {
+                commands::check_data_integrity(&app_handle, &data_root);
+            });
+            Ok(())
+        })
+        // Don't leave action processes running after the window goes away
+        .on_window_event(|event| {
+            if let tauri::WindowEvent::CloseRequested { .. } = event.event() {
+                let stopped = event.window().state::<AppState>().running_actions.cancel_all();
+                if stopped > 0 {
+                    println!("Stopped {} running action(s) on window close", stopped);
+                }
+            }
         })
         .invoke_handler(tauri::generate_handler![
             commands::get_config,
+            commands::get_effective_config,
             commands::initialize_data_directory,
+            commands::get_data_root,
+            commands::open_data_dir,
+            commands::reveal_resource,
+            commands::read_resource_raw,
+            commands::write_resource_raw,
+            commands::validate_resource_json,
+            commands::set_data_root,
             commands::validate_directory_permissions,
+            commands::is_read_only,
             commands::create_thread,
+            commands::get_thread,
+            commands::get_recent_threads,
+            commands::list_threads,
+            commands::list_threads_by_tag,
+            commands::set_thread_tags,
+            commands::get_thread_fingerprint,
+            commands::duplicate_thread,
+            commands::get_thread_execution_mode,
+            commands::set_thread_execution_mode,
+            commands::get_thread_system_prompt,
+            commands::set_thread_system_prompt,
+            commands::prune_threads,
+            commands::resolve_thread_providers,
+            commands::find_dangling_aliases,
+            commands::remap_alias,
+            commands::validate_thread_selections,
+            commands::export_thread_bundle,
+            commands::import_thread_bundle,
             commands::agent_ask,
             commands::get_agent_report,
+            commands::get_run_metrics,
+            commands::get_provider_latency_stats,
+            commands::get_provider_usage_stats,
             commands::submit_feedback,
+            commands::list_feedback,
             commands::get_all_llm_providers,
             commands::add_llm_provider,
             commands::update_llm_provider,
+            commands::patch_llm_provider,
             commands::delete_llm_provider,
+            commands::rename_provider_alias,
+            commands::set_provider_locked,
+            commands::clear_provider_key,
+            commands::rotate_provider_key,
+            commands::import_keys_from_env_file,
+            commands::set_default_providers,
             commands::test_llm_provider,
+            commands::test_all_providers,
+            commands::probe_tool_support,
+            commands::compare_providers,
+            commands::list_ollama_models,
+            commands::list_provider_models,
+            commands::normalize_base_url,
+            commands::estimate_tokens,
+            commands::estimate_thread_context_size,
+            commands::get_model_limits,
+            commands::get_model_prices,
+            commands::set_model_price,
             commands::export_providers,
             commands::import_providers,
+            commands::import_providers_from_file,
             commands::save_config_to_file_public,
             commands::load_config_from_file,
+            commands::list_config_snapshots,
+            commands::diff_config_snapshot,
+            commands::restore_config_snapshot,
+            commands::get_schema,
+            commands::validate_all,
+            commands::run_integrity_check,
+            commands::get_integrity_report,
             commands::list_guides,
             commands::load_guide,
             commands::save_guide,
+            commands::save_guide_text,
             commands::delete_guide,
+            commands::add_guide_entry,
+            commands::update_guide_entry,
+            commands::delete_guide_entry,
+            commands::dedupe_guide,
+            commands::normalize_entry_names,
             commands::create_guides_directory,
             commands::list_knowledge,
             commands::load_knowledge,
             commands::save_knowledge,
+            commands::save_knowledge_text,
+            commands::import_knowledge_from_markdown,
+            commands::export_knowledge_to_markdown,
+            commands::export_guide_to_markdown,
+            commands::diff_guides,
+            commands::list_guide_action_references,
+            commands::list_templates,
+            commands::install_template,
             commands::delete_knowledge,
+            commands::add_knowledge_entry,
+            commands::append_knowledge_entry,
+            commands::update_knowledge_entry,
+            commands::delete_knowledge_entry,
+            commands::move_knowledge_entry,
+            commands::dedupe_knowledge,
+            commands::merge_knowledge,
             commands::create_knowledge_directory,
             commands::list_actions,
+            commands::search_actions,
+            commands::get_resource_summaries,
+            commands::reformat_all,
+            commands::list_domains,
+            commands::get_resource_etag,
+            commands::get_all_etags,
+            commands::export_all_actions,
+            commands::import_all_actions,
             commands::import_action_directory,
+            commands::preview_action_import,
             commands::validate_action_directory,
             commands::delete_action,
+            commands::copy_action,
             commands::update_action_status,
+            commands::execute_action,
+            commands::cancel_all_runs,
+            commands::get_run_log,
             commands::get_action_status,
+            commands::get_action_arguments,
+            commands::lint_action,
+            commands::get_actions_health_summary,
+            commands::reset_action_status,
+            commands::list_trash,
+            commands::restore_from_trash,
+            commands::empty_trash,
             commands::set_theme,
+            commands::save_custom_theme,
+            commands::list_custom_themes,
+            commands::get_effective_theme,
             commands::set_language,
+            commands::list_languages,
+            commands::get_locale_strings,
+            commands::import_locale,
             commands::get_theme,
-            commands::get_language
+            commands::get_language,
+            commands::set_debug_llm,
+            commands::set_compress,
+            commands::get_app_version,
+            commands::check_for_updates,
+            commands::set_update_endpoint,
+            commands::set_http_proxy,
+            commands::set_max_concurrent_requests,
+            commands::check_agent_runtime,
+            commands::create_support_bundle,
+            commands::set_agent_script
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -9,6 +9,14 @@ import ActionsPage from './pages/ActionsPage';
 import { useAppStore } from './stores/useAppStore';
 import { useActionStore } from './stores/useActionStore';
 import { invoke } from '@tauri-apps/api/tauri';
+import { listen } from '@tauri-apps/api/event';
+import type { IntegrityReport } from './types';
+
+const reportCorruptFiles = (report: IntegrityReport | null) => {
+  if (report && report.corrupt_files.length > 0) {
+    console.warn('Unreadable data files:', report.corrupt_files);
+  }
+};
 
 // App component with data loading
 function App() {
@@ -45,6 +53,17 @@ function App() {
     initializeApp();
   }, [loadConfig, loadGuides, loadKnowledge, loadActions, setTheme, setLanguage]);
 
+  useEffect(() => {
+    // The startup integrity scan may finish before this listener exists, so also ask for its result
+    const unlisten = listen<IntegrityReport>('data-integrity', (event) => reportCorruptFiles(event.payload));
+    invoke<IntegrityReport | null>('get_integrity_report')
+      .then(reportCorruptFiles)
+      .catch((error) => console.error('Failed to get integrity report:', error));
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, []);
+
   return (
     <Router>
       <Layout>
@@ -158,7 +158,7 @@ const GuidesPage: React.FC = () => {
           ? {
               ...entry,
               plan: entry.plan.map((step, j) =>
-                j === stepIndex ? value : step
+                j !== stepIndex ? step : typeof step === 'string' ? value : { ...step, text: value }
               )
             }
           : entry
@@ -445,7 +445,7 @@ const GuidesPage: React.FC = () => {
                               </span>
                               <input
                                 type="text"
-                                value={step}
+                                value={typeof step === 'string' ? step : step.text}
                                 onChange={(e) => updatePlanStep(entryIndex, stepIndex, e.target.value)}
                                 style={{
                                   flex: 1,
@@ -29,6 +29,8 @@ const SettingsPage: React.FC = () => {
   const [showAddForm, setShowAddForm] = useState(false);
   const [editingInstance, setEditingInstance] = useState<string | null>(null);
   const [errors, setErrors] = useState<Record<string, string>>({});
+  // Skip imported providers identical to an existing one (kind, endpoint, model, temperature)
+  const [dedupeImports, setDedupeImports] = useState(true);
   const [newInstance, setNewInstance] = useState<Partial<LLMInstance>>({
     name: '',
     provider: 'openai_compatible',
@@ -189,8 +191,11 @@ const SettingsPage: React.FC = () => {
         };
 
         console.log('Adding instance:', instance);
-        await invoke('add_llm_provider', { provider: instance });
+        const warnings = await invoke<string[]>('add_llm_provider', { provider: instance, checkModel: true });
         console.log('Instance added successfully');
+        if (warnings.length > 0) {
+          alert(warnings.join('\n'));
+        }
         await loadInstances(); // Reload the list
 
         // Reset form
@@ -216,7 +221,7 @@ const SettingsPage: React.FC = () => {
   const deleteInstance = async (id: string) => {
     if (confirm('Are you sure you want to delete this instance?')) {
       try {
-        await invoke('delete_llm_provider', { id });
+        await invoke('delete_llm_provider', { id, force: false });
         await loadInstances(); // Reload the list
       } catch (err) {
         console.error('Failed to delete instance:', err);
@@ -263,7 +268,10 @@ const SettingsPage: React.FC = () => {
           api_key: newInstance.api_key
         };
 
-        await invoke('update_llm_provider', { id: editingInstance, provider: instance });
+        const warnings = await invoke<string[]>('update_llm_provider', { id: editingInstance, provider: instance });
+        if (warnings.length > 0) {
+          alert(warnings.join('\n'));
+        }
         await loadInstances();
         setEditingInstance(null);
         setShowAddForm(false);
@@ -345,10 +353,16 @@ const SettingsPage: React.FC = () => {
           const text = await file.text();
           try {
             console.log('Importing instances...');
-            const importedCount = await invoke<number>('import_providers', { providersJson: text });
-            console.log(`Imported ${importedCount} instances`);
+            const summary = await invoke<{ imported: number; skipped: number; warnings: string[]; fingerprint_duplicates: string[] }>('import_providers', { providersJson: text, dedupeByFingerprint: dedupeImports });
+            console.log(`Imported ${summary.imported} instances`);
             await loadInstances(); // Reload the list
-            setError(`Successfully imported ${importedCount} instances`);
+            setError(`Successfully imported ${summary.imported} instances`
+              + (summary.fingerprint_duplicates.length > 0
+                ? ` (skipped duplicates: ${summary.fingerprint_duplicates.join(', ')})`
+                : ''));
+            if (summary.warnings.length > 0) {
+              alert(summary.warnings.join('\n'));
+            }
           } catch (err) {
             console.error('Failed to import instances:', err);
             setError(`Failed to import instances: ${err}`);
@@ -537,6 +551,22 @@ const SettingsPage: React.FC = () => {
               {t('settings.llm.title')}
             </h3>
             <div style={{ display: 'flex', gap: '12px' }}>
+              <label style={{
+                display: 'flex',
+                alignItems: 'center',
+                gap: '6px',
+                color: 'var(--color-text-secondary)',
+                fontSize: '14px',
+                cursor: 'pointer'
+              }}>
+                <input
+                  type="checkbox"
+                  checked={dedupeImports}
+                  onChange={(e) => setDedupeImports(e.target.checked)}
+                  style={{ accentColor: 'var(--color-primary)' }}
+                />
+                Skip duplicates on import
+              </label>
               <button
                 onClick={importInstances}
                 style={{
@@ -164,7 +164,8 @@ export const useActionStore = create<ActionStore>((set, get) => ({
       set({ isLoading: true, error: null });
 
       await invoke('delete_action', {
-        actionName
+        actionName,
+        permanent: false
       });
 
       set({ isLoading: false });
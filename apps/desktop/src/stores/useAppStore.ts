@@ -129,11 +129,10 @@ export const useAppStore = create<AppStore>()(
       loadThreads: async () => {
         try {
           set({ is_loading: true, error: undefined });
-          // TODO: Implement loadThreads in Rust backend
-          // const threads = await invoke<Thread[]>('list_threads');
+          const threads = await invoke<Thread[]>('list_threads');
           set({
             is_loading: false,
-            // threads
+            threads
           });
         } catch (error) {
           set({ error: String(error), is_loading: false });
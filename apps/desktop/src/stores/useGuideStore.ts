@@ -2,10 +2,13 @@ import { create } from 'zustand';
 import { invoke } from '@tauri-apps/api/tauri';
 
 // Types based on the schema
+// A plan step is free text, or text linked to an action in the actions library
+export type PlanStep = string | { text: string; action?: string };
+
 export interface GuideEntry {
   name: string;
   description: string;
-  plan: string[];
+  plan: PlanStep[];
 }
 
 export interface GuideMeta {
@@ -172,7 +175,7 @@ export const useGuideStore = create<GuideStore>((set, get) => ({
     try {
       set({ isLoading: true, error: null });
 
-      await invoke('delete_guide', { filename });
+      await invoke('delete_guide', { filename, permanent: false });
 
       // Clear current guide if it's the one being deleted
       const { currentGuide } = get();
@@ -172,7 +172,7 @@ export const useKnowledgeStore = create<KnowledgeStore>((set, get) => ({
     try {
       set({ isLoading: true, error: null });
 
-      await invoke('delete_knowledge', { filename });
+      await invoke('delete_knowledge', { filename, permanent: false });
 
       // Clear current knowledge if it's the one being deleted
       const { currentKnowledge } = get();
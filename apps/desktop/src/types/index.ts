@@ -7,6 +7,7 @@ export interface Thread {
   updated_at: string;
   agent_state?: AgentState;
   config?: ThreadConfig;
+  tags?: string[];
 }
 
 export interface ThreadConfig {
@@ -15,6 +16,8 @@ export interface ThreadConfig {
   selectedKnowledge: string[];
   selectedGuides: string[];
   selectedActions: string[];
+  executionMode?: 'interactive' | 'auto';
+  systemPrompt?: string;
 }
 
 export interface AgentState {
@@ -58,6 +61,8 @@ export interface LLMProvider {
   max_tokens?: number;
   think: boolean;
   alias: string;
+  headers?: Record<string, string>;
+  locked?: boolean;
 }
 
 export interface AppConfig {
@@ -79,6 +84,8 @@ export interface ActionArgument {
   type: string;
   description: string;
   required: boolean;
+  default?: any;
+  enum?: any[];
 }
 
 export interface ActionResult {
@@ -106,7 +113,7 @@ export interface GuideEntry {
   id: string;
   name: string;
   description: string;
-  plan: string[];
+  plan: (string | { text: string; action?: string })[];
   file_path: string;
 }
 
@@ -163,6 +170,7 @@ export interface DirectoryPermissionResult {
   writable: boolean;
   isDirectory: boolean;
   error?: string;
+  inaccessible_path?: string;
 }
 
 export interface AgentAskRequest {
@@ -170,6 +178,8 @@ export interface AgentAskRequest {
   text: string;
   files: string[];
   execution_mode: 'interactive' | 'auto';
+  // Run this request in another directory without changing the thread
+  working_dir_override?: string;
 }
 
 export interface Feedback {
@@ -181,4 +191,10 @@ export interface Feedback {
   suggestions?: string;
   submitted_by: 'user' | 'system';
   ts: string;
+}
+
+export interface IntegrityReport {
+  checked_files: number;
+  // Paths are relative to the data root
+  corrupt_files: { path: string; error: string }[];
 }
\ No newline at end of file
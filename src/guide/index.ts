@@ -5,14 +5,16 @@
  * (keyword, vector, hybrid) to retrieve relevant guide entries.
  */
 
-import { readFile, access } from 'fs/promises';
+import { access } from 'fs/promises';
 import { join, dirname } from 'path';
 import { fileURLToPath } from 'url';
 import {
   GuideEntry,
   GuideFile,
-  ScreeningConfig
+  ScreeningConfig,
+  guideStepText
 } from '../types/index.js';
+import { readResourceText } from '../resource/index.js';
 
 /**
  * Manages planning guides from JSON files with screening support.
@@ -41,7 +43,7 @@ export class Guide {
         // Check if file exists
         await access(path);
 
-        const fileContent = await readFile(path, 'utf-8');
+        const fileContent = await readResourceText(path);
         const data = JSON.parse(fileContent);
 
         // Validate guide file structure
@@ -151,7 +153,7 @@ export class Guide {
     const scoredEntries: Array<[GuideEntry, number]> = [];
     for (const [entry, _] of entries) {
       // Combine name, description, and plan for scoring
-      const text = `${entry.name} ${entry.description} ${entry.plan.join(' ')}`.toLowerCase();
+      const text = `${entry.name} ${entry.description} ${entry.plan.map(guideStepText).join(' ')}`.toLowerCase();
       const textTerms = new Set(text.split(/\s+/));
 
       // Simple scoring: count matching terms
@@ -223,7 +225,7 @@ export class Guide {
       // Load the new guide file
       try {
         await access(path);
-        const fileContent = await readFile(path, 'utf-8');
+        const fileContent = await readResourceText(path);
         const data = JSON.parse(fileContent);
 
         if (!this.isValidGuideFile(data)) {
@@ -53,6 +53,7 @@ export type {
   Feedback,
 
   // Guide types
+  GuidePlanStep,
   GuideEntry,
   GuideFile,
 
@@ -75,6 +76,7 @@ export {
   validateScreeningConfig,
   validatePlan,
   validateDecision,
+  guideStepText,
   createDefaultScreeningConfig,
   createDefaultOnlineConfig,
   createDefaultExecutionConfig,
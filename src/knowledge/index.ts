@@ -5,7 +5,7 @@
  * online retrieval from web search or custom APIs.
  */
 
-import { readFile, access } from 'fs/promises';
+import { access } from 'fs/promises';
 import axios, { AxiosInstance } from 'axios';
 import {
   KnowledgeEntry,
@@ -13,6 +13,7 @@ import {
   ScreeningConfig,
   OnlineConfig
 } from '../types/index.js';
+import { readResourceText } from '../resource/index.js';
 
 /**
  * Manages knowledge entries from JSON files with local and online retrieval support.
@@ -53,7 +54,7 @@ export class Knowledge {
         // Check if file exists
         await access(path);
 
-        const fileContent = await readFile(path, 'utf-8');
+        const fileContent = await readResourceText(path);
         const data = JSON.parse(fileContent);
 
         // Validate knowledge file structure
@@ -338,7 +339,7 @@ export class Knowledge {
       // Load the new knowledge file
       try {
         await access(path);
-        const fileContent = await readFile(path, 'utf-8');
+        const fileContent = await readResourceText(path);
         const data = JSON.parse(fileContent);
 
         if (!this.isValidKnowledgeFile(data)) {
@@ -14,7 +14,8 @@ import {
   Progress,
   ActionResult,
   Observation,
-  validatePlan
+  validatePlan,
+  guideStepText
 } from '../types/index.js';
 
 /**
@@ -239,7 +240,7 @@ export class Planner {
       context += 'Relevant Guides:\n';
       for (const guide of guides) {
         context += `- ${guide.name}: ${guide.description}\n`;
-        context += `  Plan: ${guide.plan.join(' -> ')}\n`;
+        context += `  Plan: ${guide.plan.map(guideStepText).join(' -> ')}\n`;
       }
       context += '\n';
     }
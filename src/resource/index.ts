@@ -0,0 +1,17 @@
+/**
+ * Resource file reading for pulsar-agent-turbo.
+ *
+ * Guide and knowledge files are stored as plain JSON or, when the app's compress setting
+ * is on, gzipped as .json.gz.
+ */
+
+import { readFile } from 'fs/promises';
+import { gunzipSync } from 'zlib';
+
+export async function readResourceText(path: string): Promise<string> {
+  /** Read a guide/knowledge file as text, decompressing .json.gz files. */
+  const bytes = await readFile(path);
+  const content = (path.endsWith('.gz') ? gunzipSync(bytes) : bytes).toString('utf-8');
+  // Some Windows editors prefix a BOM, which JSON.parse rejects
+  return content.startsWith('\uFEFF') ? content.slice(1) : content;
+}
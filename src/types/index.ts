@@ -152,11 +152,14 @@ export interface AgentConfig {
 }
 
 // Guide types
+/** A guide plan step: free text, or text linked to an action in the actions library. */
+export type GuidePlanStep = string | { text: string; action?: string };
+
 export interface GuideEntry {
   /** A single guide entry. */
   name: string;
   description: string;
-  plan: string[];
+  plan: GuidePlanStep[];
 }
 
 export interface GuideFile {
@@ -286,6 +289,14 @@ export function validateDecision(decision: Decision): boolean {
   return true;
 }
 
+export function guideStepText(step: GuidePlanStep): string {
+  /** A guide plan step as one line of text; linked steps name their action. */
+  if (typeof step === 'string') {
+    return step;
+  }
+  return step.action ? `${step.text} (action: ${step.action})` : step.text;
+}
+
 // Default configurations
 export function createDefaultScreeningConfig(): ScreeningConfig {
   return {